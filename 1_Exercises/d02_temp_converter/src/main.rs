@@ -1,63 +1,137 @@
 // This brinds a module, use std::io::Read brinds the Read trait into scope.
-use std::io;
+use std::env;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process;
 
-fn get_temperature() -> f64{
-    let mut input_value = String::new();
-    io::stdin().read_line(&mut input_value).expect("Failed to read input");
+mod units;
 
-    let temp:f64 = match input_value.trim().parse(){
-        Ok(num) => num,
-        Err(_) => {
-            println!("Incorrect input");
-            return 0.0;
-        }
-    };
+use units::{format_number, ConversionError, Query, UNITS};
+
+/// Parse and run a single query, e.g. "100 km/h in m/s"
+fn run_query(input: &str) -> Result<String, ConversionError> {
+    let query = Query::parse(input)?;
+    let result = query.run()?;
+
+    Ok(format!(
+        "{} {} = {} {}",
+        format_number(query.value),
+        query.from.text,
+        format_number(result),
+        query.to.text
+    ))
+}
+
+fn print_units() {
+    let mut last = String::new();
 
-    temp
+    for unit in UNITS {
+        let dimension = unit.dimension.to_string();
+        if dimension != last {
+            println!("\n{}:", dimension);
+            last = dimension;
+        }
+        println!("  {}", unit.symbols.join(", "));
+    }
+    println!("\nCompound units can be written with '/', '*' and '^', e.g. km/h, kg*m/s^2, ft^3");
 }
 
-fn celcius_to_farenheit(){
-    let temp = get_temperature();
+/// Convert every line from stdin, used when the tool is piped into
+fn run_lines(reader: impl BufRead) -> bool {
+    let mut ok = true;
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("Failed to read input: {}", e);
+                return false;
+            }
+        };
 
-    if temp != 0.0 {
-        let farenheit = (temp * 9.0 / 5.0) + 32.0;
-        println!("{:.2} C is {:.2} F", temp, farenheit);
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match run_query(&line) {
+            Ok(result) => println!("{}", result),
+            Err(e) => {
+                eprintln!("{}", e);
+                ok = false;
+            }
+        }
     }
+
+    ok
 }
 
-fn farenheit_to_celcius(){
-    let temp = get_temperature();
+fn interactive() {
+    println!("Unit Converter");
+    println!("Enter a conversion like '100 km/h in m/s' or '98.6 F to C'");
+    println!("Type 'units' to list the known units, 'q' to quit");
+
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+
+        // Creation of a string variable
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Failed to read input: {}", e);
+                break;
+            }
+        }
 
-    if temp != 0.0 {
-        let celsius = (temp - 32.0) * 5.0 / 9.0;
-        println!("{:.2} F is {:.2} C", temp, celsius);
+        match input.trim() {
+            "" => continue,
+            "q" | "quit" | "exit" => break,
+            "units" => print_units(),
+            query => match run_query(query) {
+                Ok(result) => println!("{}", result),
+                Err(e) => println!("{}", e),
+            },
+        }
     }
 }
 
 fn main() {
-    println!("Temperature Converter");
-    println!("1. Celsius to Farenheit");
-    println!("2. Farenheit to Celcius");
-    println!("Select one option: ");
-
-    // Creation of a string variable
-    let mut choice = String::new();
-    io::stdin().read_line(&mut choice).expect("Failed to read input");
-
-    // Result<T, E> is an enum with two variants Ok(T) and Err(E)
-    let new_choice: u32 = match choice.trim().parse() {
-        Ok(num) => num,
-        Err(_) => {
-            println!("Invalid choice");
-            return;
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("Usage: d02_temp_converter [QUERY...]");
+        println!("  d02_temp_converter 100 km/h in m/s");
+        println!("  echo '32 F in C' | d02_temp_converter");
+        println!("  d02_temp_converter --units");
+        return;
+    }
+
+    if args.iter().any(|a| a == "--units") {
+        print_units();
+        return;
+    }
+
+    // Arguments form a single query, "-" reads queries from stdin
+    let ok = if !args.is_empty() && args != ["-"] {
+        match run_query(&args.join(" ")) {
+            Ok(result) => {
+                println!("{}", result);
+                true
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                false
+            }
         }
+    } else if !args.is_empty() || !io::stdin().is_terminal() {
+        run_lines(io::stdin().lock())
+    } else {
+        interactive();
+        true
     };
 
-    if new_choice == 1{
-        celcius_to_farenheit();
-    } else if new_choice == 2 {
-        farenheit_to_celcius();
-    } else {
-        println!("Invalid choice!");
+    if !ok {
+        process::exit(1);
     }
 }
@@ -0,0 +1,369 @@
+use std::error::Error;
+use std::fmt;
+use std::num::{IntErrorKind, ParseIntError};
+
+/// Exponents of the base quantities a unit is made of.
+/// e.g. speed is length^1 * time^-1, pressure is mass^1 * length^-1 * time^-2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Dimension {
+    pub length: i8,
+    pub mass: i8,
+    pub time: i8,
+    pub temperature: i8,
+    pub data: i8,
+}
+
+const fn dim(length: i8, mass: i8, time: i8, temperature: i8, data: i8) -> Dimension {
+    Dimension { length, mass, time, temperature, data }
+}
+
+const LENGTH: Dimension = dim(1, 0, 0, 0, 0);
+const MASS: Dimension = dim(0, 1, 0, 0, 0);
+const TIME: Dimension = dim(0, 0, 1, 0, 0);
+const TEMPERATURE: Dimension = dim(0, 0, 0, 1, 0);
+const DATA: Dimension = dim(0, 0, 0, 0, 1);
+const VOLUME: Dimension = dim(3, 0, 0, 0, 0);
+const SPEED: Dimension = dim(1, 0, -1, 0, 0);
+const PRESSURE: Dimension = dim(-1, 1, -2, 0, 0);
+
+impl Dimension {
+    /// None when an exponent doesn't fit in an i8
+    fn mul(self, other: Dimension, power: i8) -> Option<Dimension> {
+        let exponent = |mine: i8, theirs: i8| theirs.checked_mul(power).and_then(|e| mine.checked_add(e));
+        Some(Dimension {
+            length: exponent(self.length, other.length)?,
+            mass: exponent(self.mass, other.mass)?,
+            time: exponent(self.time, other.time)?,
+            temperature: exponent(self.temperature, other.temperature)?,
+            data: exponent(self.data, other.data)?,
+        })
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        match *self {
+            LENGTH => Some("length"),
+            MASS => Some("mass"),
+            TIME => Some("time"),
+            TEMPERATURE => Some("temperature"),
+            DATA => Some("data size"),
+            VOLUME => Some("volume"),
+            SPEED => Some("speed"),
+            PRESSURE => Some("pressure"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = self.name() {
+            return write!(f, "{}", name);
+        }
+
+        let parts: Vec<String> = [
+            ("length", self.length),
+            ("mass", self.mass),
+            ("time", self.time),
+            ("temperature", self.temperature),
+            ("data", self.data),
+        ]
+        .iter()
+        .filter(|(_, exp)| *exp != 0)
+        .map(|(name, exp)| if *exp == 1 { name.to_string() } else { format!("{}^{}", name, exp) })
+        .collect();
+
+        if parts.is_empty() {
+            write!(f, "dimensionless")
+        } else {
+            write!(f, "{}", parts.join("*"))
+        }
+    }
+}
+
+/// A named unit: value_in_base = (value + offset) * factor
+/// Base units are metre, kilogram, second, kelvin and byte.
+#[derive(Debug)]
+pub struct Unit {
+    pub symbols: &'static [&'static str],
+    pub dimension: Dimension,
+    pub factor: f64,
+    pub offset: f64,
+}
+
+const fn unit(symbols: &'static [&'static str], dimension: Dimension, factor: f64) -> Unit {
+    Unit { symbols, dimension, factor, offset: 0.0 }
+}
+
+pub static UNITS: &[Unit] = &[
+    // Temperature
+    unit(&["K", "kelvin", "kelvins"], TEMPERATURE, 1.0),
+    Unit { symbols: &["C", "°C", "celsius", "celcius"], dimension: TEMPERATURE, factor: 1.0, offset: 273.15 },
+    Unit { symbols: &["F", "°F", "fahrenheit", "farenheit"], dimension: TEMPERATURE, factor: 5.0 / 9.0, offset: 459.67 },
+    unit(&["R", "°R", "rankine"], TEMPERATURE, 5.0 / 9.0),
+    // Length
+    unit(&["m", "meter", "meters", "metre", "metres"], LENGTH, 1.0),
+    unit(&["km", "kilometer", "kilometers", "kilometre", "kilometres"], LENGTH, 1000.0),
+    unit(&["cm", "centimeter", "centimeters"], LENGTH, 0.01),
+    unit(&["mm", "millimeter", "millimeters"], LENGTH, 0.001),
+    unit(&["um", "µm", "micrometer", "micrometers"], LENGTH, 1e-6),
+    unit(&["nm", "nanometer", "nanometers"], LENGTH, 1e-9),
+    unit(&["in", "inch", "inches"], LENGTH, 0.0254),
+    unit(&["ft", "foot", "feet"], LENGTH, 0.3048),
+    unit(&["yd", "yard", "yards"], LENGTH, 0.9144),
+    unit(&["mi", "mile", "miles"], LENGTH, 1609.344),
+    unit(&["nmi", "nautical_mile", "nautical_miles"], LENGTH, 1852.0),
+    // Mass
+    unit(&["kg", "kilogram", "kilograms"], MASS, 1.0),
+    unit(&["g", "gram", "grams"], MASS, 0.001),
+    unit(&["mg", "milligram", "milligrams"], MASS, 1e-6),
+    unit(&["t", "tonne", "tonnes"], MASS, 1000.0),
+    unit(&["lb", "lbs", "pound", "pounds"], MASS, 0.453_592_37),
+    unit(&["oz", "ounce", "ounces"], MASS, 0.028_349_523_125),
+    unit(&["st", "stone", "stones"], MASS, 6.350_293_18),
+    // Time
+    unit(&["s", "sec", "second", "seconds"], TIME, 1.0),
+    unit(&["ms", "millisecond", "milliseconds"], TIME, 0.001),
+    unit(&["min", "minute", "minutes"], TIME, 60.0),
+    unit(&["h", "hr", "hour", "hours"], TIME, 3600.0),
+    unit(&["d", "day", "days"], TIME, 86_400.0),
+    unit(&["wk", "week", "weeks"], TIME, 604_800.0),
+    // Volume
+    unit(&["L", "l", "liter", "liters", "litre", "litres"], VOLUME, 0.001),
+    unit(&["mL", "ml", "milliliter", "milliliters"], VOLUME, 1e-6),
+    unit(&["cL", "cl", "centiliter", "centiliters"], VOLUME, 1e-5),
+    unit(&["dL", "dl", "deciliter", "deciliters"], VOLUME, 1e-4),
+    unit(&["gal", "gallon", "gallons"], VOLUME, 0.003_785_411_784),
+    unit(&["qt", "quart", "quarts"], VOLUME, 0.000_946_352_946),
+    unit(&["pt", "pint", "pints"], VOLUME, 0.000_473_176_473),
+    unit(&["cup", "cups"], VOLUME, 0.000_236_588_236_5),
+    unit(&["floz", "fl_oz", "fluid_ounce", "fluid_ounces"], VOLUME, 2.957_352_956_25e-5),
+    unit(&["tbsp", "tablespoon", "tablespoons"], VOLUME, 1.478_676_478_125e-5),
+    unit(&["tsp", "teaspoon", "teaspoons"], VOLUME, 4.928_921_593_75e-6),
+    // Speed
+    unit(&["mph"], SPEED, 0.447_04),
+    unit(&["kph", "kmh"], SPEED, 1000.0 / 3600.0),
+    unit(&["kn", "kt", "knot", "knots"], SPEED, 1852.0 / 3600.0),
+    // Data sizes
+    unit(&["B", "byte", "bytes"], DATA, 1.0),
+    unit(&["b", "bit", "bits"], DATA, 0.125),
+    unit(&["kB", "KB", "kilobyte", "kilobytes"], DATA, 1e3),
+    unit(&["MB", "megabyte", "megabytes"], DATA, 1e6),
+    unit(&["GB", "gigabyte", "gigabytes"], DATA, 1e9),
+    unit(&["TB", "terabyte", "terabytes"], DATA, 1e12),
+    unit(&["KiB", "kibibyte", "kibibytes"], DATA, 1024.0),
+    unit(&["MiB", "mebibyte", "mebibytes"], DATA, 1_048_576.0),
+    unit(&["GiB", "gibibyte", "gibibytes"], DATA, 1_073_741_824.0),
+    unit(&["TiB", "tebibyte", "tebibytes"], DATA, 1_099_511_627_776.0),
+    unit(&["kb", "Kb", "kilobit", "kilobits"], DATA, 125.0),
+    unit(&["Mb", "megabit", "megabits"], DATA, 125_000.0),
+    unit(&["Gb", "gigabit", "gigabits"], DATA, 125_000_000.0),
+    // Pressure
+    unit(&["Pa", "pascal", "pascals"], PRESSURE, 1.0),
+    unit(&["hPa"], PRESSURE, 100.0),
+    unit(&["kPa"], PRESSURE, 1000.0),
+    unit(&["MPa"], PRESSURE, 1e6),
+    unit(&["bar"], PRESSURE, 100_000.0),
+    unit(&["mbar"], PRESSURE, 100.0),
+    unit(&["atm", "atmosphere", "atmospheres"], PRESSURE, 101_325.0),
+    unit(&["psi"], PRESSURE, 6_894.757_293_168),
+    unit(&["mmHg", "torr"], PRESSURE, 133.322_387_415),
+    unit(&["inHg"], PRESSURE, 3_386.388_666_6),
+];
+
+/// Errors produced while parsing or converting a query
+#[derive(Debug, PartialEq)]
+pub enum ConversionError {
+    InvalidQuery(String),
+    InvalidNumber(String),
+    UnknownUnit(String),
+    /// Offset units (°C, °F) can't be multiplied or divided by other units
+    OffsetInCompound(String),
+    IncompatibleDimensions { from: String, from_dim: Dimension, to: String, to_dim: Dimension },
+    BelowAbsoluteZero(f64),
+    /// The exponents of a unit expression went past what can be stored
+    ExponentTooLarge(String),
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversionError::InvalidQuery(q) => {
+                write!(f, "Invalid query '{}', expected e.g. '100 km/h in m/s'", q)
+            }
+            ConversionError::InvalidNumber(n) => write!(f, "Invalid number '{}'", n),
+            ConversionError::UnknownUnit(u) => write!(f, "Unknown unit '{}'", u),
+            ConversionError::OffsetInCompound(u) => {
+                write!(f, "Unit '{}' has an offset and can't be part of a compound unit", u)
+            }
+            ConversionError::IncompatibleDimensions { from, from_dim, to, to_dim } => write!(
+                f,
+                "Can't convert '{}' ({}) to '{}' ({})",
+                from, from_dim, to, to_dim
+            ),
+            ConversionError::BelowAbsoluteZero(v) => {
+                write!(f, "Temperature {} is below absolute zero", format_number(*v))
+            }
+            ConversionError::ExponentTooLarge(u) => write!(f, "Exponents in '{}' are too large", u),
+        }
+    }
+}
+
+impl Error for ConversionError {}
+
+/// A unit expression such as `km/h` or `kg*m/s^2`, reduced to a factor and dimension
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitExpr {
+    pub text: String,
+    pub dimension: Dimension,
+    pub factor: f64,
+    pub offset: f64,
+}
+
+fn lookup(symbol: &str) -> Option<&'static Unit> {
+    // Exact match first so that "b"/"B" and "Mb"/"MB" stay distinct
+    UNITS
+        .iter()
+        .find(|u| u.symbols.contains(&symbol))
+        .or_else(|| {
+            let lower = symbol.to_lowercase();
+            UNITS.iter().find(|u| {
+                u.symbols.iter().any(|s| s.chars().count() > 3 && s.to_lowercase() == lower)
+            })
+        })
+}
+
+/// Parse a unit expression: simple units joined by `*` or `/`, each with an optional `^n`
+pub fn parse_unit(text: &str) -> Result<UnitExpr, ConversionError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(ConversionError::UnknownUnit(String::new()));
+    }
+
+    let mut dimension = Dimension::default();
+    let mut factor = 1.0;
+    let mut offset = 0.0;
+    let mut sign: i8 = 1;
+    let mut start = 0;
+
+    // A trailing '*' flushes the last part
+    let separators: Vec<(usize, char)> = text
+        .char_indices()
+        .filter(|&(_, c)| c == '*' || c == '/')
+        .chain([(text.len(), '*')])
+        .collect();
+
+    for (parts, (i, c)) in separators.into_iter().enumerate() {
+        let part = text[start..i].trim();
+        let (symbol, power) = match part.split_once('^') {
+            Some((s, p)) => {
+                let p: i8 = p.trim().parse().map_err(|e: ParseIntError| match e.kind() {
+                    IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => ConversionError::ExponentTooLarge(text.to_string()),
+                    _ => ConversionError::UnknownUnit(part.to_string()),
+                })?;
+                (s.trim(), p)
+            }
+            None => (part, 1),
+        };
+
+        let found = lookup(symbol).ok_or_else(|| ConversionError::UnknownUnit(symbol.to_string()))?;
+        if found.offset != 0.0 && (parts > 0 || power != 1 || sign != 1 || i != text.len()) {
+            return Err(ConversionError::OffsetInCompound(symbol.to_string()));
+        }
+
+        let too_large = || ConversionError::ExponentTooLarge(text.to_string());
+        let power = power.checked_mul(sign).ok_or_else(too_large)?;
+        dimension = dimension.mul(found.dimension, power).ok_or_else(too_large)?;
+        factor *= found.factor.powi(power as i32);
+        offset = found.offset;
+
+        sign = if c == '/' { -1 } else { 1 };
+        start = i + c.len_utf8();
+    }
+
+    Ok(UnitExpr { text: text.to_string(), dimension, factor, offset })
+}
+
+/// Convert `value` from one unit expression into another
+pub fn convert(value: f64, from: &UnitExpr, to: &UnitExpr) -> Result<f64, ConversionError> {
+    if from.dimension != to.dimension {
+        return Err(ConversionError::IncompatibleDimensions {
+            from: from.text.clone(),
+            from_dim: from.dimension,
+            to: to.text.clone(),
+            to_dim: to.dimension,
+        });
+    }
+
+    let base = (value + from.offset) * from.factor;
+    if from.dimension == TEMPERATURE && base < 0.0 {
+        return Err(ConversionError::BelowAbsoluteZero(value));
+    }
+
+    Ok(base / to.factor - to.offset)
+}
+
+/// A parsed query like `100 km/h in m/s`
+#[derive(Debug, PartialEq)]
+pub struct Query {
+    pub value: f64,
+    pub from: UnitExpr,
+    pub to: UnitExpr,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, ConversionError> {
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+
+        // The last separator with a unit on both sides, so that "12 in in cm"
+        // reads as inches to centimeters and "5 m in in" as meters to inches
+        let sep = (1..tokens.len().saturating_sub(1))
+            .rev()
+            .find(|&i| matches!(tokens[i].to_lowercase().as_str(), "in" | "to" | "->" | "as"))
+            .ok_or_else(|| ConversionError::InvalidQuery(input.trim().to_string()))?;
+
+        let left = tokens[..sep].join(" ");
+        let right = tokens[sep + 1..].join("");
+
+        // The number may be glued to the unit: "100km/h"
+        let split = left
+            .char_indices()
+            .find(|&(i, c)| {
+                !(c.is_ascii_digit() || c == '.' || c == '_' || ((c == '-' || c == '+') && i == 0)
+                    || ((c == 'e' || c == 'E') && left[i + 1..].starts_with(|n: char| n.is_ascii_digit() || n == '-')))
+            })
+            .map(|(i, _)| i)
+            .unwrap_or(left.len());
+
+        let (number, unit) = left.split_at(split);
+        if unit.trim().is_empty() {
+            return Err(ConversionError::InvalidQuery(input.trim().to_string()));
+        }
+
+        let value: f64 = number
+            .replace('_', "")
+            .parse()
+            .map_err(|_| ConversionError::InvalidNumber(number.to_string()))?;
+
+        Ok(Query {
+            value,
+            from: parse_unit(&unit.replace(' ', ""))?,
+            to: parse_unit(&right)?,
+        })
+    }
+
+    pub fn run(&self) -> Result<f64, ConversionError> {
+        convert(self.value, &self.from, &self.to)
+    }
+}
+
+/// Print numbers without float noise: 27.777778 rather than 27.77777777777778
+pub fn format_number(value: f64) -> String {
+    let abs = value.abs();
+    if abs != 0.0 && !(1e-4..1e15).contains(&abs) {
+        return format!("{:e}", value);
+    }
+
+    let text = format!("{:.6}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_string() } else { text.to_string() }
+}
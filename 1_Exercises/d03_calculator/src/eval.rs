@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::parser::{BinaryOp, CalcError, Expr, Statement};
+use crate::value::{
+    add, division, exact_function, factorial, multiplication, power, remainder, substract, Mode, Value,
+};
+
+/// Built-in functions: name, number of arguments, implementation
+type Builtin = (&'static str, usize, fn(&[f64]) -> f64);
+
+pub const FUNCTIONS: &[Builtin] = &[
+    ("sqrt", 1, |a| a[0].sqrt()),
+    ("cbrt", 1, |a| a[0].cbrt()),
+    ("abs", 1, |a| a[0].abs()),
+    ("sin", 1, |a| a[0].sin()),
+    ("cos", 1, |a| a[0].cos()),
+    ("tan", 1, |a| a[0].tan()),
+    ("asin", 1, |a| a[0].asin()),
+    ("acos", 1, |a| a[0].acos()),
+    ("atan", 1, |a| a[0].atan()),
+    ("exp", 1, |a| a[0].exp()),
+    ("ln", 1, |a| a[0].ln()),
+    ("log", 1, |a| a[0].log10()),
+    ("log2", 1, |a| a[0].log2()),
+    ("floor", 1, |a| a[0].floor()),
+    ("ceil", 1, |a| a[0].ceil()),
+    ("round", 1, |a| a[0].round()),
+    ("min", 2, |a| a[0].min(a[1])),
+    ("max", 2, |a| a[0].max(a[1])),
+    ("pow", 2, |a| a[0].powf(a[1])),
+];
+
+pub const CONSTANTS: &[(&str, f64)] = &[("pi", std::f64::consts::PI), ("e", std::f64::consts::E)];
+
+/// Calculator state: user variables, the `ans` register and the number mode
+pub struct Calculator {
+    pub variables: HashMap<String, Value>,
+    pub ans: Value,
+    pub mode: Mode,
+}
+
+impl Calculator {
+    pub fn new() -> Self {
+        Calculator { variables: HashMap::new(), ans: Value::zero(), mode: Mode::Float }
+    }
+
+    pub fn format(&self, value: &Value) -> String {
+        self.mode.format(value)
+    }
+
+    /// Run a parsed line, storing the result in `ans` (and the variable when assigning)
+    pub fn execute(&mut self, statement: &Statement) -> Result<Value, CalcError> {
+        let value = match statement {
+            Statement::Assign(name, expr) => {
+                if name == "ans" || CONSTANTS.iter().any(|(c, _)| c == name) {
+                    return Err(CalcError::new(format!("'{}' can't be assigned", name), 1));
+                }
+                let value = self.eval(expr)?;
+                self.variables.insert(name.clone(), value.clone());
+                value
+            }
+            Statement::Expr(expr) => self.eval(expr)?,
+        };
+
+        self.ans = value.clone();
+        Ok(value)
+    }
+
+    pub fn eval(&self, expr: &Expr) -> Result<Value, CalcError> {
+        match expr {
+            Expr::Number(text, column) => Value::parse(text, self.mode.is_exact())
+                .ok_or_else(|| CalcError::new(format!("invalid number '{}'", text), *column)),
+            Expr::Variable(name, column) => self.lookup(name, *column),
+            Expr::Negate(inner, _) => Ok(self.eval(inner)?.negate()),
+            Expr::Factorial(inner, column) => factorial(self.eval(inner)?, *column),
+            Expr::Binary(op, lhs, rhs, column) => {
+                let a = self.eval(lhs)?;
+                let b = self.eval(rhs)?;
+                match op {
+                    BinaryOp::Add => Ok(add(a, b)),
+                    BinaryOp::Sub => Ok(substract(a, b)),
+                    BinaryOp::Mul => Ok(multiplication(a, b)),
+                    BinaryOp::Div => division(a, b, *column),
+                    BinaryOp::Rem => remainder(a, b, *column),
+                    BinaryOp::Pow => power(a, b, *column),
+                }
+            }
+            Expr::Call(name, args, column) => {
+                let (_, arity, function) = FUNCTIONS
+                    .iter()
+                    .find(|(f, _, _)| f == name)
+                    .ok_or_else(|| CalcError::new(format!("unknown function '{}'", name), *column))?;
+
+                if args.len() != *arity {
+                    return Err(CalcError::new(
+                        format!("'{}' expects {} argument(s), got {}", name, arity, args.len()),
+                        *column,
+                    ));
+                }
+
+                let values = args.iter().map(|a| self.eval(a)).collect::<Result<Vec<_>, _>>()?;
+                if let Some(result) = exact_function(name, &values) {
+                    return Ok(result);
+                }
+
+                let floats: Vec<f64> = values.iter().map(Value::to_f64).collect();
+                let result = function(&floats);
+                if result.is_nan() {
+                    return Err(CalcError::new(format!("'{}' is undefined for this input", name), *column));
+                }
+                Ok(Value::Float(result))
+            }
+        }
+    }
+
+    fn lookup(&self, name: &str, column: usize) -> Result<Value, CalcError> {
+        if name == "ans" {
+            return Ok(self.ans.clone());
+        }
+        if let Some(value) = self.variables.get(name) {
+            return Ok(value.clone());
+        }
+        if let Some((_, value)) = CONSTANTS.iter().find(|(c, _)| *c == name) {
+            return Ok(Value::Float(*value));
+        }
+        Err(CalcError::new(format!("unknown variable '{}'", name), column))
+    }
+}
@@ -1,70 +1,92 @@
-use std::io;
+use std::io::{self, Write};
 
-fn add(a: f64, b:f64) -> f64{
-    a + b
-}
+mod eval;
+mod parser;
+mod value;
 
-fn substract(a: f64, b:f64) -> f64{
-    a - b
-}
+use eval::{Calculator, CONSTANTS, FUNCTIONS};
+use value::{MAX_PLACES, Mode};
+
+const PROMPT: &str = "> ";
 
-fn multiplication(a: f64, b:f64) -> f64{
-    a * b
+fn print_help() {
+    println!("Operators: + - * / % ^ (or **) and postfix ! for factorial");
+    println!("Assign with 'x = 3', the last result is stored in 'ans'");
+
+    let functions: Vec<&str> = FUNCTIONS.iter().map(|(name, _, _)| *name).collect();
+    let constants: Vec<&str> = CONSTANTS.iter().map(|(name, _)| *name).collect();
+    println!("Functions: {}", functions.join(", "));
+    println!("Constants: {}", constants.join(", "));
+    println!("Modes: 'mode float', 'mode rational' (exact fractions), 'mode decimal N' (exact, N places, at most {})", MAX_PLACES);
+    println!("Commands: vars, mode, help, quit");
 }
 
-fn division(a: f64, b:f64) -> f64{
-    if b == 0.0 {
-        println!("Division by zero not valid!");
-        std::process::exit(1);
+fn print_vars(calc: &Calculator) {
+    println!("ans = {}", calc.format(&calc.ans));
+
+    let mut names: Vec<&String> = calc.variables.keys().collect();
+    names.sort();
+    for name in names {
+        println!("{} = {}", name, calc.format(&calc.variables[name]));
     }
-    a / b
 }
 
 fn main() {
     println!("Calculator: ");
-    println!("Emter your expression: ");
+    println!("Enter your expression (type 'help' for more, 'quit' to exit): ");
 
-    let mut input = String::new();
+    let mut calc = Calculator::new();
 
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Failed to read input!");
+    loop {
+        print!("{}", PROMPT);
+        io::stdout().flush().unwrap();
 
-    let exp: Vec<&str> = input.trim().split_whitespace().collect();
-
-    if exp.len() != 3 {
-        println!("Expression invalid!");
-        return;
-    }
-
-    let a:f64 = match exp[0].parse() {
-        Ok(num) => num,
-        Err(_) => {
-            println!("Invalid number a");
-            return;
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Failed to read input: {}", e);
+                break;
+            }
         }
-    };
 
-    let operator = exp[1];
-
-    let b:f64 = match exp[2].parse() {
-        Ok(num) => num,
-        Err(_) => {
-                println!("Invalid number b");
-                return;
+        let line = input.trim_end();
+        match line.trim() {
+            "" => continue,
+            "quit" | "exit" | "q" => break,
+            "help" => {
+                print_help();
+                continue;
             }
-    };
-
-    let result: f64 = match operator {
-        "+" => add(a,b),
-        "-" => substract(a,b),
-        "*" => multiplication(a,b),
-        "/" => division(a,b),
-        _ => {
-            println!("Invalid operator!");
-            return;
+            "vars" => {
+                print_vars(&calc);
+                continue;
+            }
+            "mode" => {
+                println!("Mode: {}", calc.mode);
+                continue;
+            }
+            other if other.starts_with("mode ") => {
+                match Mode::parse(&other["mode ".len()..]) {
+                    Ok(mode) => {
+                        calc.mode = mode;
+                        println!("Mode: {}", calc.mode);
+                    }
+                    Err(e) => println!("{}", e),
+                }
+                continue;
+            }
+            _ => {}
         }
-    };
 
-    println!("Result: {:.2}", result);
+        match parser::parse(line).and_then(|statement| calc.execute(&statement)) {
+            Ok(result) => println!("{}", calc.format(&result)),
+            Err(e) => {
+                // Point at the offending column, right below what was typed
+                println!("{}^", " ".repeat(PROMPT.len() + e.column - 1));
+                println!("{}", e);
+            }
+        }
+    }
 }
@@ -0,0 +1,334 @@
+use std::error::Error;
+use std::fmt;
+
+/// Error with the 1-based column where it happened
+#[derive(Debug, PartialEq)]
+pub struct CalcError {
+    pub message: String,
+    pub column: usize,
+}
+
+impl CalcError {
+    pub fn new(message: impl Into<String>, column: usize) -> Self {
+        CalcError { message: message.into(), column }
+    }
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error at column {}: {}", self.column, self.message)
+    }
+}
+
+impl Error for CalcError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Number(String),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Caret,
+    Bang,
+    LParen,
+    RParen,
+    Comma,
+    Assign,
+    End,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenKind::Number(n) => write!(f, "number '{}'", n),
+            TokenKind::Ident(name) => write!(f, "'{}'", name),
+            TokenKind::Plus => write!(f, "'+'"),
+            TokenKind::Minus => write!(f, "'-'"),
+            TokenKind::Star => write!(f, "'*'"),
+            TokenKind::Slash => write!(f, "'/'"),
+            TokenKind::Percent => write!(f, "'%'"),
+            TokenKind::Caret => write!(f, "'^'"),
+            TokenKind::Bang => write!(f, "'!'"),
+            TokenKind::LParen => write!(f, "'('"),
+            TokenKind::RParen => write!(f, "')'"),
+            TokenKind::Comma => write!(f, "','"),
+            TokenKind::Assign => write!(f, "'='"),
+            TokenKind::End => write!(f, "end of input"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub column: usize,
+}
+
+/// Split the input into tokens, remembering the column of each one
+pub fn tokenize(input: &str) -> Result<Vec<Token>, CalcError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == '_') {
+                i += 1;
+            }
+            // Exponent part: 1e10, 2.5E-3
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+
+            let text: String = chars[start..i].iter().filter(|c| **c != '_').collect();
+            if text.matches('.').count() > 1 || text == "." {
+                return Err(CalcError::new(format!("invalid number '{}'", text), column));
+            }
+            tokens.push(Token { kind: TokenKind::Number(text), column });
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            tokens.push(Token { kind: TokenKind::Ident(name), column });
+            continue;
+        }
+
+        let kind = match c {
+            '+' => TokenKind::Plus,
+            '-' => TokenKind::Minus,
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                i += 1;
+                TokenKind::Caret
+            }
+            '*' => TokenKind::Star,
+            '/' => TokenKind::Slash,
+            '%' => TokenKind::Percent,
+            '^' => TokenKind::Caret,
+            '!' => TokenKind::Bang,
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            ',' => TokenKind::Comma,
+            '=' => TokenKind::Assign,
+            _ => return Err(CalcError::new(format!("unexpected character '{}'", c), column)),
+        };
+        tokens.push(Token { kind, column });
+        i += 1;
+    }
+
+    tokens.push(Token { kind: TokenKind::End, column: chars.len() + 1 });
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+/// Syntax tree, every node keeps the column of its operator or first token
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(String, usize),
+    Variable(String, usize),
+    Negate(Box<Expr>, usize),
+    Factorial(Box<Expr>, usize),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>, usize),
+    Call(String, Vec<Expr>, usize),
+}
+
+/// A full input line: either an assignment or a bare expression
+#[derive(Debug, PartialEq)]
+pub enum Statement {
+    Assign(String, Expr),
+    Expr(Expr),
+}
+
+/// Recursive-descent parser, one method per precedence level:
+///
+/// statement := IDENT '=' expr | expr
+/// expr      := term (('+' | '-') term)*
+/// term      := unary (('*' | '/' | '%') unary)*
+/// unary     := ('-' | '+') unary | power
+/// power     := postfix ('^' unary)?
+/// postfix   := primary '!'*
+/// primary   := NUMBER | IDENT | IDENT '(' args ')' | '(' expr ')'
+pub struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Parser { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<Token, CalcError> {
+        let token = self.next();
+        if token.kind == kind {
+            Ok(token)
+        } else {
+            Err(CalcError::new(format!("expected {}, found {}", kind, token.kind), token.column))
+        }
+    }
+
+    pub fn parse_statement(&mut self) -> Result<Statement, CalcError> {
+        let statement = match (&self.peek().kind, self.tokens.get(self.pos + 1).map(|t| &t.kind)) {
+            (TokenKind::Ident(name), Some(TokenKind::Assign)) => {
+                let name = name.clone();
+                self.pos += 2;
+                Statement::Assign(name, self.parse_expr()?)
+            }
+            _ => Statement::Expr(self.parse_expr()?),
+        };
+
+        let token = self.peek();
+        if token.kind != TokenKind::End {
+            return Err(CalcError::new(format!("unexpected {}", token.kind), token.column));
+        }
+        Ok(statement)
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, CalcError> {
+        let mut lhs = self.parse_term()?;
+
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Plus => BinaryOp::Add,
+                TokenKind::Minus => BinaryOp::Sub,
+                _ => return Ok(lhs),
+            };
+            let column = self.next().column;
+            let rhs = self.parse_term()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs), column);
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Expr, CalcError> {
+        let mut lhs = self.parse_unary()?;
+
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Star => BinaryOp::Mul,
+                TokenKind::Slash => BinaryOp::Div,
+                TokenKind::Percent => BinaryOp::Rem,
+                _ => return Ok(lhs),
+            };
+            let column = self.next().column;
+            let rhs = self.parse_unary()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs), column);
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, CalcError> {
+        match self.peek().kind {
+            TokenKind::Minus => {
+                let column = self.next().column;
+                Ok(Expr::Negate(Box::new(self.parse_unary()?), column))
+            }
+            TokenKind::Plus => {
+                self.next();
+                self.parse_unary()
+            }
+            _ => self.parse_power(),
+        }
+    }
+
+    // Right associative and binds tighter than unary minus: -2^2 = -4, 2^3^2 = 512
+    fn parse_power(&mut self) -> Result<Expr, CalcError> {
+        let base = self.parse_postfix()?;
+
+        if self.peek().kind == TokenKind::Caret {
+            let column = self.next().column;
+            let exponent = self.parse_unary()?;
+            return Ok(Expr::Binary(BinaryOp::Pow, Box::new(base), Box::new(exponent), column));
+        }
+        Ok(base)
+    }
+
+    fn parse_postfix(&mut self) -> Result<Expr, CalcError> {
+        let mut expr = self.parse_primary()?;
+
+        while self.peek().kind == TokenKind::Bang {
+            let column = self.next().column;
+            expr = Expr::Factorial(Box::new(expr), column);
+        }
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, CalcError> {
+        let token = self.next();
+
+        match token.kind {
+            TokenKind::Number(n) => Ok(Expr::Number(n, token.column)),
+            TokenKind::Ident(name) => {
+                if self.peek().kind != TokenKind::LParen {
+                    return Ok(Expr::Variable(name, token.column));
+                }
+
+                self.next();
+                let mut args = Vec::new();
+                if self.peek().kind != TokenKind::RParen {
+                    args.push(self.parse_expr()?);
+                    while self.peek().kind == TokenKind::Comma {
+                        self.next();
+                        args.push(self.parse_expr()?);
+                    }
+                }
+                self.expect(TokenKind::RParen)?;
+                Ok(Expr::Call(name, args, token.column))
+            }
+            TokenKind::LParen => {
+                let expr = self.parse_expr()?;
+                self.expect(TokenKind::RParen)?;
+                Ok(expr)
+            }
+            kind => Err(CalcError::new(format!("unexpected {}", kind), token.column)),
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Statement, CalcError> {
+    Parser::new(tokenize(input)?).parse_statement()
+}
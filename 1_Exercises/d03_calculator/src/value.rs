@@ -0,0 +1,274 @@
+use std::fmt;
+
+use num::bigint::BigInt;
+use num::rational::BigRational;
+use num::traits::{One, Pow, Signed, ToPrimitive, Zero};
+
+use crate::parser::CalcError;
+
+/// Largest exponent accepted in scientific notation
+const MAX_EXACT_EXPONENT: u32 = 100_000;
+/// Largest exact power, in bits of its numerator or denominator
+/// (about a million decimal digits)
+const MAX_EXACT_BITS: u64 = 4_000_000;
+/// Largest n accepted for exact n!
+const MAX_FACTORIAL: u64 = 100_000;
+/// Most decimal places `mode decimal N` prints
+pub const MAX_PLACES: usize = 1000;
+
+/// A number is either exact (arbitrary-precision rational) or an f64.
+/// Exact values turn into floats as soon as they meet an inexact operation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Exact(BigRational),
+    Float(f64),
+}
+
+/// How numbers are read and printed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Float,
+    Rational,
+    Decimal(usize),
+}
+
+impl Mode {
+    pub fn is_exact(&self) -> bool {
+        *self != Mode::Float
+    }
+
+    /// Parse the argument of the `mode` command: "float", "rational", "decimal 2"
+    pub fn parse(text: &str) -> Result<Mode, String> {
+        let unknown = || "Unknown mode, use: float, rational or decimal N".to_string();
+        let mut parts = text.split_whitespace();
+        let mode = match parts.next().ok_or_else(unknown)? {
+            "float" => Mode::Float,
+            "rational" | "exact" => Mode::Rational,
+            "decimal" | "fixed" => Mode::Decimal(match parts.next() {
+                Some(places) => match places.parse() {
+                    Ok(places) if places <= MAX_PLACES => places,
+                    _ => return Err(format!("The number of decimal places must be between 0 and {}", MAX_PLACES)),
+                },
+                None => 2,
+            }),
+            _ => return Err(unknown()),
+        };
+
+        if parts.next().is_some() {
+            return Err(unknown());
+        }
+        Ok(mode)
+    }
+
+    pub fn format(&self, value: &Value) -> String {
+        match (self, value) {
+            (Mode::Float, v) => v.to_f64().to_string(),
+            (Mode::Rational, Value::Exact(r)) => r.to_string(),
+            (Mode::Decimal(places), Value::Exact(r)) => format_decimal(r, *places),
+            // Inexact results are marked so they aren't mistaken for exact ones
+            (Mode::Rational, Value::Float(f)) => format!("≈ {}", f),
+            (Mode::Decimal(places), Value::Float(f)) => format!("≈ {:.*}", places, f),
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Float => write!(f, "float"),
+            Mode::Rational => write!(f, "rational"),
+            Mode::Decimal(places) => write!(f, "decimal {}", places),
+        }
+    }
+}
+
+/// Round half away from zero to a fixed number of decimal places
+fn format_decimal(value: &BigRational, places: usize) -> String {
+    let scale = BigInt::from(10).pow(places as u32);
+    let scaled = (value * BigRational::from_integer(scale)).round().to_integer();
+
+    let digits = scaled.abs().to_string();
+    let sign = if scaled.is_negative() { "-" } else { "" };
+    if places == 0 {
+        return format!("{}{}", sign, digits);
+    }
+
+    let digits = format!("{:0>width$}", digits, width = places + 1);
+    let (int_part, frac_part) = digits.split_at(digits.len() - places);
+    format!("{}{}.{}", sign, int_part, frac_part)
+}
+
+impl Value {
+    pub fn zero() -> Value {
+        Value::Exact(BigRational::zero())
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Value::Exact(r) => r.to_f64().unwrap_or(f64::NAN),
+            Value::Float(f) => *f,
+        }
+    }
+
+    /// Parse a number literal, exactly when asked: "0.1" becomes 1/10 rather than 0.1000000000000000055
+    pub fn parse(text: &str, exact: bool) -> Option<Value> {
+        if !exact {
+            return text.parse().ok().map(Value::Float);
+        }
+
+        let lower = text.to_lowercase();
+        let (mantissa, exponent) = match lower.split_once('e') {
+            Some((m, e)) => (m, e.parse::<i64>().ok()?),
+            None => (lower.as_str(), 0),
+        };
+
+        let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits = format!("{}{}", int_part, frac_part);
+        if digits.is_empty() {
+            return None;
+        }
+
+        let exponent = exponent - frac_part.len() as i64;
+        if exponent.unsigned_abs() > MAX_EXACT_EXPONENT as u64 {
+            return None;
+        }
+
+        let numerator: BigInt = digits.parse().ok()?;
+        let power = BigInt::from(10).pow(exponent.unsigned_abs() as u32);
+        let value = if exponent >= 0 {
+            BigRational::from_integer(numerator * power)
+        } else {
+            BigRational::new(numerator, power)
+        };
+        Some(Value::Exact(value))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Value::Exact(r) => r.is_zero(),
+            Value::Float(f) => *f == 0.0,
+        }
+    }
+
+    pub fn negate(self) -> Value {
+        match self {
+            Value::Exact(r) => Value::Exact(-r),
+            Value::Float(f) => Value::Float(-f),
+        }
+    }
+
+    fn as_integer(&self) -> Option<BigInt> {
+        match self {
+            Value::Exact(r) if r.is_integer() => Some(r.to_integer()),
+            _ => None,
+        }
+    }
+}
+
+/// Apply the exact operation when both sides are exact, otherwise the float one
+fn binary(a: Value, b: Value, exact: fn(BigRational, BigRational) -> BigRational, float: fn(f64, f64) -> f64) -> Value {
+    match (a, b) {
+        (Value::Exact(x), Value::Exact(y)) => Value::Exact(exact(x, y)),
+        (a, b) => Value::Float(float(a.to_f64(), b.to_f64())),
+    }
+}
+
+pub fn add(a: Value, b: Value) -> Value {
+    binary(a, b, |x, y| x + y, |x, y| x + y)
+}
+
+pub fn substract(a: Value, b: Value) -> Value {
+    binary(a, b, |x, y| x - y, |x, y| x - y)
+}
+
+pub fn multiplication(a: Value, b: Value) -> Value {
+    binary(a, b, |x, y| x * y, |x, y| x * y)
+}
+
+pub fn division(a: Value, b: Value, column: usize) -> Result<Value, CalcError> {
+    if b.is_zero() {
+        return Err(CalcError::new("division by zero", column));
+    }
+    Ok(binary(a, b, |x, y| x / y, |x, y| x / y))
+}
+
+pub fn remainder(a: Value, b: Value, column: usize) -> Result<Value, CalcError> {
+    if b.is_zero() {
+        return Err(CalcError::new("division by zero", column));
+    }
+    Ok(binary(a, b, |x, y| x % y, |x, y| x % y))
+}
+
+/// Integer exponents stay exact, anything else goes through powf
+pub fn power(base: Value, exponent: Value, column: usize) -> Result<Value, CalcError> {
+    let (Value::Exact(b), Some(e)) = (&base, exponent.as_integer()) else {
+        return Ok(Value::Float(base.to_f64().powf(exponent.to_f64())));
+    };
+
+    // The result has about bits(base) * |e| bits, except for 0, 1 and -1
+    // which stay small whatever the exponent
+    let base_bits = b.numer().bits().max(b.denom().bits());
+    let trivial = b.is_zero() || (b.numer().abs().is_one() && b.denom().is_one());
+    let e = match e.to_i32() {
+        Some(e) if trivial || base_bits.saturating_mul(e.unsigned_abs() as u64) <= MAX_EXACT_BITS => e,
+        _ => return Err(CalcError::new("result too large for exact arithmetic", column)),
+    };
+    if b.is_zero() && e < 0 {
+        return Err(CalcError::new("division by zero", column));
+    }
+    Ok(Value::Exact(Pow::pow(b, e)))
+}
+
+pub fn factorial(n: Value, column: usize) -> Result<Value, CalcError> {
+    let error = || CalcError::new("factorial needs a non-negative integer", column);
+
+    match n {
+        Value::Float(f) => {
+            if f < 0.0 || f.fract() != 0.0 {
+                return Err(error());
+            }
+            // Anything past 170! is infinite in f64 anyway
+            Ok(Value::Float((1..=f.min(171.0) as u64).fold(1.0, |acc, k| acc * k as f64)))
+        }
+        exact => {
+            let n = exact.as_integer().filter(|n| !n.is_negative()).ok_or_else(error)?;
+            let n = n
+                .to_u64()
+                .filter(|n| *n <= MAX_FACTORIAL)
+                .ok_or_else(|| CalcError::new(format!("factorial is limited to {}!", MAX_FACTORIAL), column))?;
+
+            let result = (2..=n).fold(BigInt::one(), |acc, k| acc * k);
+            Ok(Value::Exact(BigRational::from_integer(result)))
+        }
+    }
+}
+
+/// Functions that have an exact answer for exact input
+pub fn exact_function(name: &str, args: &[Value]) -> Option<Value> {
+    let exact: Vec<&BigRational> = args
+        .iter()
+        .map(|v| match v {
+            Value::Exact(r) => Some(r),
+            Value::Float(_) => None,
+        })
+        .collect::<Option<_>>()?;
+
+    let result = match (name, exact.as_slice()) {
+        ("abs", [x]) => x.abs(),
+        ("floor", [x]) => x.floor(),
+        ("ceil", [x]) => x.ceil(),
+        ("round", [x]) => x.round(),
+        ("min", [x, y]) => (*x).min(*y).clone(),
+        ("max", [x, y]) => (*x).max(*y).clone(),
+        // Only perfect squares, e.g. sqrt(9/4) = 3/2
+        ("sqrt", [x]) if !x.is_negative() => {
+            let (n, d) = (x.numer().sqrt(), x.denom().sqrt());
+            if &(&n * &n) != x.numer() || &(&d * &d) != x.denom() {
+                return None;
+            }
+            BigRational::new(n, d)
+        }
+        _ => return None,
+    };
+    Some(Value::Exact(result))
+}
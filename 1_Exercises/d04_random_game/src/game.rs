@@ -0,0 +1,157 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use rand::Rng;
+
+/// Range and attempt limit of a game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    pub min: u32,
+    pub max: u32,
+    pub max_attempts: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Custom(Config),
+}
+
+impl Difficulty {
+    pub fn parse(name: &str) -> Option<Difficulty> {
+        match name.to_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "normal" | "medium" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn config(&self) -> Config {
+        match self {
+            Difficulty::Easy => Config { min: 1, max: 50, max_attempts: 10 },
+            Difficulty::Normal => Config { min: 1, max: 100, max_attempts: 7 },
+            Difficulty::Hard => Config { min: 1, max: 1000, max_attempts: 10 },
+            Difficulty::Custom(config) => *config,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Normal => write!(f, "normal"),
+            Difficulty::Hard => write!(f, "hard"),
+            Difficulty::Custom(c) => write!(f, "custom {}..={} ({} attempts)", c.min, c.max, c.max_attempts),
+        }
+    }
+}
+
+/// Worst-case number of guesses binary search needs for the range
+pub fn optimal_guesses(min: u32, max: u32) -> u32 {
+    let size = (max - min) as u64 + 1;
+    64 - size.leading_zeros()
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    TooSmall,
+    TooBig,
+    Correct { attempts: u32 },
+    OutOfAttempts { secret: u32 },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GameError {
+    OutOfRange { min: u32, max: u32 },
+    Finished,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::OutOfRange { min, max } => write!(f, "Guess must be between {} and {}", min, max),
+            GameError::Finished => write!(f, "The game is already over"),
+        }
+    }
+}
+
+/// One round of the guessing game, independent of where guesses come from
+pub struct Game {
+    pub config: Config,
+    secret: u32,
+    attempts: u32,
+    finished: bool,
+}
+
+impl Game {
+    pub fn new(config: Config, rng: &mut impl Rng) -> Self {
+        Game {
+            config,
+            secret: rng.random_range(config.min..=config.max),
+            attempts: 0,
+            finished: false,
+        }
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.config.max_attempts - self.attempts
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn guess(&mut self, guess: u32) -> Result<Outcome, GameError> {
+        if self.finished {
+            return Err(GameError::Finished);
+        }
+        if guess < self.config.min || guess > self.config.max {
+            return Err(GameError::OutOfRange { min: self.config.min, max: self.config.max });
+        }
+
+        self.attempts += 1;
+
+        let outcome = match guess.cmp(&self.secret) {
+            Ordering::Equal => Outcome::Correct { attempts: self.attempts },
+            _ if self.attempts >= self.config.max_attempts => Outcome::OutOfAttempts { secret: self.secret },
+            Ordering::Less => Outcome::TooSmall,
+            Ordering::Greater => Outcome::TooBig,
+        };
+
+        if matches!(outcome, Outcome::Correct { .. } | Outcome::OutOfAttempts { .. }) {
+            self.finished = true;
+        }
+        Ok(outcome)
+    }
+}
+
+/// Play the game with binary search, returning every guess made
+pub fn solve(game: &mut Game) -> Vec<(u32, Outcome)> {
+    let (mut low, mut high) = (game.config.min, game.config.max);
+    let mut guesses = Vec::new();
+
+    while !game.is_finished() && low <= high {
+        let guess = low + (high - low) / 2;
+        let outcome = match game.guess(guess) {
+            Ok(outcome) => outcome,
+            Err(_) => break,
+        };
+
+        match outcome {
+            Outcome::TooSmall => low = guess + 1,
+            Outcome::TooBig => high = guess - 1,
+            _ => {}
+        }
+        guesses.push((guess, outcome));
+    }
+
+    guesses
+}
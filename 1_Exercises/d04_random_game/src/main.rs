@@ -1,23 +1,125 @@
-use std::io;
-use rand::Rng;
-use std::cmp::Ordering;
+use std::env;
+use std::io::{self, Write};
+use std::process;
 
-fn main() {
-    println!("Guess the number");
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
-    // Generate a Rnd number
-    let random_number = rand::rng().random_range(1..=100);
+mod game;
+mod scores;
 
-    loop {
-        println!("Input a number: ");
+use game::{optimal_guesses, solve, Difficulty, Game, Outcome};
+use scores::Leaderboard;
+
+const SCORES_FILE: &str = "scores.json";
+
+/// Options read from the command line
+struct Options {
+    difficulty: Difficulty,
+    seed: Option<u64>,
+    name: Option<String>,
+    bot: bool,
+    show_scores: bool,
+}
+
+fn print_usage() {
+    println!("Usage: d04_random_game [OPTIONS]");
+    println!("  --difficulty <easy|normal|hard>   Range and attempt limit (default: normal)");
+    println!("  --range <MIN> <MAX>               Custom range, use with --attempts");
+    println!("  --attempts <N>                    Custom attempt limit");
+    println!("  --seed <N>                        Reproducible secret number");
+    println!("  --name <NAME>                     Player name for the high-score table");
+    println!("  --bot                             Let the computer play with binary search");
+    println!("  --scores                          Show the high-score table");
+}
 
-        let mut input = String::new();
+fn value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).and_then(|pos| args.get(pos + 1)).map(String::as_str)
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut difficulty = match value(args, "--difficulty") {
+        Some(name) => Difficulty::parse(name).ok_or(format!("Unknown difficulty '{}'", name))?,
+        None => Difficulty::Normal,
+    };
 
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read input");
+    let range = args.iter().position(|a| a == "--range");
+    let attempts = value(args, "--attempts");
+    if range.is_some() || attempts.is_some() {
+        let mut config = difficulty.config();
 
-        let guess:u32 = match input.trim().parse() {
+        if let Some(pos) = range {
+            let bound = |i: usize| -> Result<u32, String> {
+                args.get(pos + i).and_then(|v| v.parse().ok()).ok_or("--range needs two numbers".to_string())
+            };
+            config.min = bound(1)?;
+            config.max = bound(2)?;
+            if config.min > config.max {
+                return Err("--range minimum must not be above the maximum".to_string());
+            }
+        }
+        if let Some(attempts) = attempts {
+            config.max_attempts = attempts.parse().map_err(|_| "Invalid number of attempts".to_string())?;
+            if config.max_attempts == 0 {
+                return Err("--attempts must be at least 1".to_string());
+            }
+        }
+        difficulty = Difficulty::Custom(config);
+    }
+
+    let seed = match value(args, "--seed") {
+        Some(seed) => Some(seed.parse().map_err(|_| format!("Invalid seed '{}'", seed))?),
+        None => None,
+    };
+
+    Ok(Options {
+        difficulty,
+        seed,
+        name: value(args, "--name").map(str::to_string),
+        bot: args.iter().any(|a| a == "--bot"),
+        show_scores: args.iter().any(|a| a == "--scores"),
+    })
+}
+
+fn prompt(msg: &str) -> Option<String> {
+    print!("{}", msg);
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_string()),
+    }
+}
+
+fn play_bot(game: &mut Game) {
+    let config = game.config;
+    println!("Bot is guessing a number between {} and {}", config.min, config.max);
+
+    for (guess, outcome) in solve(game) {
+        match outcome {
+            Outcome::TooSmall => println!("Bot guess: {} -> too small", guess),
+            Outcome::TooBig => println!("Bot guess: {} -> too big", guess),
+            Outcome::Correct { attempts } => println!("Bot guess: {} -> correct after {} guesses", guess, attempts),
+            Outcome::OutOfAttempts { secret } => println!("Bot guess: {} -> out of attempts, it was {}", guess, secret),
+        }
+    }
+
+    println!("Optimal worst case for this range: {} guesses", optimal_guesses(config.min, config.max));
+}
+
+/// Read guesses from stdin until the game ends, returns the attempts when won
+fn play_interactive(game: &mut Game) -> Option<u32> {
+    let config = game.config;
+    println!(
+        "Guess a number between {} and {}, you have {} attempts",
+        config.min, config.max, config.max_attempts
+    );
+
+    loop {
+        let input = prompt(&format!("Attempt {}/{} - input a number: ", game.attempts() + 1, config.max_attempts))?;
+
+        let guess: u32 = match input.parse() {
             Ok(num) => num,
             Err(_) => {
                 println!("Invalid input!");
@@ -25,15 +127,83 @@ fn main() {
             }
         };
 
-        println!("Your guess: {}", guess);
-
-        match guess.cmp(&random_number) {
-            Ordering::Less => println!("Too small.."),
-            Ordering::Greater => println!("Too big.."),
-            Ordering::Equal => {
-                println!("Congratulations!");
-                break;
+        match game.guess(guess) {
+            Ok(Outcome::TooSmall) => println!("Too small.. ({} left)", game.remaining()),
+            Ok(Outcome::TooBig) => println!("Too big.. ({} left)", game.remaining()),
+            Ok(Outcome::Correct { attempts }) => {
+                println!("Congratulations! You found it in {} attempts.", attempts);
+                return Some(attempts);
+            }
+            Ok(Outcome::OutOfAttempts { secret }) => {
+                println!("Out of attempts! The number was {}.", secret);
+                return None;
             }
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        print_usage();
+        return;
+    }
+
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            print_usage();
+            process::exit(2);
+        }
+    };
+
+    let mut leaderboard = match Leaderboard::load(SCORES_FILE) {
+        Ok(leaderboard) => leaderboard,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", SCORES_FILE, e);
+            process::exit(1);
         }
+    };
+    if options.show_scores {
+        leaderboard.print();
+        return;
+    }
+
+    println!("Guess the number ({})", options.difficulty);
+
+    // A fixed seed always produces the same secret number
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+    let mut game = Game::new(options.difficulty.config(), &mut rng);
+
+    if options.bot {
+        play_bot(&mut game);
+        return;
+    }
+
+    let name = match options.name {
+        Some(name) => name,
+        None => match prompt("Player name: ") {
+            Some(name) if !name.is_empty() => name,
+            _ => "anonymous".to_string(),
+        },
+    };
+
+    let result = play_interactive(&mut game);
+    if result.is_none() && !game.is_finished() {
+        // stdin closed before the game ended
+        return;
+    }
+
+    if leaderboard.record(&name, &options.difficulty.to_string(), result) {
+        println!("New personal best for {}!", name);
+    }
+    if let Err(e) = leaderboard.save(SCORES_FILE) {
+        eprintln!("Failed to save scores: {}", e);
     }
 }
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+/// Results of one player on one difficulty
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Record {
+    pub played: u32,
+    pub won: u32,
+    pub best: Option<u32>,
+}
+
+/// High scores keyed by player name, then by difficulty
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Leaderboard {
+    pub players: BTreeMap<String, BTreeMap<String, Record>>,
+}
+
+impl Leaderboard {
+    /// An empty leaderboard when there is no file yet
+    pub fn load(path: &str) -> io::Result<Leaderboard> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Leaderboard::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Through a .tmp file, so a game killed mid-save keeps the old scores
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+
+        let tmp_path = format!("{}.tmp", path);
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&tmp_path, path)
+    }
+
+    /// Record a finished game, returns true when it's a new personal best
+    pub fn record(&mut self, player: &str, difficulty: &str, attempts: Option<u32>) -> bool {
+        let record = self
+            .players
+            .entry(player.to_string())
+            .or_default()
+            .entry(difficulty.to_string())
+            .or_default();
+
+        record.played += 1;
+        let Some(attempts) = attempts else {
+            return false;
+        };
+
+        record.won += 1;
+        if record.best.is_none_or(|best| attempts < best) {
+            record.best = Some(attempts);
+            return true;
+        }
+        false
+    }
+
+    /// Best results for a difficulty, fewest attempts first
+    pub fn top(&self, difficulty: &str, limit: usize) -> Vec<(&str, &Record)> {
+        let mut rows: Vec<(&str, &Record)> = self
+            .players
+            .iter()
+            .filter_map(|(name, records)| records.get(difficulty).map(|r| (name.as_str(), r)))
+            .filter(|(_, r)| r.best.is_some())
+            .collect();
+
+        rows.sort_by_key(|(name, r)| (r.best, std::cmp::Reverse(r.won), *name));
+        rows.truncate(limit);
+        rows
+    }
+
+    pub fn difficulties(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .players
+            .values()
+            .flat_map(|records| records.keys().map(String::as_str))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    pub fn print(&self) {
+        if self.players.is_empty() {
+            println!("No scores yet!");
+            return;
+        }
+
+        for difficulty in self.difficulties() {
+            println!("\n== {} ==", difficulty);
+            for (rank, (name, record)) in self.top(difficulty, 10).iter().enumerate() {
+                println!(
+                    "{:>2}. {:<16} best: {:>3}  won: {}/{}",
+                    rank + 1,
+                    name,
+                    record.best.unwrap_or_default(),
+                    record.won,
+                    record.played
+                );
+            }
+        }
+    }
+}
@@ -1,41 +1,160 @@
 // Import crates for oppening and reading a file
 use std::env;
 use std::fs::File;
-use std::io::{Read};
+use std::io::{self, BufReader};
+use std::process;
+
+use serde_json::json;
+
+mod stats;
+
+use stats::{count_reader, Stats};
+
+/// Which columns to print, like wc's -l -w -m -c
+struct Columns {
+    lines: bool,
+    words: bool,
+    chars: bool,
+    bytes: bool,
+}
+
+struct Options {
+    columns: Columns,
+    top: Option<usize>,
+    json: bool,
+    files: Vec<String>,
+}
+
+fn print_usage() {
+    println!("Type: cargo run -- [OPTIONS] [FILE...]");
+    println!("With no FILE, or when FILE is -, read standard input.");
+    println!("  -l, --lines     print line counts");
+    println!("  -w, --words     print word counts");
+    println!("  -m, --chars     print character counts");
+    println!("  -c, --bytes     print byte counts");
+    println!("  --top <N>       print the N most frequent words");
+    println!("  --json          print the results as JSON");
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut columns = Columns { lines: false, words: false, chars: false, bytes: false };
+    let mut top = None;
+    let mut json = false;
+    let mut files = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-l" | "--lines" => columns.lines = true,
+            "-w" | "--words" => columns.words = true,
+            "-m" | "--chars" => columns.chars = true,
+            "-c" | "--bytes" => columns.bytes = true,
+            "--json" => json = true,
+            "--top" => {
+                let n = iter.next().ok_or("--top needs a number")?;
+                top = Some(n.parse().map_err(|_| format!("Invalid number '{}'", n))?);
+            }
+            flag if flag.starts_with('-') && flag != "-" => return Err(format!("Unknown option '{}'", flag)),
+            file => files.push(file.to_string()),
+        }
+    }
+
+    // No column selected means all of them
+    if !(columns.lines || columns.words || columns.chars || columns.bytes) {
+        columns = Columns { lines: true, words: true, chars: true, bytes: true };
+    }
+    if files.is_empty() {
+        files.push("-".to_string());
+    }
+
+    Ok(Options { columns, top, json, files })
+}
+
+fn count_input(path: &str, track_words: bool) -> io::Result<Stats> {
+    if path == "-" {
+        return count_reader(BufReader::new(io::stdin()), track_words);
+    }
+
+    let file = File::open(path)?;
+    count_reader(BufReader::new(file), track_words)
+}
+
+fn print_row(stats: &Stats, columns: &Columns, name: &str) {
+    let mut row = String::new();
+    for (enabled, value) in [
+        (columns.lines, stats.lines),
+        (columns.words, stats.words),
+        (columns.chars, stats.chars),
+        (columns.bytes, stats.bytes),
+    ] {
+        if enabled {
+            row.push_str(&format!("{:>8} ", value));
+        }
+    }
+    println!("{}{}", row, if name == "-" { "" } else { name });
+}
 
 fn main() {
     // Collect command-line arguments
-    let args: Vec<String> = env::args().collect();
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    if args.len() != 2 {
-        println!("Type: cargo run <file_path>");
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        print_usage();
+        return;
     }
 
-    let file_path = &args[1];
-    println!("Reading file: {}", file_path);
-
-    //Read the file contents
-    let mut file = match File::open(file_path) {
-        Ok(num) => num,
+    let options = match parse_args(&args) {
+        Ok(options) => options,
         Err(err) => {
-            println!("Error opening file {}", err);
-            return;
+            eprintln!("{}", err);
+            print_usage();
+            process::exit(2);
         }
     };
 
-    let mut contents = String::new();
+    let mut results = Vec::new();
+    let mut total = Stats::default();
+    let mut failed = false;
 
-    // Copy the content of the file to contents variable after opening the file
-    if let Err(err) = file.read_to_string(&mut contents) {
-        println!("Error reading file: {}", err);
-        return;
+    for path in &options.files {
+        match count_input(path, options.top.is_some()) {
+            Ok(stats) => {
+                total = total.merge(stats.clone());
+                results.push((path, stats));
+            }
+            Err(err) => {
+                eprintln!("Error reading {}: {}", path, err);
+                failed = true;
+            }
+        }
     }
 
-    // Count words
-    let word_count = count_words(&contents);
-    println!("Word Count: {}", word_count);
-}
+    let top = options.top.map(|n| total.top_words(n));
+
+    if options.json {
+        let files: Vec<_> = results.iter().map(|(name, stats)| json!({ "name": name, "stats": stats })).collect();
+        let mut output = json!({ "files": files, "total": total });
+        if let Some(top) = &top {
+            output["top_words"] = top.iter().map(|(word, count)| json!({ "word": word, "count": count })).collect();
+        }
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    } else {
+        for (name, stats) in &results {
+            print_row(stats, &options.columns, name);
+        }
+        if results.len() > 1 {
+            print_row(&total, &options.columns, "total");
+        }
 
-fn count_words(text: &str) -> usize{
-    text.split_whitespace().count()
+        if let Some(top) = &top {
+            println!("\nTop words:");
+            for (rank, (word, count)) in top.iter().enumerate() {
+                println!("{:>3}. {:<20} {}", rank + 1, word, count);
+            }
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
 }
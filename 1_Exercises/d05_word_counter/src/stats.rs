@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+
+use rayon::prelude::*;
+use serde::Serialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Size of the blocks handed to the worker threads
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Counts for one input (or the total of several)
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct Stats {
+    pub lines: u64,
+    pub words: u64,
+    pub chars: u64,
+    pub bytes: u64,
+    #[serde(skip)]
+    pub frequencies: HashMap<String, u64>,
+}
+
+impl Stats {
+    pub fn merge(mut self, other: Stats) -> Stats {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.chars += other.chars;
+        self.bytes += other.bytes;
+
+        for (word, count) in other.frequencies {
+            *self.frequencies.entry(word).or_insert(0) += count;
+        }
+        self
+    }
+
+    /// Most frequent words, ties broken alphabetically
+    pub fn top_words(&self, n: usize) -> Vec<(&str, u64)> {
+        let mut words: Vec<(&str, u64)> = self.frequencies.iter().map(|(w, c)| (w.as_str(), *c)).collect();
+        words.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        words.truncate(n);
+        words
+    }
+}
+
+/// Count one block of text. Words are split on Unicode word boundaries (UAX #29),
+/// so "don't", "naïve" and "東京" are handled like a human would expect.
+pub fn count_chunk(chunk: &[u8], track_words: bool) -> Stats {
+    let text = String::from_utf8_lossy(chunk);
+    let mut stats = Stats {
+        lines: chunk.iter().filter(|b| **b == b'\n').count() as u64,
+        chars: text.chars().count() as u64,
+        bytes: chunk.len() as u64,
+        ..Stats::default()
+    };
+
+    for word in text.unicode_words() {
+        stats.words += 1;
+        if track_words {
+            *stats.frequencies.entry(word.to_lowercase()).or_insert(0) += 1;
+        }
+    }
+
+    stats
+}
+
+/// Reads a stream in blocks of about `CHUNK_SIZE` that end after
+/// whitespace, so no word or UTF-8 sequence is split between two blocks.
+/// Memory stays bounded even without newlines: text with no whitespace
+/// for a whole block is cut at a character boundary instead, which can
+/// split that one word.
+struct Chunks<R: BufRead> {
+    reader: R,
+    carry: Vec<u8>,
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for Chunks<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut chunk = std::mem::take(&mut self.carry);
+        while chunk.len() < CHUNK_SIZE {
+            let buf = match self.reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e)),
+            };
+            if buf.is_empty() {
+                self.done = true;
+                return if chunk.is_empty() { None } else { Some(Ok(chunk)) };
+            }
+
+            let len = buf.len();
+            chunk.extend_from_slice(buf);
+            self.reader.consume(len);
+        }
+
+        // Keep the unfinished last word for the next block
+        let end = match chunk.iter().rposition(|b| b.is_ascii_whitespace()) {
+            Some(pos) => pos + 1,
+            // Before the start of the last character, continuation bytes are 10xxxxxx
+            None => chunk.iter().rposition(|b| b & 0xC0 != 0x80).filter(|pos| *pos > 0).unwrap_or(chunk.len()),
+        };
+        self.carry = chunk.split_off(end);
+
+        Some(Ok(chunk))
+    }
+}
+
+/// Stream a reader through the worker pool, keeping only a few blocks in memory
+pub fn count_reader(reader: impl BufRead + Send, track_words: bool) -> io::Result<Stats> {
+    let chunks = Chunks { reader, carry: Vec::new(), done: false };
+
+    chunks
+        .par_bridge()
+        .map(|chunk| chunk.map(|c| count_chunk(&c, track_words)))
+        .try_reduce(Stats::default, |a, b| Ok(a.merge(b)))
+}
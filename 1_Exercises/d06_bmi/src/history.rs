@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::get_bmi;
+
+/// One recorded measurement, always stored in metric units
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub date: NaiveDate,
+    pub weight: f64,
+    pub height: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waist: Option<f64>,
+}
+
+impl Measurement {
+    pub fn bmi(&self) -> f64 {
+        get_bmi(self.weight, self.height)
+    }
+}
+
+/// Measurements per person, kept sorted by date
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct History {
+    pub people: BTreeMap<String, Vec<Measurement>>,
+}
+
+/// Change between the start of a period and the latest measurement
+#[derive(Debug, PartialEq)]
+pub struct Trend {
+    pub days: i64,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub weight_change: f64,
+    pub bmi_change: f64,
+    pub waist_change: Option<f64>,
+}
+
+impl Trend {
+    /// Weight change per week over the actual span covered
+    pub fn weekly_rate(&self) -> f64 {
+        let span = (self.to - self.from).num_days();
+        if span == 0 { 0.0 } else { self.weight_change / span as f64 * 7.0 }
+    }
+}
+
+impl History {
+    /// An empty history until the first save
+    pub fn load(path: &str) -> io::Result<History> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(History::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Written to a .tmp file, then renamed into place
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+
+        let tmp_path = format!("{}.tmp", path);
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&tmp_path, path)
+    }
+
+    /// Add a measurement; a second one on the same day replaces the first
+    pub fn record(&mut self, name: &str, measurement: Measurement) {
+        let entries = self.people.entry(name.to_string()).or_default();
+        entries.retain(|m| m.date != measurement.date);
+        entries.push(measurement);
+        entries.sort_by_key(|m| m.date);
+    }
+
+    pub fn get(&self, name: &str) -> &[Measurement] {
+        self.people.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Compare the latest measurement with the first one of the last `days`
+    /// days. Older measurements are left out, so the change and the weekly
+    /// rate only ever cover the period itself.
+    pub fn trend(&self, name: &str, days: i64) -> Option<Trend> {
+        let entries = self.get(name);
+        let latest = entries.last()?;
+        let start = latest.date - Duration::days(days);
+
+        let baseline = entries.iter().find(|m| m.date >= start)?;
+
+        if baseline.date == latest.date {
+            return None;
+        }
+
+        Some(Trend {
+            days,
+            from: baseline.date,
+            to: latest.date,
+            weight_change: latest.weight - baseline.weight,
+            bmi_change: latest.bmi() - baseline.bmi(),
+            waist_change: latest.waist.zip(baseline.waist).map(|(now, then)| now - then),
+        })
+    }
+}
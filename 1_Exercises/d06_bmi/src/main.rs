@@ -1,62 +1,213 @@
-use std::io;
+use std::io::{self, Write};
+use std::process;
+
+use chrono::{Local, NaiveDate};
+
+mod history;
+mod metrics;
+
+use history::{History, Measurement};
+use metrics::{
+    classify_bmi, classify_waist_to_height, format_height, format_waist, format_weight, get_bmi, get_bmr,
+    get_tdee, get_waist_to_height, parse_height, parse_waist, parse_weight, ActivityLevel, Sex, UnitSystem,
+};
+
+const HISTORY_FILE: &str = "history.json";
 
 fn main() {
-    println!("BMI Calculator");
+    println!("BMI & Health Calculator");
 
-    // Get Weight
-    println!("Get weight: ");
-    let weight = match get_input_value() {
-        Some(value) => value,
-        None => {
-            println!("Invalid input for weight");
-            return;
+    let mut history = match History::load(HISTORY_FILE) {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", HISTORY_FILE, e);
+            process::exit(1);
         }
     };
-    // Get Height
-    println!("Get height: ");
-    let height = match get_input_value() {
-        Some(value) => value,
-        None => {
-            println!("Incorrect input for height");
-            return;
+
+    loop {
+        println!("\n1. Calculate metrics");
+        println!("2. Show history and trends");
+        println!("3. Exit");
+
+        let Some(choice) = get_input("Select one option: ") else {
+            break;
+        };
+
+        match choice.as_str() {
+            "1" => calculate(&mut history),
+            "2" => show_history(&history),
+            "3" => break,
+            _ => println!("Invalid choice!"),
         }
+    }
+}
+
+fn calculate(history: &mut History) {
+    let system = match get_input("Units - 1. Metric (kg, cm)  2. Imperial (lb, ft in): ").as_deref() {
+        Some("2") => UnitSystem::Imperial,
+        _ => UnitSystem::Metric,
+    };
+
+    let (weight_hint, height_hint, waist_hint) = match system {
+        UnitSystem::Metric => ("kg", "cm or m", "cm"),
+        UnitSystem::Imperial => ("lb", "e.g. 5 9 or 5'9\"", "in"),
+    };
+
+    // Get Weight
+    let Some(weight) = ask(&format!("Get weight ({}): ", weight_hint), |t| parse_weight(t, system)) else {
+        println!("Invalid input for weight");
+        return;
+    };
+    // Get Height
+    let Some(height) = ask(&format!("Get height ({}): ", height_hint), |t| parse_height(t, system)) else {
+        println!("Incorrect input for height");
+        return;
     };
 
     // Compute the BMI and classify the results
     let bmi = get_bmi(weight, height);
+    println!("\nYour bmi is {:.1}", bmi);
+    println!("BMI category: {}", classify_bmi(bmi));
+
+    let waist = get_input(&format!("Waist ({}, empty to skip): ", waist_hint))
+        .filter(|t| !t.is_empty())
+        .and_then(|t| parse_waist(&t, system));
+    if let Some(waist) = waist {
+        let ratio = get_waist_to_height(waist, height);
+        println!("Waist-to-height ratio: {:.2} ({})", ratio, classify_waist_to_height(ratio));
+    }
+
+    if let Some(age) = get_input("Age (empty to skip BMR): ").and_then(|t| t.parse::<u32>().ok()) {
+        let sex = match get_input("Sex (m/f): ").as_deref() {
+            Some("m") | Some("M") => Sex::Male,
+            Some("f") | Some("F") => Sex::Female,
+            _ => {
+                println!("Invalid sex, skipping BMR");
+                return save(history, weight, height, waist, system);
+            }
+        };
+
+        let bmr = get_bmr(weight, height, age, sex);
+        println!("BMR (Mifflin-St Jeor): {:.0} kcal/day", bmr);
+
+        for (i, level) in ActivityLevel::ALL.iter().enumerate() {
+            println!("{}. {}", i + 1, level.description());
+        }
+        let activity = get_input("Activity level: ")
+            .and_then(|t| t.parse::<usize>().ok())
+            .and_then(|i| ActivityLevel::ALL.get(i.wrapping_sub(1)));
+        match activity {
+            Some(level) => println!("TDEE: {:.0} kcal/day", get_tdee(bmr, *level)),
+            None => println!("Invalid activity level, skipping TDEE"),
+        }
+    }
+
+    save(history, weight, height, waist, system);
+}
+
+/// Optionally store the measurement in the history file
+fn save(history: &mut History, weight: f64, height: f64, waist: Option<f64>, system: UnitSystem) {
+    let Some(name) = get_input("\nSave to history as (name, empty to skip): ").filter(|n| !n.is_empty()) else {
+        return;
+    };
 
-    println!("Your bmi is {:.1}", bmi);
+    let today = Local::now().date_naive();
+    let date = match get_input("Date (YYYY-MM-DD, empty for today): ").filter(|d| !d.is_empty()) {
+        Some(text) => match NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => {
+                println!("Invalid date, measurement not saved");
+                return;
+            }
+        },
+        None => today,
+    };
 
-    let category = classify_bmi(bmi);
-    println!("BMI category: {}", category);
+    history.record(&name, Measurement { date, weight, height, waist });
+    match history.save(HISTORY_FILE) {
+        Ok(()) => println!(
+            "Saved {} / {} for {} on {}",
+            format_weight(weight, system),
+            format_height(height, system),
+            name,
+            date
+        ),
+        Err(e) => println!("Failed to save history: {}", e),
+    }
 }
 
-fn classify_bmi(bmi:f64) -> &'static str{
-    if bmi < 18.5 {
-        "Underweight"
-    } else if bmi >= 18.5 && bmi < 24.9 {
-        "Normal"
-    } else if bmi >= 24.9 && bmi < 29.9{
-        "Overweight"
-    } else {
-        "Obesity"
+fn show_history(history: &History) {
+    if history.people.is_empty() {
+        println!("No history yet!");
+        return;
+    }
+
+    let names: Vec<&String> = history.people.keys().collect();
+    println!("People: {}", names.iter().map(|n| n.as_str()).collect::<Vec<_>>().join(", "));
+
+    let Some(name) = get_input("Name: ") else {
+        return;
+    };
+    let entries = history.get(&name);
+    if entries.is_empty() {
+        println!("No measurements for {}", name);
+        return;
+    }
+
+    let system = UnitSystem::Metric;
+    for m in entries {
+        let waist = m.waist.map(|w| format!("  waist {}", format_waist(w, system))).unwrap_or_default();
+        println!(
+            "{}  {:>9}  {:>9}  BMI {:.1}{}",
+            m.date,
+            format_weight(m.weight, system),
+            format_height(m.height, system),
+            m.bmi(),
+            waist
+        );
+    }
+
+    println!();
+    for days in [30, 90] {
+        match history.trend(&name, days) {
+            Some(trend) => {
+                let waist = trend.waist_change.map(|w| format!(", waist {:+.1} cm", w * 100.0)).unwrap_or_default();
+                println!(
+                    "Last {} days ({} -> {}): weight {:+.1} kg ({:+.2} kg/week), BMI {:+.1}{}",
+                    trend.days,
+                    trend.from,
+                    trend.to,
+                    trend.weight_change,
+                    trend.weekly_rate(),
+                    trend.bmi_change,
+                    waist
+                );
+            }
+            None => println!("Last {} days: not enough measurements", days),
+        }
     }
 }
 
-fn get_bmi(weight:f64, height:f64) -> f64 {
-    weight / (height * height)
+/// Prompt until the input parses, an empty line or end of input gives up
+fn ask(prompt: &str, parse: impl Fn(&str) -> Option<f64>) -> Option<f64> {
+    loop {
+        let input = get_input(prompt).filter(|t| !t.is_empty())?;
+        match parse(&input) {
+            Some(value) => return Some(value),
+            None => println!("Could not read '{}', try again (empty to cancel)", input),
+        }
+    }
 }
 
-fn get_input_value() -> Option<f64>{
+fn get_input(prompt: &str) -> Option<String> {
+    print!("{}", prompt);
+    io::stdout().flush().unwrap();
+
     // Declar variable as string
     let mut input = String::new();
-
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Failed to read!");
-
-    match input.trim().parse::<f64>() {
-        Ok(value) => Some(value),
-        Err(_) => None,
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_string()),
     }
-}
\ No newline at end of file
+}
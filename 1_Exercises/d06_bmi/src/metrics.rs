@@ -0,0 +1,199 @@
+const KG_PER_LB: f64 = 0.453_592_37;
+const KG_PER_STONE: f64 = 6.350_293_18;
+const M_PER_INCH: f64 = 0.0254;
+const M_PER_FOOT: f64 = 0.3048;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnitSystem {
+    Metric,
+    Imperial,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sex {
+    Male,
+    Female,
+}
+
+/// Activity multipliers used to turn BMR into daily energy expenditure
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActivityLevel {
+    Sedentary,
+    Light,
+    Moderate,
+    Active,
+    VeryActive,
+}
+
+impl ActivityLevel {
+    pub const ALL: [ActivityLevel; 5] = [
+        ActivityLevel::Sedentary,
+        ActivityLevel::Light,
+        ActivityLevel::Moderate,
+        ActivityLevel::Active,
+        ActivityLevel::VeryActive,
+    ];
+
+    pub fn factor(&self) -> f64 {
+        match self {
+            ActivityLevel::Sedentary => 1.2,
+            ActivityLevel::Light => 1.375,
+            ActivityLevel::Moderate => 1.55,
+            ActivityLevel::Active => 1.725,
+            ActivityLevel::VeryActive => 1.9,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ActivityLevel::Sedentary => "Sedentary (little or no exercise)",
+            ActivityLevel::Light => "Light (exercise 1-3 days/week)",
+            ActivityLevel::Moderate => "Moderate (exercise 3-5 days/week)",
+            ActivityLevel::Active => "Active (exercise 6-7 days/week)",
+            ActivityLevel::VeryActive => "Very active (hard exercise or physical job)",
+        }
+    }
+}
+
+pub fn classify_bmi(bmi: f64) -> &'static str {
+    if bmi < 18.5 {
+        "Underweight"
+    } else if (18.5..24.9).contains(&bmi) {
+        "Normal"
+    } else if (24.9..29.9).contains(&bmi) {
+        "Overweight"
+    } else {
+        "Obesity"
+    }
+}
+
+/// Weight in kg, height in m
+pub fn get_bmi(weight: f64, height: f64) -> f64 {
+    weight / (height * height)
+}
+
+/// Basal metabolic rate in kcal/day (Mifflin-St Jeor)
+pub fn get_bmr(weight: f64, height: f64, age: u32, sex: Sex) -> f64 {
+    let base = 10.0 * weight + 6.25 * height * 100.0 - 5.0 * age as f64;
+    match sex {
+        Sex::Male => base + 5.0,
+        Sex::Female => base - 161.0,
+    }
+}
+
+/// Total daily energy expenditure in kcal/day
+pub fn get_tdee(bmr: f64, activity: ActivityLevel) -> f64 {
+    bmr * activity.factor()
+}
+
+pub fn get_waist_to_height(waist: f64, height: f64) -> f64 {
+    waist / height
+}
+
+pub fn classify_waist_to_height(ratio: f64) -> &'static str {
+    if ratio < 0.4 {
+        "Low"
+    } else if ratio < 0.5 {
+        "Healthy"
+    } else if ratio < 0.6 {
+        "Increased risk"
+    } else {
+        "High risk"
+    }
+}
+
+/// Split "154lb" or "5 ft" into the number and the unit suffix
+fn split_unit(text: &str) -> Option<(f64, String)> {
+    let text = text.trim();
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let value = text[..end].parse().ok()?;
+    Some((value, text[end..].trim().to_lowercase()))
+}
+
+/// Weight in kg. Accepts kg, g, lb, st suffixes; a bare number uses the unit system
+pub fn parse_weight(text: &str, system: UnitSystem) -> Option<f64> {
+    let (value, unit) = split_unit(text)?;
+    let kg = match unit.as_str() {
+        "" if system == UnitSystem::Metric => value,
+        "" => value * KG_PER_LB,
+        "kg" | "kgs" => value,
+        "g" => value / 1000.0,
+        "lb" | "lbs" => value * KG_PER_LB,
+        "st" => value * KG_PER_STONE,
+        _ => return None,
+    };
+    (kg > 0.0).then_some(kg)
+}
+
+/// Length in metres from a single value with an optional m, cm, ft/' or in/" suffix
+fn parse_length_part(text: &str, default: f64) -> Option<f64> {
+    let (value, unit) = split_unit(text)?;
+    let factor = match unit.as_str() {
+        "" => default,
+        "m" => 1.0,
+        "cm" => 0.01,
+        "ft" | "'" => M_PER_FOOT,
+        "in" | "\"" => M_PER_INCH,
+        _ => return None,
+    };
+    Some(value * factor)
+}
+
+/// Height in metres. Metric: "1.75", "175", "175cm" or "175 cm".
+/// Imperial: "5 9", "5'9\"", "5ft 9in" or "69in".
+pub fn parse_height(text: &str, system: UnitSystem) -> Option<f64> {
+    // Put a space after feet markers so "5'9" splits into two parts
+    let text = text.replace('\'', "' ").replace("ft", "ft ");
+    let parts: Vec<&str> = text.split_whitespace().collect();
+
+    let metres = match (system, parts.as_slice()) {
+        (UnitSystem::Imperial, [feet, inches]) => parse_length_part(feet, M_PER_FOOT)? + parse_length_part(inches, M_PER_INCH)?,
+        (UnitSystem::Metric, [value, unit @ ("m" | "cm")]) => parse_length_part(&format!("{}{}", value, unit), 1.0)?,
+        (UnitSystem::Metric, [value]) => {
+            // A bare number above 3 is surely centimetres
+            let (number, unit) = split_unit(value)?;
+            if unit.is_empty() && number > 3.0 { number / 100.0 } else { parse_length_part(value, 1.0)? }
+        }
+        (UnitSystem::Imperial, [value]) => {
+            let (number, unit) = split_unit(value)?;
+            if unit.is_empty() && number <= 8.0 { number * M_PER_FOOT } else { parse_length_part(value, M_PER_INCH)? }
+        }
+        _ => return None,
+    };
+    (metres > 0.0).then_some(metres)
+}
+
+/// Waist circumference in metres, bare numbers are cm or inches
+pub fn parse_waist(text: &str, system: UnitSystem) -> Option<f64> {
+    let default = match system {
+        UnitSystem::Metric => 0.01,
+        UnitSystem::Imperial => M_PER_INCH,
+    };
+    parse_length_part(text, default).filter(|m| *m > 0.0)
+}
+
+pub fn format_weight(kg: f64, system: UnitSystem) -> String {
+    match system {
+        UnitSystem::Metric => format!("{:.1} kg", kg),
+        UnitSystem::Imperial => format!("{:.1} lb", kg / KG_PER_LB),
+    }
+}
+
+pub fn format_height(metres: f64, system: UnitSystem) -> String {
+    match system {
+        UnitSystem::Metric => format!("{:.1} cm", metres * 100.0),
+        UnitSystem::Imperial => {
+            let inches = (metres / M_PER_INCH).round() as u32;
+            format!("{}'{}\"", inches / 12, inches % 12)
+        }
+    }
+}
+
+pub fn format_waist(metres: f64, system: UnitSystem) -> String {
+    match system {
+        UnitSystem::Metric => format!("{:.1} cm", metres * 100.0),
+        UnitSystem::Imperial => format!("{:.1} in", metres / M_PER_INCH),
+    }
+}
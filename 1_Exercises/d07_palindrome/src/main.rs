@@ -1,37 +1,137 @@
-use std::io;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
 
-fn main() {
-    println!("Get input: ");
-    // Get the input string
-    let mut input = String::new();
+mod palindrome;
 
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Invalid input!");
+use palindrome::{
+    can_form_palindrome, count_palindromes, is_palindrome, longest_palindrome, Cleaned, Cleaning,
+};
 
-    // Clean the string
-    let cleaned_string = clean_string(&input);
-    // Return if palindrome
+fn print_usage() {
+    println!("Usage:");
+    println!("  d07_palindrome [--keep-accents] [TEXT...]             analyse TEXT (or ask for it)");
+    println!("  d07_palindrome scan [--keep-accents] [--min-len N] FILE...");
+    println!("By default accents are ignored, use --keep-accents to compare them.");
+}
 
-    if cleaned_string.is_empty() {
+fn analyse(input: &str, mode: Cleaning) {
+    let cleaned = Cleaned::new(input, mode);
+    let chars = &cleaned.chars;
+
+    if chars.is_empty() {
         println!("Input a valid string!");
         return;
     }
 
-    if is_palindrome(&cleaned_string) {
+    if is_palindrome(chars) {
         println!("{}: is palindrome!", input.trim());
-    } else{
+    } else {
         println!("{}: is not a palindrome!", input.trim());
     }
+
+    let longest = longest_palindrome(chars);
+    let original = cleaned.original_range(longest.clone());
+    println!(
+        "Longest palindromic substring: \"{}\" ({} chars)",
+        &input[original],
+        longest.len()
+    );
+    println!("Palindromic substrings: {}", count_palindromes(chars));
+
+    let verdict = if can_form_palindrome(chars) { "yes" } else { "no" };
+    println!("Can be rearranged into a palindrome: {}", verdict);
+}
+
+/// Report palindromic words and whole palindromic lines of each file
+fn scan(files: &[String], mode: Cleaning, min_len: usize) {
+    for path in files {
+        let file = match File::open(path) {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("Failed to open {}: {}", path, e);
+                continue;
+            }
+        };
+
+        let reader = BufReader::new(file);
+        for (i, line) in reader.split(b'\n').enumerate() {
+            let line = match line {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                Err(e) => {
+                    eprintln!("Failed to read {}: {}", path, e);
+                    break;
+                }
+            };
+
+            let cleaned = Cleaned::new(&line, mode);
+            if cleaned.chars.len() >= min_len && is_palindrome(&cleaned.chars) {
+                println!("{}:{}: line: {}", path, i + 1, line.trim());
+                continue;
+            }
+
+            for word in line.split(|c: char| !c.is_alphanumeric() && !is_mark(c)) {
+                let cleaned = Cleaned::new(word, mode);
+                if cleaned.chars.len() >= min_len && is_palindrome(&cleaned.chars) {
+                    println!("{}:{}: word: {}", path, i + 1, word);
+                }
+            }
+        }
+    }
 }
-fn is_palindrome(input: &str) -> bool {
-    input == input.chars().rev().collect::<String>()
+
+fn is_mark(c: char) -> bool {
+    unicode_normalization::char::is_combining_mark(c)
 }
 
-fn clean_string(input:&str) -> String {
-    input
-        .chars() // Iterate over each character
-        .filter(|c| c.is_alphanumeric()) // Keep only letters and numbers
-        .map(|c| c.to_lowercase().to_string()) // Convert to lowercase
-        .collect::<String>() // Collect into a new String
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        print_usage();
+        return;
+    }
+
+    let mode = if let Some(pos) = args.iter().position(|a| a == "--keep-accents") {
+        args.remove(pos);
+        Cleaning::KeepAccents
+    } else {
+        Cleaning::FoldAccents
+    };
+
+    if args.first().map(String::as_str) == Some("scan") {
+        let mut min_len = 3;
+        if let Some(pos) = args.iter().position(|a| a == "--min-len") {
+            min_len = match args.get(pos + 1).and_then(|n| n.parse().ok()) {
+                Some(n) => n,
+                None => {
+                    eprintln!("--min-len needs a number");
+                    return;
+                }
+            };
+            args.drain(pos..pos + 2);
+        }
+
+        if args.len() < 2 {
+            print_usage();
+            return;
+        }
+        scan(&args[1..], mode, min_len);
+        return;
+    }
+
+    if !args.is_empty() {
+        analyse(&args.join(" "), mode);
+        return;
+    }
+
+    println!("Get input: ");
+    // Get the input string
+    let mut input = String::new();
+
+    io::stdin()
+        .read_line(&mut input)
+        .expect("Invalid input!");
+
+    analyse(&input, mode);
 }
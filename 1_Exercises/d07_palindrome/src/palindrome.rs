@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// How text is normalized before comparing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cleaning {
+    /// Drop accents too, so "Dábale" reads as "dabale"
+    FoldAccents,
+    /// Only unify equivalent forms, so a precomposed "é" equals "e" + U+0301
+    KeepAccents,
+}
+
+/// Normalized characters of a text, each remembering which byte
+/// range of the original text it came from
+pub struct Cleaned {
+    pub chars: Vec<char>,
+    origin: Vec<Range<usize>>,
+}
+
+impl Cleaned {
+    pub fn new(input: &str, mode: Cleaning) -> Self {
+        let mut chars = Vec::new();
+        let mut origin = Vec::new();
+
+        let mut push = |c: char, range: &Range<usize>| {
+            if c.is_alphanumeric() {
+                for lower in c.to_lowercase() {
+                    chars.push(lower);
+                    origin.push(range.clone());
+                }
+            }
+        };
+
+        match mode {
+            // Compatibility decomposition, then throw away the combining marks
+            Cleaning::FoldAccents => {
+                for (start, c) in input.char_indices() {
+                    let range = start..start + c.len_utf8();
+                    for d in c.to_string().nfkd().filter(|d| !is_combining_mark(*d)) {
+                        push(d, &range);
+                    }
+                }
+            }
+            // Compose each base letter with the marks that follow it
+            Cleaning::KeepAccents => {
+                let mut start = 0;
+                let indices: Vec<(usize, char)> = input.char_indices().collect();
+                for (i, &(offset, c)) in indices.iter().enumerate() {
+                    let next_is_mark = indices.get(i + 1).is_some_and(|(_, n)| is_combining_mark(*n));
+                    if next_is_mark {
+                        continue;
+                    }
+                    let end = offset + c.len_utf8();
+                    for composed in input[start..end].nfkc() {
+                        if !is_combining_mark(composed) {
+                            push(composed, &(start..end));
+                        }
+                    }
+                    start = end;
+                }
+            }
+        }
+
+        Cleaned { chars, origin }
+    }
+
+    /// Byte range in the original text covered by cleaned chars `range`
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        if range.is_empty() {
+            return 0..0;
+        }
+        self.origin[range.start].start..self.origin[range.end - 1].end
+    }
+}
+
+pub fn is_palindrome(input: &[char]) -> bool {
+    input.iter().eq(input.iter().rev())
+}
+
+/// Manacher's algorithm. Works on "#a#b#a#" so that odd and even palindromes
+/// are handled alike; radii[i] is the length of the longest palindrome in the
+/// original text centred at position i of the separated string.
+pub fn manacher(chars: &[char]) -> Vec<usize> {
+    let separated: Vec<Option<char>> = std::iter::once(None)
+        .chain(chars.iter().flat_map(|c| [Some(*c), None]))
+        .collect();
+    let n = separated.len();
+
+    let mut radii = vec![0usize; n];
+    let (mut center, mut right) = (0usize, 0usize);
+
+    for i in 0..n {
+        let mut radius = if i < right { radii[2 * center - i].min(right - i) } else { 0 };
+
+        while i > radius && i + radius + 1 < n && separated[i - radius - 1] == separated[i + radius + 1] {
+            radius += 1;
+        }
+
+        radii[i] = radius;
+        if i + radius > right {
+            center = i;
+            right = i + radius;
+        }
+    }
+
+    radii
+}
+
+/// Range (in cleaned chars) of the longest palindromic substring, the leftmost on ties
+pub fn longest_palindrome(chars: &[char]) -> Range<usize> {
+    let radii = manacher(chars);
+
+    let (center, radius) = radii
+        .iter()
+        .enumerate()
+        .fold((0, 0), |best, (i, r)| if *r > best.1 { (i, *r) } else { best });
+
+    let start = (center - radius) / 2;
+    start..start + radius
+}
+
+/// Number of palindromic substrings, counting every occurrence ("aaa" has 6)
+pub fn count_palindromes(chars: &[char]) -> usize {
+    manacher(chars).iter().map(|r| r.div_ceil(2)).sum()
+}
+
+/// A permutation can be a palindrome when at most one character has an odd count
+pub fn can_form_palindrome(chars: &[char]) -> bool {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in chars {
+        *counts.entry(*c).or_insert(0) += 1;
+    }
+    counts.values().filter(|n| *n % 2 == 1).count() <= 1
+}
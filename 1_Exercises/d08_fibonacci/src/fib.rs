@@ -0,0 +1,95 @@
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// Lazy, unbounded Fibonacci sequence: 0, 1, 1, 2, 3, 5, ...
+pub struct Fibonacci {
+    current: BigUint,
+    next: BigUint,
+}
+
+impl Iterator for Fibonacci {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<BigUint> {
+        let following = &self.current + &self.next;
+        let next = std::mem::replace(&mut self.next, following);
+        Some(std::mem::replace(&mut self.current, next))
+    }
+}
+
+pub fn fibonacci() -> Fibonacci {
+    Fibonacci { current: BigUint::zero(), next: BigUint::one() }
+}
+
+/// Largest n for `nth`. F(n) has about 0.209 * n digits, so this is a
+/// number of about 209,000 digits; use `nth_mod` for anything larger.
+pub const MAX_NTH: u64 = 1_000_000;
+
+/// F(n) with fast doubling, O(log n) big-integer multiplications:
+/// F(2k) = F(k) * (2F(k+1) - F(k)) and F(2k+1) = F(k)^2 + F(k+1)^2.
+/// None above MAX_NTH.
+pub fn nth(n: u64) -> Option<BigUint> {
+    if n > MAX_NTH {
+        return None;
+    }
+
+    let mut a = BigUint::zero(); // F(k)
+    let mut b = BigUint::one(); // F(k+1)
+
+    for bit in (0..64 - n.leading_zeros()).rev() {
+        let c = &a * (&b * 2u32 - &a);
+        let d = &a * &a + &b * &b;
+
+        if (n >> bit) & 1 == 0 {
+            (a, b) = (c, d);
+        } else {
+            b = &c + &d;
+            a = d;
+        }
+    }
+
+    Some(a)
+}
+
+/// F(n) mod m with fast doubling, every step stays below m
+pub fn nth_mod(n: u64, m: u64) -> u64 {
+    if m == 1 {
+        return 0;
+    }
+
+    let m = m as u128;
+    let (mut a, mut b) = (0u128, 1u128);
+
+    for bit in (0..64 - n.leading_zeros()).rev() {
+        let c = a * ((2 * b + m - a) % m) % m;
+        let d = (a * a % m + b * b % m) % m;
+
+        if (n >> bit) & 1 == 0 {
+            (a, b) = (c, d);
+        } else {
+            (a, b) = (d, (c + d) % m);
+        }
+    }
+
+    a as u64
+}
+
+/// Length of the cycle of F(n) mod m, found by waiting for the pair (0, 1)
+/// to come back. The period never exceeds 6m, so `limit` bounds the search.
+pub fn pisano_period(m: u64, limit: u64) -> Option<u64> {
+    if m == 1 {
+        return Some(1);
+    }
+
+    let m = m as u128;
+    let (mut a, mut b) = (0u128, 1u128);
+
+    for i in 1..=limit {
+        (a, b) = (b, (a + b) % m);
+        if a == 0 && b == 1 {
+            return Some(i);
+        }
+    }
+
+    None
+}
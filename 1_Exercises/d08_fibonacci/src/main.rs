@@ -1,6 +1,67 @@
-use std::io;
+use std::env;
+use std::io::{self, BufWriter, Write};
+
+mod fib;
+
+use fib::{MAX_NTH, fibonacci, nth, nth_mod, pisano_period};
+
+/// Give up on Pisano periods after this many steps
+const PISANO_LIMIT: u64 = 100_000_000;
+/// Most terms `seq` prints. Term n has about 0.209 * n digits, so the
+/// whole sequence is already about ten million digits long.
+const MAX_TERMS: u64 = 10_000;
+
+fn print_usage() {
+    println!("Usage:");
+    println!("  d08_fibonacci               ask for the number of terms");
+    println!("  d08_fibonacci seq <N>       first N terms, N up to {}", MAX_TERMS);
+    println!("  d08_fibonacci nth <N>       F(N), N up to {}", MAX_NTH);
+    println!("  d08_fibonacci mod <N> <M>   F(N) mod M, also shows the Pisano period of M");
+    println!("  d08_fibonacci pisano <M>    Pisano period of M");
+    println!("Numbers may be written as powers, e.g. 10^18");
+}
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.is_empty() {
+        interactive();
+        return;
+    }
+
+    let numbers: Option<Vec<u64>> = args[1..].iter().map(|a| parse_number(a)).collect();
+    let Some(numbers) = numbers else {
+        println!("Invalid input!");
+        return;
+    };
+
+    match (args[0].as_str(), numbers.as_slice()) {
+        ("seq", [n]) => print_sequence(*n),
+        ("nth", [n]) => match nth(*n) {
+            Some(value) => {
+                let value = value.to_string();
+                println!("F({}) = {}", n, value);
+                println!("({} digits)", value.len());
+            }
+            None => println!("F({}) is too large to print, N is at most {}. Try: mod {} <M>", n, MAX_NTH, n),
+        },
+        ("mod", [n, m]) if *m > 0 => {
+            println!("F({}) mod {} = {}", n, m, nth_mod(*n, *m));
+            if *m <= PISANO_LIMIT / 6
+                && let Some(period) = pisano_period(*m, 6 * m)
+            {
+                println!("Pisano period of {}: {}", m, period);
+            }
+        }
+        ("pisano", [m]) if *m > 0 => match pisano_period(*m, 6u64.saturating_mul(*m).min(PISANO_LIMIT)) {
+            Some(period) => println!("Pisano period of {}: {}", m, period),
+            None => println!("Pisano period of {} is longer than {} steps", m, PISANO_LIMIT),
+        },
+        _ => print_usage(),
+    }
+}
+
+fn interactive() {
     // Get input value
     println!("Enter the number of terms: ");
 
@@ -13,21 +74,34 @@ fn main() {
     };
 
     // Compute the fibonacci series and print the fibonacci values
+    print_sequence(terms.into());
+}
 
-    println!("Fibonacci serie: {:?}", generate_fibonacci(terms));
+/// Print each term as it is computed, only the latest two are kept
+fn print_sequence(terms: u64) {
+    if terms > MAX_TERMS {
+        println!("{} terms is too many to print, N is at most {}. Try: nth <N>", terms, MAX_TERMS);
+        return;
+    }
+    // Fails only when stdout is closed, nobody is left to tell then
+    write_sequence(terms, &mut BufWriter::new(io::stdout().lock())).ok();
 }
 
-fn generate_fibonacci(num: u32) -> Vec<u64>{
-    let mut sequence = vec!();
-    if num >= 1 { sequence.push(0) };
-    if num >= 2 { sequence.push(1) };
-    
-    for i in 2..=num {
-        let next = sequence[i as usize - 1] + sequence[i as usize - 2];
-        sequence.push(next);
+fn write_sequence(terms: u64, out: &mut impl Write) -> io::Result<()> {
+    write!(out, "Fibonacci serie: [")?;
+    for (i, value) in fibonacci().take(terms as usize).enumerate() {
+        write!(out, "{}{}", if i == 0 { "" } else { ", " }, value)?;
     }
+    writeln!(out, "]")?;
+    out.flush()
+}
 
-    sequence
+/// Parse "1000" or "10^18"
+fn parse_number(text: &str) -> Option<u64> {
+    match text.split_once('^') {
+        Some((base, exp)) => base.parse::<u64>().ok()?.checked_pow(exp.parse().ok()?),
+        None => text.replace('_', "").parse().ok(),
+    }
 }
 
 fn validate_input() -> Option<u32>{
@@ -37,9 +111,6 @@ fn validate_input() -> Option<u32>{
         .read_line(&mut input)
         .expect("Invalid input!");
 
-    match input.trim().parse::<u32>() {
-        Ok(value) => Some(value),
-        Err(_) => None
-    }
+    input.trim().parse::<u32>().ok()
 
 }
@@ -1,59 +1,85 @@
-use std::io;
+use std::env;
+use std::io::{self, BufWriter, Write};
 
-fn main() {
-    println!("Input number: ");
-
-    let value = match get_input() {
-        Some(num) => num,
-        None => {
-            println!("Invalid input value!");
-            return;
-        }
-    };
+mod primes;
 
-    if value <= 1 {
-        println!("Value must be greater thatn 1");
-    }
+use primes::{factorize, is_prime, nth_prime, primes_in_range};
 
-    // Evaluate if value is prime number.
-    println!("Is {} a prime number: {}", value, is_prime(value));
+fn print_usage() {
+    println!("Usage:");
+    println!("  d09_prime_numbers is-prime <N>...");
+    println!("  d09_prime_numbers range <A> <B> [--count]");
+    println!("  d09_prime_numbers factor <N>...");
+    println!("  d09_prime_numbers nth <N>");
 }
 
-fn is_prime(value: u32) -> bool {
-    if value <= 1 {
-        return false;
-    }
+/// "2^3 * 3 * 5" style output
+fn format_factors(factors: &[u64]) -> String {
+    let mut parts = Vec::new();
+    let mut i = 0;
 
-    if value == 2 {
-        return true;
+    while i < factors.len() {
+        let p = factors[i];
+        let exp = factors[i..].iter().take_while(|f| **f == p).count();
+        parts.push(if exp == 1 { p.to_string() } else { format!("{}^{}", p, exp) });
+        i += exp;
     }
 
-    if value % 2 == 0 {
-        return false;
-    }
+    parts.join(" * ")
+}
 
-    let limit = (value as f64).sqrt() as u32 + 1;
+fn parse(text: &str) -> Option<u64> {
+    text.replace('_', "").parse().ok()
+}
 
-    for i in 3..=limit {
-        if value % i == 0 {
-            return false;
-        }
-    }
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    true
-}
+    let Some(command) = args.first() else {
+        print_usage();
+        return;
+    };
+
+    let count_only = args.iter().any(|a| a == "--count");
+    let numbers: Option<Vec<u64>> = args[1..].iter().filter(|a| *a != "--count").map(|a| parse(a)).collect();
+    let Some(numbers) = numbers else {
+        println!("Invalid input value!");
+        return;
+    };
 
-fn get_input() -> Option<u32> {
-    let mut input = String::new();
+    match (command.as_str(), numbers.as_slice()) {
+        ("is-prime", values) if !values.is_empty() => {
+            for value in values {
+                println!("Is {} a prime number: {}", value, is_prime(*value));
+            }
+        }
+        ("range", [low, high]) => {
+            let mut count = 0u64;
+            let mut out = BufWriter::new(io::stdout().lock());
 
-    io::stdin()
-        .read_line(&mut input)
-        .expect("No input value");
+            primes_in_range(*low, *high, |p| {
+                count += 1;
+                count_only || writeln!(out, "{}", p).is_ok()
+            });
+            drop(out);
 
-    match input.trim().parse::<u32>() {
-        Ok(num) => Some(num),
-        Err(_) => None
+            if count_only {
+                println!("{}", count);
+            }
+        }
+        ("factor", values) if !values.is_empty() => {
+            for value in values {
+                if *value < 2 {
+                    println!("{}: no prime factors", value);
+                } else {
+                    println!("{} = {}", value, format_factors(&factorize(*value)));
+                }
+            }
+        }
+        ("nth", [n]) => match nth_prime(*n) {
+            Some(p) => println!("Prime #{} is {}", n, p),
+            None => println!("Value must be greater than 0"),
+        },
+        _ => print_usage(),
     }
 }
-
-
@@ -0,0 +1,221 @@
+/// Numbers handled per sieve segment, small enough to stay in cache
+const SEGMENT_SIZE: u64 = 1 << 16;
+/// Past this square root the base primes get too big, test each number with Miller-Rabin instead
+const MAX_BASE_SIEVE: u64 = 1 << 27;
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Deterministic Miller-Rabin: these twelve bases are enough for every u64
+pub fn is_prime(value: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if value < 2 {
+        return false;
+    }
+    for p in BASES {
+        if value.is_multiple_of(p) {
+            return value == p;
+        }
+    }
+
+    // value - 1 = d * 2^s with d odd
+    let s = (value - 1).trailing_zeros();
+    let d = (value - 1) >> s;
+
+    'witness: for a in BASES {
+        let mut x = pow_mod(a, d, value);
+        if x == 1 || x == value - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, value);
+            if x == value - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+
+    true
+}
+
+/// Plain sieve of Eratosthenes for the base primes up to `limit`
+fn simple_sieve(limit: u64) -> Vec<u64> {
+    let limit = limit as usize;
+    let mut composite = vec![false; limit + 1];
+    let mut primes = Vec::new();
+
+    for i in 2..=limit {
+        if composite[i] {
+            continue;
+        }
+        primes.push(i as u64);
+        for multiple in (i * i..=limit).step_by(i) {
+            composite[multiple] = true;
+        }
+    }
+
+    primes
+}
+
+/// Call `visit` with every prime in [low, high], in order, until it returns false.
+/// Memory use is one segment plus the primes up to sqrt(high), never anything
+/// proportional to high.
+pub fn primes_in_range(low: u64, high: u64, mut visit: impl FnMut(u64) -> bool) {
+    let low = low.max(2);
+    if low > high {
+        return;
+    }
+
+    let root = high.isqrt();
+    if root > MAX_BASE_SIEVE {
+        // Huge numbers: the base primes would not fit, test one by one
+        for n in low..=high {
+            if is_prime(n) && !visit(n) {
+                return;
+            }
+        }
+        return;
+    }
+
+    let base_primes = simple_sieve(root);
+    let mut start = low;
+
+    loop {
+        let end = high.min(start.saturating_add(SEGMENT_SIZE - 1));
+        let mut composite = vec![false; (end - start + 1) as usize];
+
+        for &p in &base_primes {
+            if p * p > end {
+                break;
+            }
+            // First multiple of p in the segment, but never p itself
+            let first = (p * p).max(start.div_ceil(p) * p);
+            for multiple in (first..=end).step_by(p as usize) {
+                composite[(multiple - start) as usize] = true;
+            }
+        }
+
+        for (i, is_composite) in composite.iter().enumerate() {
+            if !is_composite && !visit(start + i as u64) {
+                return;
+            }
+        }
+
+        if end == high {
+            break;
+        }
+        start = end + 1;
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Pollard's rho with Brent's cycle detection, finds a non-trivial factor of a composite n
+fn pollard_rho(n: u64) -> u64 {
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+
+    // Try increasing constants until one gives a proper factor
+    for c in 1u128.. {
+        let f = |x: u64| ((mul_mod(x, x, n) as u128 + c) % n as u128) as u64;
+        let (mut x, mut y, mut g) = (2u64, 2u64, 1u64);
+        let mut power = 1;
+        let mut lam = 1;
+
+        while g == 1 {
+            if power == lam {
+                x = y;
+                power *= 2;
+                lam = 0;
+            }
+            y = f(y);
+            lam += 1;
+            g = gcd(x.abs_diff(y), n);
+        }
+
+        if g != n {
+            return g;
+        }
+    }
+
+    unreachable!()
+}
+
+/// Prime factors of n in ascending order, with repetition (12 -> [2, 2, 3])
+pub fn factorize(n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut n = n;
+
+    // Small primes first, rho is wasteful for them
+    for p in [2u64, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        while n.is_multiple_of(p) && n > 1 {
+            factors.push(p);
+            n /= p;
+        }
+    }
+
+    let mut pending = vec![n];
+    while let Some(m) = pending.pop() {
+        if m == 1 {
+            continue;
+        }
+        if is_prime(m) {
+            factors.push(m);
+            continue;
+        }
+        let d = pollard_rho(m);
+        pending.push(d);
+        pending.push(m / d);
+    }
+
+    factors.sort_unstable();
+    factors
+}
+
+/// The n-th prime, 1-based: nth_prime(1) = 2
+pub fn nth_prime(n: u64) -> Option<u64> {
+    if n == 0 {
+        return None;
+    }
+
+    // Rosser's bound: p_n < n (ln n + ln ln n) for n >= 6
+    let bound = if n < 6 {
+        13
+    } else {
+        let x = n as f64;
+        (x * (x.ln() + x.ln().ln())).ceil() as u64
+    };
+
+    let mut count = 0;
+    let mut found = None;
+    primes_in_range(2, bound, |p| {
+        count += 1;
+        if count == n {
+            found = Some(p);
+        }
+        found.is_none()
+    });
+    found
+}
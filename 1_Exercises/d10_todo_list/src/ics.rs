@@ -0,0 +1,76 @@
+use chrono::Utc;
+
+use crate::store::{Priority, Task};
+
+/// Escape text values as required by RFC 5545
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Lines longer than 75 octets are folded onto continuation lines starting with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Build a calendar with one all-day event per task that has a due date
+pub fn export(tasks: &[&Task]) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//d10_todo_list//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for task in tasks {
+        let Some(due) = task.due else {
+            continue;
+        };
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:task-{}@d10_todo_list", task.id));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", due.format("%Y%m%d")));
+        // A date-only event lasts one day without DTEND too, which the last
+        // date of the calendar needs
+        if let Some(end) = due.succ_opt() {
+            lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+        }
+        lines.push(format!("SUMMARY:{}", escape(&task.description)));
+
+        let priority = match task.priority {
+            Priority::High => 1,
+            Priority::Medium => 5,
+            Priority::Low => 9,
+        };
+        lines.push(format!("PRIORITY:{}", priority));
+
+        if !task.tags.is_empty() {
+            let tags: Vec<String> = task.tags.iter().map(|t| escape(t)).collect();
+            lines.push(format!("CATEGORIES:{}", tags.join(",")));
+        }
+        if let Some(rule) = task.recurrence {
+            lines.push(format!("RRULE:{}", rule.to_rrule()));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|l| fold(l)).collect()
+}
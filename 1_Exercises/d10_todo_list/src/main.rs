@@ -1,18 +1,188 @@
 // Serde is a framework for serializing and deserializing Rust data structures efficiently and generically.
-use std::fs::{self, File};
-use std::io::{self, Write};
-use serde::{Serialize, Deserialize};
-
-#[derive(Serialize, Deserialize, Debug)]
-struct Task {
-    id: usize,
-    description: String,
-    completed: bool,
-}
+use std::cmp::{Ordering, Reverse};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use chrono::{Local, NaiveDate};
+
+mod ics;
+mod recurrence;
+mod store;
+
+use recurrence::{parse_date, Recurrence};
+use store::{NewTask, Priority, Task, TaskList};
+
+const TASKS_FILE: &str = "tasks.json";
 
 fn main() {
-    let mut tasks: Vec<Task> = load_tasks();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let path = Path::new(TASKS_FILE);
+
+    let mut tasks = match TaskList::load(path) {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", TASKS_FILE, e);
+            process::exit(1);
+        }
+    };
+
+    let result = if args.is_empty() {
+        menu(&mut tasks);
+        Ok(true)
+    } else {
+        run_command(&mut tasks, &args)
+    };
+
+    match result {
+        // Only write the file when something changed
+        Ok(true) => {
+            if let Err(e) = tasks.save(path) {
+                eprintln!("Failed to save tasks: {}", e);
+                process::exit(1);
+            }
+        }
+        Ok(false) => {}
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn print_usage() {
+    println!("Usage:");
+    println!("  todo                                     interactive menu");
+    println!("  todo add <DESCRIPTION> [--priority low|medium|high] [--due DATE] [--tag TAG]... [--every RULE]");
+    println!("  todo ls [--pending | --done] [--tag TAG]");
+    println!("  todo due                                 overdue, today and upcoming tasks");
+    println!("  todo done <ID>...");
+    println!("  todo undo <ID>...");
+    println!("  todo rm <ID>...");
+    println!("  todo export <FILE.ics>                   pending tasks with a due date");
+    println!("DATE: 2026-10-20, today, tomorrow, monday, +3d, +2w");
+    println!("RULE: daily, weekly, monthly, \"every monday\", \"every 2 weeks\"");
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn parse_ids(args: &[String]) -> Result<Vec<usize>, String> {
+    if args.is_empty() {
+        return Err("Missing task id".to_string());
+    }
+    args.iter().map(|a| a.parse().map_err(|_| format!("Invalid task id '{}'", a))).collect()
+}
+
+/// Run one subcommand, returns whether the task list changed
+fn run_command(tasks: &mut TaskList, args: &[String]) -> Result<bool, String> {
+    let rest = &args[1..];
+
+    match args[0].as_str() {
+        "add" => {
+            let mut new = NewTask::default();
+            let mut words = Vec::new();
+
+            let mut iter = rest.iter();
+            while let Some(arg) = iter.next() {
+                let mut value = || iter.next().ok_or(format!("{} needs a value", arg));
+                match arg.as_str() {
+                    "--priority" | "-p" => {
+                        let value = value()?;
+                        new.priority = Priority::parse(value).ok_or(format!("Unknown priority '{}'", value))?;
+                    }
+                    "--due" | "-d" => {
+                        let value = value()?;
+                        new.due = Some(parse_date(value, today()).ok_or(format!("Invalid date '{}'", value))?);
+                    }
+                    "--tag" | "-t" => new.tags.extend(value()?.split(',').map(|t| t.trim().to_string())),
+                    "--every" | "--repeat" => {
+                        let value = value()?;
+                        new.recurrence = Some(Recurrence::parse(value).ok_or(format!("Invalid recurrence '{}'", value))?);
+                    }
+                    word => words.push(word),
+                }
+            }
+
+            new.description = words.join(" ");
+            if new.description.trim().is_empty() {
+                return Err("Task description can't be empty".to_string());
+            }
+            // A recurring task starts at its first occurrence
+            if let (None, Some(rule)) = (new.due, new.recurrence) {
+                new.due = rule.first_on_or_after(today());
+            }
+
+            let task = tasks.add(new);
+            println!("Added task {}", task.id);
+            Ok(true)
+        }
+        "ls" | "list" => {
+            let status = rest.iter().find(|a| *a == "--pending" || *a == "--done");
+            let tag = rest.iter().position(|a| a == "--tag").and_then(|pos| rest.get(pos + 1));
+            let shown: Vec<&Task> = tasks
+                .tasks
+                .iter()
+                .filter(|t| match status.map(String::as_str) {
+                    Some("--pending") => !t.completed,
+                    Some("--done") => t.completed,
+                    _ => true,
+                })
+                .filter(|t| tag.is_none_or(|tag| t.tags.contains(tag)))
+                .collect();
+            view_tasks(&shown);
+            Ok(false)
+        }
+        "due" => {
+            view_due(&tasks.tasks, today());
+            Ok(false)
+        }
+        "done" => {
+            for id in parse_ids(rest)? {
+                if let Some(next) = tasks.complete(id, today())? {
+                    println!("Task {} repeats, next occurrence is task {}", id, next);
+                }
+            }
+            println!("Tasks updated!");
+            Ok(true)
+        }
+        "undo" => {
+            for id in parse_ids(rest)? {
+                let task = tasks.get_mut(id).ok_or(format!("No task with id {}", id))?;
+                task.completed = false;
+            }
+            println!("Tasks updated!");
+            Ok(true)
+        }
+        "export" => {
+            let path = rest.first().ok_or("Missing output file")?;
+            let pending: Vec<&Task> = tasks.tasks.iter().filter(|t| !t.completed).collect();
+            fs::write(path, ics::export(&pending)).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            println!("Exported {} tasks to {}", pending.iter().filter(|t| t.due.is_some()).count(), path);
+            Ok(false)
+        }
+        "rm" | "delete" => {
+            for id in parse_ids(rest)? {
+                tasks.remove(id).ok_or(format!("No task with id {}", id))?;
+            }
+            println!("Tasks deleted!");
+            Ok(true)
+        }
+        "help" | "--help" | "-h" => {
+            print_usage();
+            Ok(false)
+        }
+        other => {
+            print_usage();
+            Err(format!("Unknown command '{}'", other))
+        }
+    }
+}
 
+fn menu(tasks: &mut TaskList) {
     loop {
         println!("\nTo-Do List Menu:");
         println!("1. Add Task");
@@ -24,12 +194,11 @@ fn main() {
         let choice = get_input("Enter your choice");
 
         match choice.trim() {
-            "1" => add_task(&mut tasks),
-            "2" => view_tasks(&tasks),
-            "3" => mark_task_completed(&mut tasks),
-            "4" => delete_task(&mut tasks),
-            "5" => {
-                save_tasks(&tasks);
+            "1" => add_task(tasks),
+            "2" => view_tasks(&tasks.tasks.iter().collect::<Vec<_>>()),
+            "3" => mark_task_completed(tasks),
+            "4" => delete_task(tasks),
+            "5" | "" => {
                 println!("Tasks saved!");
                 break;
             }
@@ -38,60 +207,127 @@ fn main() {
     }
 }
 
-fn save_tasks(tasks: &Vec<Task>) {
-    let json = serde_json::to_string_pretty(tasks).expect("Failed to serialize tasks!");
-    let mut file = File::create("tasks.json").expect("Failed to create file!");
-    file.write_all(json.as_bytes()).expect("Failed to write tasks to file!");
-}
-
-fn mark_task_completed(tasks: &mut Vec<Task>) {
+fn mark_task_completed(tasks: &mut TaskList) {
     let id = get_input("Tasks to complete: ");
-    if let Ok(id)  = id.trim().parse::<usize>() {
-        if let Some(task) = tasks.iter_mut().find(|t| t.id == id){
-            task.completed = true;
-            println!("Tasks completed!");
-        } else{
-            println!("No task available!");
+    if let Ok(id) = id.trim().parse::<usize>() {
+        match tasks.complete(id, today()) {
+            Ok(next) => {
+                println!("Tasks completed!");
+                if let Some(next) = next {
+                    println!("Next occurrence added as task {}", next);
+                }
+            }
+            Err(_) => println!("No task available!"),
         }
     } else {
         println!("Invalid tasks!");
     }
 }
 
-fn delete_task(tasks: &mut Vec<Task>) {
+fn delete_task(tasks: &mut TaskList) {
     let id = get_input("Tasks to delete: ");
 
     if let Ok(id) = id.trim().parse::<usize>() {
-        if let Some(index) = tasks.iter().position(|t| t.id == id){
-            tasks.remove(index);
+        if tasks.remove(id).is_some() {
             println!("Tasks deleted!");
+        } else {
+            println!("No task available!");
         }
     } else {
         println!("Invalid task!");
     }
 }
 
-fn view_tasks(tasks: &Vec<Task>) {
+fn view_tasks(tasks: &[&Task]) {
     if tasks.is_empty() {
         println!("No tasks!");
     } else {
         for task in tasks {
             let status = if task.completed {"✅"} else {"❌"};
-            println!("{} - {} : {}", task.id, status, task.description)
+            println!("{} - {} [{}] : {}{}", task.id, status, task.priority, task.description, details(task))
         }
     }
+}
+
+/// " (due 2026-10-20, every monday) #work #home"
+fn details(task: &Task) -> String {
+    let mut parts = Vec::new();
+    if let Some(due) = task.due {
+        parts.push(format!("due {}", due));
+    }
+    if let Some(rule) = task.recurrence {
+        parts.push(rule.to_string());
+    }
 
+    let mut text = if parts.is_empty() { String::new() } else { format!(" ({})", parts.join(", ")) };
+    for tag in &task.tags {
+        text.push_str(&format!(" #{}", tag));
+    }
+    text
 }
 
-fn add_task(tasks: &mut Vec<Task>){
+/// Pending tasks with a due date, grouped by when they are due
+fn view_due(tasks: &[Task], today: NaiveDate) {
+    let mut pending: Vec<&Task> = tasks.iter().filter(|t| !t.completed && t.due.is_some()).collect();
+    pending.sort_by_key(|t| (t.due, Reverse(t.priority)));
+
+    for (title, order) in [("Overdue", Ordering::Less), ("Today", Ordering::Equal), ("Upcoming", Ordering::Greater)] {
+        let group: Vec<&Task> = pending.iter().copied().filter(|t| t.due.map(|d| d.cmp(&today)) == Some(order)).collect();
+        println!("\n{} ({}):", title, group.len());
+        if !group.is_empty() {
+            view_tasks(&group);
+        }
+    }
+}
+
+fn add_task(tasks: &mut TaskList) {
     let description = get_input("Enter task description:");
-    let id = tasks.len() + 1;
+    if description.trim().is_empty() {
+        println!("Task description can't be empty!");
+        return;
+    }
+
+    let priority = get_input("Priority (low/medium/high, empty for medium):");
+    let priority = match priority.trim() {
+        "" => Priority::default(),
+        text => match Priority::parse(text) {
+            Some(p) => p,
+            None => {
+                println!("Invalid priority!");
+                return;
+            }
+        },
+    };
+
+    let due = get_input("Due date (e.g. 2026-10-20, tomorrow, friday; empty for none):");
+    let due = match due.trim() {
+        "" => None,
+        text => match parse_date(text, today()) {
+            Some(date) => Some(date),
+            None => {
+                println!("Invalid date!");
+                return;
+            }
+        },
+    };
+
+    let rule = get_input("Repeat (e.g. every monday, every 2 weeks; empty for never):");
+    let recurrence = match rule.trim() {
+        "" => None,
+        text => match Recurrence::parse(text) {
+            Some(rule) => Some(rule),
+            None => {
+                println!("Invalid recurrence!");
+                return;
+            }
+        },
+    };
 
-    tasks.push(Task {
-        id,
-        description: description.trim().to_string(),
-        completed: false
-    });
+    let tags = get_input("Tags (comma separated, empty for none):");
+    let tags = tags.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+
+    let due = due.or_else(|| recurrence.and_then(|r| r.first_on_or_after(today())));
+    tasks.add(NewTask { description, priority, due, tags, recurrence });
     println!("Tasks added!");
 }
 
@@ -105,10 +341,3 @@ fn get_input(prompt: &str) -> String {
 
     choice
 }
-
-fn load_tasks() -> Vec<Task>{
-    match fs::read_to_string("tasks.json") {
-        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|_| Vec::new()),
-        Err(_) => Vec::new(),
-    }
-}
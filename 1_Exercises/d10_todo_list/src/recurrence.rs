@@ -0,0 +1,174 @@
+use std::fmt;
+
+use chrono::{Datelike, Duration, Months, NaiveDate, TimeDelta, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// How often a task repeats. Stored in tasks.json as its text form, e.g. "every 2 weeks"
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum Recurrence {
+    Days(u32),
+    Weeks(u32),
+    Months(u32),
+    Years(u32),
+    Weekday(Weekday),
+}
+
+fn parse_weekday(text: &str) -> Option<Weekday> {
+    let weekday = match text.trim_end_matches('s') {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(weekday)
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "monday",
+        Weekday::Tue => "tuesday",
+        Weekday::Wed => "wednesday",
+        Weekday::Thu => "thursday",
+        Weekday::Fri => "friday",
+        Weekday::Sat => "saturday",
+        Weekday::Sun => "sunday",
+    }
+}
+
+impl Recurrence {
+    /// Parse rules such as "daily", "every monday", "every 2 weeks" or "3 months"
+    pub fn parse(text: &str) -> Option<Recurrence> {
+        let text = text.trim().to_lowercase();
+        match text.as_str() {
+            "daily" => return Some(Recurrence::Days(1)),
+            "weekly" => return Some(Recurrence::Weeks(1)),
+            "monthly" => return Some(Recurrence::Months(1)),
+            "yearly" | "annually" => return Some(Recurrence::Years(1)),
+            _ => {}
+        }
+
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let words = match words.as_slice() {
+            ["every", rest @ ..] => rest,
+            rest => rest,
+        };
+
+        let (count, unit) = match words {
+            [unit] => (1, *unit),
+            [count, unit] => (count.parse().ok()?, *unit),
+            _ => return None,
+        };
+        if count == 0 {
+            return None;
+        }
+
+        let rule = match unit.trim_end_matches('s') {
+            "day" => Recurrence::Days(count),
+            "week" => Recurrence::Weeks(count),
+            "month" => Recurrence::Months(count),
+            "year" => Recurrence::Years(count),
+            _ if count == 1 => Recurrence::Weekday(parse_weekday(unit)?),
+            _ => return None,
+        };
+        Some(rule)
+    }
+
+    /// The occurrence after `date`, None past the last date chrono knows
+    pub fn next_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match *self {
+            Recurrence::Days(n) => date.checked_add_signed(TimeDelta::try_days(n as i64)?),
+            Recurrence::Weeks(n) => date.checked_add_signed(TimeDelta::try_weeks(n as i64)?),
+            Recurrence::Months(n) => date.checked_add_months(Months::new(n)),
+            Recurrence::Years(n) => date.checked_add_months(Months::new(n.checked_mul(12)?)),
+            Recurrence::Weekday(weekday) => {
+                let days = (7 + weekday.num_days_from_monday() as i64 - date.weekday().num_days_from_monday() as i64) % 7;
+                date.checked_add_signed(Duration::days(if days == 0 { 7 } else { days }))
+            }
+        }
+    }
+
+    /// First occurrence on or after `date`, used when a recurring task has no due date yet
+    pub fn first_on_or_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::Weekday(_) => self.next_after(date.pred_opt()?),
+            _ => Some(date),
+        }
+    }
+
+    /// iCalendar RRULE value, e.g. "FREQ=WEEKLY;INTERVAL=2"
+    pub fn to_rrule(self) -> String {
+        let (freq, interval) = match self {
+            Recurrence::Days(n) => ("DAILY", n),
+            Recurrence::Weeks(n) => ("WEEKLY", n),
+            Recurrence::Months(n) => ("MONTHLY", n),
+            Recurrence::Years(n) => ("YEARLY", n),
+            Recurrence::Weekday(weekday) => {
+                let day = &weekday_name(weekday)[..2];
+                return format!("FREQ=WEEKLY;BYDAY={}", day.to_uppercase());
+            }
+        };
+
+        if interval == 1 { format!("FREQ={}", freq) } else { format!("FREQ={};INTERVAL={}", freq, interval) }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (count, unit) = match self {
+            Recurrence::Days(n) => (*n, "day"),
+            Recurrence::Weeks(n) => (*n, "week"),
+            Recurrence::Months(n) => (*n, "month"),
+            Recurrence::Years(n) => (*n, "year"),
+            Recurrence::Weekday(weekday) => return write!(f, "every {}", weekday_name(*weekday)),
+        };
+
+        if count == 1 { write!(f, "every {}", unit) } else { write!(f, "every {} {}s", count, unit) }
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Recurrence::parse(&text).ok_or(format!("invalid recurrence '{}'", text))
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(rule: Recurrence) -> String {
+        rule.to_string()
+    }
+}
+
+/// Parse a due date: "2026-10-20", "today", "tomorrow", a weekday name or "+3d" / "+2w"
+pub fn parse_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = text.trim().to_lowercase();
+
+    match text.as_str() {
+        "today" => return Some(today),
+        "tomorrow" => return Some(today + Duration::days(1)),
+        _ => {}
+    }
+
+    if let Some(offset) = text.strip_prefix('+') {
+        let (number, unit) = offset.split_at(offset.find(|c: char| !c.is_ascii_digit()).unwrap_or(offset.len()));
+        let n: i64 = number.parse().ok()?;
+        return match unit {
+            "" | "d" => today.checked_add_signed(TimeDelta::try_days(n)?),
+            "w" => today.checked_add_signed(TimeDelta::try_weeks(n)?),
+            "m" => today.checked_add_months(Months::new(n.try_into().ok()?)),
+            _ => None,
+        };
+    }
+
+    if let Some(weekday) = parse_weekday(&text) {
+        return Recurrence::Weekday(weekday).next_after(today);
+    }
+
+    NaiveDate::parse_from_str(&text, "%Y-%m-%d").ok()
+}
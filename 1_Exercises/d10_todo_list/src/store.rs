@@ -0,0 +1,190 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::recurrence::Recurrence;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Priority {
+    pub fn parse(text: &str) -> Option<Priority> {
+        match text.to_lowercase().as_str() {
+            "low" | "l" => Some(Priority::Low),
+            "medium" | "med" | "m" => Some(Priority::Medium),
+            "high" | "h" => Some(Priority::High),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Priority::Low => write!(f, "low"),
+            Priority::Medium => write!(f, "medium"),
+            Priority::High => write!(f, "high"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Task {
+    pub id: usize,
+    pub description: String,
+    pub completed: bool,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    /// Id of the occurrence spawned when this task was completed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<usize>,
+}
+
+/// Fields of a task that is about to be added
+#[derive(Debug, Default)]
+pub struct NewTask {
+    pub description: String,
+    pub priority: Priority,
+    pub due: Option<NaiveDate>,
+    pub tags: Vec<String>,
+    pub recurrence: Option<Recurrence>,
+}
+
+/// Everything stored in tasks.json. `next_id` only ever grows,
+/// so an id is never handed out twice, even after deletes.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct TaskList {
+    pub next_id: usize,
+    pub tasks: Vec<Task>,
+}
+
+/// tasks.json used to be a bare array of tasks
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredTasks {
+    List(TaskList),
+    Legacy(Vec<Task>),
+}
+
+impl TaskList {
+    pub fn load(path: &Path) -> io::Result<TaskList> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(TaskList { next_id: 1, tasks: Vec::new() }),
+            Err(e) => return Err(e),
+        };
+
+        let mut list = match serde_json::from_str(&content).map_err(io::Error::other)? {
+            StoredTasks::List(list) => list,
+            StoredTasks::Legacy(tasks) => TaskList { next_id: 1, tasks },
+        };
+
+        // Never reuse an id that is already taken
+        let max_id = list.tasks.iter().map(|t| t.id).max().unwrap_or(0);
+        list.next_id = list.next_id.max(max_id + 1);
+        Ok(list)
+    }
+
+    /// Written next to the target, then renamed over it
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+
+        let mut tmp_name = path.as_os_str().to_owned();
+        tmp_name.push(".tmp");
+        let tmp_path = Path::new(&tmp_name);
+
+        let mut file = File::create(tmp_path)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(tmp_path, path)
+    }
+
+    pub fn add(&mut self, new: NewTask) -> &Task {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.tasks.push(Task {
+            id,
+            description: new.description.trim().to_string(),
+            completed: false,
+            priority: new.priority,
+            due: new.due,
+            tags: new.tags,
+            recurrence: new.recurrence,
+            next: None,
+        });
+        self.tasks.last().unwrap()
+    }
+
+    /// Mark a task as done. A recurring task spawns its next occurrence,
+    /// skipping any dates that are already in the past; its id is returned.
+    /// There is none when the next date would be past the end of the calendar,
+    /// or when the task was done before, undone, and its next occurrence is
+    /// still there.
+    pub fn complete(&mut self, id: usize, today: NaiveDate) -> Result<Option<usize>, String> {
+        let index = self.tasks.iter().position(|t| t.id == id).ok_or(format!("No task with id {}", id))?;
+        let task = &mut self.tasks[index];
+        if task.completed {
+            return Ok(None);
+        }
+        task.completed = true;
+
+        let Some(rule) = task.recurrence else {
+            return Ok(None);
+        };
+        let task = task.clone();
+        if let Some(next) = task.next
+            && self.tasks.iter().any(|t| t.id == next)
+        {
+            return Ok(None);
+        }
+
+        let mut due = rule.next_after(task.due.unwrap_or(today));
+        while let Some(date) = due
+            && date <= today
+        {
+            due = rule.next_after(date);
+        }
+        let Some(due) = due else {
+            return Ok(None);
+        };
+
+        let next = NewTask {
+            description: task.description,
+            priority: task.priority,
+            due: Some(due),
+            tags: task.tags,
+            recurrence: Some(rule),
+        };
+        let next = self.add(next).id;
+        self.tasks[index].next = Some(next);
+        Ok(Some(next))
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|t| t.id == id)
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Task> {
+        let index = self.tasks.iter().position(|t| t.id == id)?;
+        Some(self.tasks.remove(index))
+    }
+}
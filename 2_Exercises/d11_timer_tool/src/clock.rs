@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+
+/// Measures running time against the monotonic clock. Elapsed time is always
+/// computed from `Instant`s, never by counting ticks, so it can't drift.
+#[derive(Debug)]
+pub struct Stopwatch {
+    started: Option<Instant>,
+    banked: Duration,
+}
+
+impl Stopwatch {
+    pub fn start() -> Stopwatch {
+        Stopwatch { started: Some(Instant::now()), banked: Duration::ZERO }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match self.started {
+            Some(started) => self.banked + started.elapsed(),
+            None => self.banked,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(started) = self.started.take() {
+            self.banked += started.elapsed();
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        if self.is_running() { self.pause() } else { self.resume() }
+    }
+}
+
+/// A named countdown
+#[derive(Debug)]
+pub struct Timer {
+    pub name: String,
+    pub length: Duration,
+    pub finished: bool,
+    clock: Stopwatch,
+}
+
+impl Timer {
+    pub fn start(name: &str, length: Duration) -> Timer {
+        Timer { name: name.to_string(), length, finished: false, clock: Stopwatch::start() }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.length.saturating_sub(self.clock.elapsed())
+    }
+
+    /// The moment the timer runs out, `None` while it is paused or when it
+    /// is too far off for an `Instant`
+    pub fn deadline(&self) -> Option<Instant> {
+        self.clock.started.and_then(|started| started.checked_add(self.length.saturating_sub(self.clock.banked)))
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.clock.is_running()
+    }
+
+    pub fn toggle(&mut self) {
+        self.clock.toggle();
+    }
+}
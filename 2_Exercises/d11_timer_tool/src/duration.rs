@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+/// Longest duration accepted, longer ones are more likely typos than timers
+pub const MAX_DURATION: Duration = Duration::from_secs(30 * 24 * 3600);
+
+/// Seconds in h:m:s, None when it doesn't fit
+fn hms(h: u64, m: u64, s: u64) -> Option<u64> {
+    h.checked_mul(3600)?.checked_add(m.checked_mul(60)?)?.checked_add(s)
+}
+
+/// Parse a duration written as "1h30m", "90s", "25:00", "1:30:00",
+/// "2h 5m", a bare number of seconds or the old "H M S" form. None for
+/// anything longer than MAX_DURATION.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    parse_any_duration(text).filter(|d| *d <= MAX_DURATION)
+}
+
+fn parse_any_duration(text: &str) -> Option<Duration> {
+    let text = text.trim().to_lowercase();
+    if text.is_empty() {
+        return None;
+    }
+
+    // "25:00" is minutes and seconds, "1:30:00" hours, minutes and seconds
+    if text.contains(':') {
+        let parts: Vec<u64> = text.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+        let seconds = match parts.as_slice() {
+            [m, s] if *s < 60 => hms(0, *m, *s)?,
+            [h, m, s] if *m < 60 && *s < 60 => hms(*h, *m, *s)?,
+            _ => return None,
+        };
+        return Some(Duration::from_secs(seconds));
+    }
+
+    // Three space-separated numbers are hours, minutes and seconds
+    let numbers: Option<Vec<u64>> = text.split_whitespace().map(|p| p.parse().ok()).collect();
+    if let Some([h, m, s]) = numbers.as_deref() {
+        return Some(Duration::from_secs(hms(*h, *m, *s)?));
+    }
+
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Ok(seconds) = text.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = text.as_str();
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+        let units = rest[digits..].find(|c: char| c.is_ascii_digit()).map_or(rest.len(), |i| i + digits);
+        if digits == 0 {
+            return None;
+        }
+
+        let value: f64 = rest[..digits].parse().ok()?;
+        let unit = match &rest[digits..units] {
+            "ms" => 0.001,
+            "s" | "sec" | "secs" => 1.0,
+            "m" | "min" | "mins" => 60.0,
+            "h" | "hr" | "hrs" => 3600.0,
+            "d" => 86400.0,
+            _ => return None,
+        };
+        // Rejects values too large for a Duration, and NaN or infinity
+        let part = Duration::try_from_secs_f64(value * unit).ok()?;
+        total = total.checked_add(part)?;
+        rest = &rest[units..];
+    }
+
+    Some(total)
+}
+
+/// "01:29:59", rounding partial seconds up so a countdown never shows 00:00:00 early
+pub fn format_hms(duration: Duration) -> String {
+    let mut total = duration.as_secs();
+    if duration.subsec_nanos() > 0 {
+        total = total.saturating_add(1);
+    }
+    format!("{:02}:{:02}:{:02}", total / 3600, (total % 3600) / 60, total % 60)
+}
+
+/// "00:03:07.25", for stopwatch readings
+pub fn format_precise(duration: Duration) -> String {
+    let total = duration.as_secs();
+    let hundredths = duration.subsec_millis() / 10;
+    format!("{:02}:{:02}:{:02}.{:02}", total / 3600, (total % 3600) / 60, total % 60, hundredths)
+}
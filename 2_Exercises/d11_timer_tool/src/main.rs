@@ -1,60 +1,264 @@
-use std::io::{self, Write};
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-fn main () {
-    println!("Enter time duration: ");
+mod clock;
+mod duration;
 
-    let duration = match get_time_input() {
-        Some(dur) => dur,
+use clock::{Stopwatch, Timer};
+use duration::{MAX_DURATION, format_hms, format_precise, parse_duration};
+
+/// How a countdown screen ended
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Finished,
+    Skipped,
+    Quit,
+}
+
+struct Pomodoro {
+    work: Duration,
+    short_break: Duration,
+    long_break: Duration,
+    long_every: u32,
+    sessions: u32,
+}
+
+fn print_usage() {
+    println!("Usage:");
+    println!("  d11_timer_tool                               prompt for a duration");
+    println!("  d11_timer_tool timer <DURATION | NAME=DURATION>...");
+    println!("  d11_timer_tool stopwatch");
+    println!("  d11_timer_tool pomodoro [--work 25m] [--short 5m] [--long 15m] [--every 4] [--sessions 4]");
+    println!("DURATION: 1h30m, 90s, 25:00, 1:30:00, at most {} days", MAX_DURATION.as_secs() / 86400);
+    println!("While running type a command and press Enter:");
+    println!("  p [NAME]  pause/resume     s  skip     q  quit     (stopwatch: Enter records a lap)");
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
         None => {
-            println!{"Invalid input!"};
-            return;
+            println!("Enter time duration: ");
+            let mut input = String::new();
+            io::stdin()
+                .read_line(&mut input)
+                .expect("Invalid input!");
+
+            let Some(duration) = parse_duration(&input) else {
+                println!("Invalid input!");
+                return;
+            };
+            println!("Timer set for: {}", format_hms(duration));
+            run_timers(vec![Timer::start("Timer", duration)], &commands());
         }
-    };
+        Some("timer") => start_timers(&args[1..]),
+        Some("stopwatch") => run_stopwatch(&commands()),
+        Some("pomodoro") => match parse_pomodoro(&args[1..]) {
+            Some(config) => run_pomodoro(&config, &commands()),
+            None => print_usage(),
+        },
+        Some("help" | "--help" | "-h") => print_usage(),
+        // `d11_timer_tool 25m` works as a shortcut for `timer 25m`
+        Some(_) => start_timers(&args),
+    }
+}
+
+fn start_timers(specs: &[String]) {
+    let mut timers = Vec::new();
+    for (i, spec) in specs.iter().enumerate() {
+        let (name, text) = match spec.split_once('=') {
+            Some((name, text)) => (name.to_string(), text),
+            None => (format!("#{}", i + 1), spec.as_str()),
+        };
+        let Some(duration) = parse_duration(text) else {
+            println!("Invalid duration '{}'", text);
+            print_usage();
+            return;
+        };
+        timers.push(Timer::start(&name, duration));
+    }
 
-    println!("Timer set for: {} hours {} minutes {} seconds", duration.0, duration.1, duration.2);
-    start_timer(duration.0, duration.1, duration.2);
-    println!("Time's up!");
+    if timers.is_empty() {
+        print_usage();
+        return;
+    }
+    run_timers(timers, &commands());
 }
 
-fn start_timer(hours: u64, minutes:u64, seconds:u64) {
-    let total_seconds = seconds + minutes * 60 + hours * 3600;
+fn parse_pomodoro(args: &[String]) -> Option<Pomodoro> {
+    let value = |flag: &str| args.iter().position(|a| a == flag).map(|pos| args.get(pos + 1));
+    let duration = |flag: &str, default: u64| match value(flag) {
+        None => Some(Duration::from_secs(default * 60)),
+        Some(text) => parse_duration(text?),
+    };
+    let count = |flag: &str, default: u32| match value(flag) {
+        None => Some(default),
+        Some(text) => text?.parse().ok().filter(|n| *n > 0),
+    };
+
+    Some(Pomodoro {
+        work: duration("--work", 25)?,
+        short_break: duration("--short", 5)?,
+        long_break: duration("--long", 15)?,
+        long_every: count("--every", 4)?,
+        sessions: count("--sessions", 4)?,
+    })
+}
 
-    for i in (0..=total_seconds).rev() {
-        let hrs = i / 3600;
-        let mins = ( i % 3600) / 60;
-        let secs = i % 60;
+/// Read stdin lines on a separate thread so the display can keep ticking
+fn commands() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line.trim().to_string()).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
 
-        print!("\rTime Remaining: {:02}:{:02}:{:02}", hrs, mins, secs);
-        io::stdout().flush().unwrap();
-        if i != 0 { thread::sleep(Duration::from_secs(1)); }
+/// Wait until `until` or until a command is typed, whichever comes first
+fn wait_for_command(commands: &Receiver<String>, until: Instant) -> Option<String> {
+    let timeout = until.saturating_duration_since(Instant::now());
+    match commands.recv_timeout(timeout) {
+        Ok(command) => Some(command),
+        Err(RecvTimeoutError::Timeout) => None,
+        // stdin was closed, just keep time
+        Err(RecvTimeoutError::Disconnected) => {
+            thread::sleep(until.saturating_duration_since(Instant::now()));
+            None
+        }
     }
-    println!();
+}
+
+/// The next whole `step` after `origin`. Ticks are scheduled from a fixed
+/// origin instead of sleeping a fixed amount, so the display never drifts.
+fn next_tick(origin: Instant, step: Duration) -> Instant {
+    let steps = origin.elapsed().as_nanos() / step.as_nanos() + 1;
+    origin + step * steps as u32
+}
 
+fn render(line: &str) {
+    print!("\r\x1b[2K{}", line);
+    io::stdout().flush().unwrap();
 }
 
-fn get_time_input() -> Option<(u64, u64, u64)>{
-    let mut input = String::new();
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Invalid input!");
+fn run_timers(mut timers: Vec<Timer>, commands: &Receiver<String>) -> Outcome {
+    let origin = Instant::now();
+
+    loop {
+        for timer in timers.iter_mut().filter(|t| !t.finished && t.remaining().is_zero()) {
+            timer.finished = true;
+            render(&format!("{} is up!\x07\n", timer.name));
+        }
+        if timers.iter().all(|t| t.finished) {
+            return Outcome::Finished;
+        }
+
+        let status: Vec<String> = timers
+            .iter()
+            .filter(|t| !t.finished)
+            .map(|t| {
+                let paused = if t.is_running() { "" } else { " (paused)" };
+                format!("{} {}{}", t.name, format_hms(t.remaining()), paused)
+            })
+            .collect();
+        render(&status.join(" | "));
 
-    let parts: Vec<&str> = input.trim().split_whitespace().collect();
+        // Wake up for the next display tick, or earlier if a timer runs out
+        let wake = timers
+            .iter()
+            .filter(|t| !t.finished)
+            .filter_map(Timer::deadline)
+            .fold(next_tick(origin, Duration::from_secs(1)), Instant::min);
 
-    if parts.len() != 3{
-        return None;
+        let Some(command) = wait_for_command(commands, wake) else {
+            continue;
+        };
+        match command.split_once(' ').unwrap_or((&command, "")) {
+            ("q", _) => {
+                println!();
+                return Outcome::Quit;
+            }
+            ("s", _) => {
+                println!();
+                return Outcome::Skipped;
+            }
+            ("p", "") => {
+                // Pause everything if anything runs, otherwise resume everything
+                let running = timers.iter().any(Timer::is_running);
+                for timer in timers.iter_mut().filter(|t| t.is_running() == running) {
+                    timer.toggle();
+                }
+            }
+            ("p", name) => match timers.iter_mut().find(|t| t.name == name) {
+                Some(timer) => timer.toggle(),
+                None => println!("No timer named '{}'", name),
+            },
+            _ => println!("Commands: p [NAME], s, q"),
+        }
     }
+}
 
-    // Returns None if parse fails
-    // This is equivalent to this:
-    // let hours = match parts[0].parse::<u64>() {
-    //     Ok(h) => h,
-    //     Err(_) => return None,  // Early return
-    // }; 
-    let hours = parts[0].parse::<u64>().ok()?; 
-    let minutes = parts[1].parse::<u64>().ok()?;
-    let seconds = parts[2].parse::<u64>().ok()?;
+fn run_stopwatch(commands: &Receiver<String>) {
+    println!("Stopwatch started. Enter: lap, p: pause/resume, q: stop");
 
-    Some((hours, minutes, seconds))
-}
\ No newline at end of file
+    let origin = Instant::now();
+    let mut stopwatch = Stopwatch::start();
+    let mut laps: Vec<Duration> = Vec::new();
+
+    loop {
+        let paused = if stopwatch.is_running() { "" } else { " (paused)" };
+        render(&format!("{}{}", format_precise(stopwatch.elapsed()), paused));
+
+        let Some(command) = wait_for_command(commands, next_tick(origin, Duration::from_millis(50))) else {
+            continue;
+        };
+        match command.as_str() {
+            "" => {
+                let split = stopwatch.elapsed();
+                let lap = split - laps.last().copied().unwrap_or_default();
+                laps.push(split);
+                println!("\rLap {:>3}  {}  {}", laps.len(), format_precise(lap), format_precise(split));
+            }
+            "p" => stopwatch.toggle(),
+            "q" => break,
+            _ => println!("Commands: Enter, p, q"),
+        }
+    }
+
+    let total = stopwatch.elapsed();
+    render(&format!("Total: {}\n", format_precise(total)));
+
+    if laps.len() > 1 {
+        let times: Vec<Duration> = laps.iter().zip([Duration::ZERO].iter().chain(&laps)).map(|(a, b)| *a - *b).collect();
+        let fastest = times.iter().enumerate().min_by_key(|(_, t)| **t).map(|(i, _)| i + 1);
+        let slowest = times.iter().enumerate().max_by_key(|(_, t)| **t).map(|(i, _)| i + 1);
+        println!("Fastest lap: {}, slowest lap: {}", fastest.unwrap_or(0), slowest.unwrap_or(0));
+    }
+}
+
+fn run_pomodoro(config: &Pomodoro, commands: &Receiver<String>) {
+    for session in 1..=config.sessions {
+        let mut phases = vec![(format!("Work {}/{}", session, config.sessions), config.work)];
+        if session % config.long_every == 0 {
+            phases.push(("Long break".to_string(), config.long_break));
+        } else if session < config.sessions {
+            phases.push(("Short break".to_string(), config.short_break));
+        }
+
+        for (name, length) in phases {
+            println!("{} ({})", name, format_hms(length));
+            if run_timers(vec![Timer::start(&name, length)], commands) == Outcome::Quit {
+                return;
+            }
+        }
+    }
+    println!("All {} sessions done!", config.sessions);
+}
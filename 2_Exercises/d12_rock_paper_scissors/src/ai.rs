@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use crate::rules::Rules;
+
+/// Older observations fade by this factor each round, so the model follows
+/// the player's current habits instead of the whole session
+const DECAY: f64 = 0.95;
+/// Share of rounds played at random, so the AI itself stays unpredictable
+const EXPLORATION: f64 = 0.1;
+
+pub enum Strategy {
+    Random,
+    Markov(Markov),
+}
+
+impl Strategy {
+    pub fn parse(text: &str, order: usize) -> Option<Strategy> {
+        match text {
+            "random" => Some(Strategy::Random),
+            "markov" | "adaptive" => Some(Strategy::Markov(Markov::new(order))),
+            _ => None,
+        }
+    }
+
+    pub fn choose(&mut self, rules: &Rules, rng: &mut impl Rng) -> usize {
+        match self {
+            Strategy::Random => rng.random_range(0..rules.moves.len()),
+            Strategy::Markov(model) => model.choose(rules, rng),
+        }
+    }
+
+    pub fn observe(&mut self, player_move: usize) {
+        if let Strategy::Markov(model) = self {
+            model.observe(player_move);
+        }
+    }
+}
+
+/// Predicts the player's next move from their last `order` moves. Shorter
+/// contexts are kept too and used when the longer one hasn't been seen yet.
+pub struct Markov {
+    order: usize,
+    history: Vec<usize>,
+    counts: HashMap<Vec<usize>, Vec<f64>>,
+    pub predicted: usize,
+    pub correct: usize,
+    last_prediction: Option<usize>,
+}
+
+impl Markov {
+    pub fn new(order: usize) -> Markov {
+        Markov { order, history: Vec::new(), counts: HashMap::new(), predicted: 0, correct: 0, last_prediction: None }
+    }
+
+    /// Probability of each player move, `None` when nothing is known yet
+    fn predict(&self, moves: usize) -> Option<Vec<f64>> {
+        for length in (1..=self.order.min(self.history.len())).rev() {
+            let context = &self.history[self.history.len() - length..];
+            if let Some(counts) = self.counts.get(context) {
+                let total: f64 = counts.iter().sum();
+                if total > 0.0 {
+                    return Some((0..moves).map(|m| counts.get(m).unwrap_or(&0.0) / total).collect());
+                }
+            }
+        }
+        None
+    }
+
+    /// Pick the move with the best expected score against the predicted player move
+    fn choose(&mut self, rules: &Rules, rng: &mut impl Rng) -> usize {
+        let moves = rules.moves.len();
+        let Some(odds) = self.predict(moves) else {
+            self.last_prediction = None;
+            return rng.random_range(0..moves);
+        };
+
+        let likely = (0..moves).max_by(|a, b| odds[*a].total_cmp(&odds[*b])).unwrap_or(0);
+        self.last_prediction = Some(likely);
+
+        if rng.random_bool(EXPLORATION) {
+            return rng.random_range(0..moves);
+        }
+
+        let score = |mine: usize| -> f64 {
+            (0..moves)
+                .map(|theirs| {
+                    let points = if rules.beats(mine, theirs) {
+                        1.0
+                    } else if rules.beats(theirs, mine) {
+                        -1.0
+                    } else {
+                        0.0
+                    };
+                    odds[theirs] * points
+                })
+                .sum()
+        };
+
+        let scores: Vec<f64> = (0..moves).map(score).collect();
+        let best = scores.iter().copied().fold(f64::MIN, f64::max);
+        let candidates: Vec<usize> = (0..moves).filter(|m| best - scores[*m] < 1e-9).collect();
+        *candidates.choose(rng).unwrap_or(&0)
+    }
+
+    fn observe(&mut self, player_move: usize) {
+        if let Some(prediction) = self.last_prediction.take() {
+            self.predicted += 1;
+            if prediction == player_move {
+                self.correct += 1;
+            }
+        }
+
+        for counts in self.counts.values_mut() {
+            counts.iter_mut().for_each(|c| *c *= DECAY);
+        }
+        for length in 1..=self.order.min(self.history.len()) {
+            let context = self.history[self.history.len() - length..].to_vec();
+            let counts = self.counts.entry(context).or_default();
+            if counts.len() <= player_move {
+                counts.resize(player_move + 1, 0.0);
+            }
+            counts[player_move] += 1.0;
+        }
+
+        self.history.push(player_move);
+        if self.history.len() > self.order {
+            self.history.remove(0);
+        }
+    }
+}
@@ -1,67 +1,247 @@
-use std::io;
-use rand::Rng;
+use std::env;
+use std::io::{self, Write};
+use std::path::Path;
 
-enum GameResult {
-    Win,
-    Lose,
-    Draw
+mod ai;
+mod net;
+mod rules;
+
+use ai::Strategy;
+use rules::{GameResult, Rules};
+
+enum Choice {
+    Move(usize),
+    Stats,
+    Quit,
+}
+
+/// Totals over the whole session
+#[derive(Default)]
+struct Stats {
+    wins: u32,
+    losses: u32,
+    draws: u32,
+    matches_won: u32,
+    matches_lost: u32,
+    moves: Vec<u32>,
+}
+
+impl Stats {
+    fn record(&mut self, user: usize, result: &GameResult) {
+        match result {
+            GameResult::Win => self.wins += 1,
+            GameResult::Lose => self.losses += 1,
+            GameResult::Draw => self.draws += 1,
+        }
+        if self.moves.len() <= user {
+            self.moves.resize(user + 1, 0);
+        }
+        self.moves[user] += 1;
+    }
+
+    fn print(&self, rules: &Rules, strategy: &Strategy) {
+        let rounds = self.wins + self.losses + self.draws;
+        println!("\nSession statistics:");
+        println!("  Rounds: {} (won {}, lost {}, drawn {})", rounds, self.wins, self.losses, self.draws);
+        if self.matches_won + self.matches_lost > 0 {
+            println!("  Matches: won {}, lost {}", self.matches_won, self.matches_lost);
+        }
+        if rounds > 0 {
+            println!("  Win rate: {:.1}%", 100.0 * self.wins as f64 / rounds as f64);
+            for (id, count) in self.moves.iter().enumerate().filter(|(_, c)| **c > 0) {
+                println!("  {}: played {} times", rules.moves[id], count);
+            }
+        }
+        if let Strategy::Markov(model) = strategy
+            && model.predicted > 0
+        {
+            let accuracy = 100.0 * model.correct as f64 / model.predicted as f64;
+            println!("  Computer predicted your move {:.1}% of the time", accuracy);
+        }
+    }
+}
+
+fn print_usage() {
+    println!("Usage:");
+    println!("  d12_rock_paper_scissors [--rules FILE] [--best-of N] [--ai markov|random] [--order K]");
+    println!("  d12_rock_paper_scissors host [PORT] [--public] [--rules FILE] [--best-of N]    play against someone else");
+    println!("  d12_rock_paper_scissors join [HOST:PORT]                          (default 127.0.0.1:{})", net::DEFAULT_PORT);
+    println!("  --rules FILE   rule table, e.g. rules/rpsls.txt (default: rock paper scissors)");
+    println!("  --best-of N    play matches of N rounds, draws don't count");
+    println!("  --public       let players on other machines join, by default only local ones can");
+    println!("  --ai           markov learns your habits (default), random picks uniformly");
+    println!("  --order K      how many of your previous moves the markov AI looks at (default 2)");
+}
+
+fn value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let pos = args.iter().position(|a| a == flag)?;
+    args.get(pos + 1).map(String::as_str)
 }
 
 fn main() {
-    println!("Enter 'rock', 'paer', or 'scissors'. Type 'quit' to exit!");
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        print_usage();
+        return;
+    }
 
-    loop {
-        println!("Make your choice: ");
+    let rules = match value(&args, "--rules") {
+        Some(path) => match Rules::load(Path::new(path)) {
+            Ok(rules) => rules,
+            Err(e) => {
+                println!("Invalid rules file {}: {}", path, e);
+                return;
+            }
+        },
+        None => Rules::classic(),
+    };
 
-        let user_choice = get_user_choice();
+    let best_of = match value(&args, "--best-of").map(str::parse::<u32>) {
+        None => None,
+        Some(Ok(n)) if n > 0 => Some(n),
+        Some(_) => {
+            print_usage();
+            return;
+        }
+    };
 
-        if user_choice == "quit" {
-            println!("Bye!");
-            break;
+    let result = match args.first().map(String::as_str) {
+        Some("host") => {
+            let port = args.get(1).and_then(|p| p.parse().ok()).unwrap_or(net::DEFAULT_PORT);
+            let public = args.iter().any(|a| a == "--public");
+            Some(net::host(port, public, &rules, best_of))
         }
+        Some("join") => {
+            let default = format!("127.0.0.1:{}", net::DEFAULT_PORT);
+            let address = args.get(1).filter(|a| !a.starts_with("--")).unwrap_or(&default);
+            Some(net::join(address))
+        }
+        _ => None,
+    };
+    if let Some(result) = result {
+        if let Err(e) = result {
+            println!("Game over: {}", e);
+        }
+        return;
+    }
 
-        let computer_choice = get_computer_choice();
+    let order = value(&args, "--order").and_then(|o| o.parse().ok()).filter(|o| *o > 0).unwrap_or(2);
+    let Some(mut strategy) = Strategy::parse(value(&args, "--ai").unwrap_or("markov"), order) else {
+        print_usage();
+        return;
+    };
 
-        println!("Computer choice: {}", computer_choice);
+    println!("{}: enter {}. Type 'stats' for statistics, 'quit' to exit!", rules.name, rules.moves.join(", "));
+    if let Some(n) = best_of {
+        println!("Best of {}: first to {} wins takes the match.", n, n / 2 + 1);
+    }
+
+    let mut rng = rand::rng();
+    let mut stats = Stats::default();
+    let (mut match_wins, mut match_losses) = (0, 0);
+
+    loop {
+        println!("Make your choice: ");
+
+        let user_choice = match get_user_choice(&rules) {
+            Choice::Move(id) => id,
+            Choice::Stats => {
+                stats.print(&rules, &strategy);
+                continue;
+            }
+            Choice::Quit => break,
+        };
 
-        match determine_winner(&user_choice, &computer_choice) {
+        let computer_choice = strategy.choose(&rules, &mut rng);
+        strategy.observe(user_choice);
+
+        println!("Computer choice: {}", rules.moves[computer_choice]);
+        if let Some(reason) = rules.describe(user_choice, computer_choice) {
+            println!("{}.", reason);
+        }
+
+        let result = rules.determine_winner(user_choice, computer_choice);
+        match result {
             GameResult::Win => println!("You win!"),
             GameResult::Lose => println!("You lose!"),
             GameResult::Draw => println!("It's a draw!"),
         }
-    }
-}
+        stats.record(user_choice, &result);
+
+        let Some(n) = best_of else {
+            continue;
+        };
+        match result {
+            GameResult::Win => match_wins += 1,
+            GameResult::Lose => match_losses += 1,
+            GameResult::Draw => {}
+        }
+        println!("Match score: {} - {}", match_wins, match_losses);
+
+        let needed = n / 2 + 1;
+        if match_wins == needed || match_losses == needed {
+            if match_wins == needed {
+                println!("You won the match!");
+                stats.matches_won += 1;
+            } else {
+                println!("The computer won the match!");
+                stats.matches_lost += 1;
+            }
+            (match_wins, match_losses) = (0, 0);
 
-fn determine_winner(user: &str, computer:&str) -> GameResult {
-    match (user, computer) {
-        ("rock", "scissors") => GameResult::Win,
-        ("scissors", "paper") => GameResult::Win,
-        ("paper", "rock") => GameResult::Win,
-        (a,b) if a == b => GameResult::Draw,
-        _ => GameResult::Lose
+            if !ask_yes_no("Play another match? (y/n)") {
+                break;
+            }
+        }
     }
-}
 
-fn get_computer_choice() -> String {
-    let choices = ["rock", "paper", "scissors"];
-    let index = rand::rng().random_range(0..choices.len());
-    choices[index].to_string()
+    stats.print(&rules, &strategy);
+    println!("Bye!");
 }
 
-fn get_user_choice() -> String {
-    let mut choice = String::new();
+fn ask_yes_no(prompt: &str) -> bool {
+    println!("{}", prompt);
+    let mut answer = String::new();
     io::stdin()
-        .read_line(&mut choice)
+        .read_line(&mut answer)
         .expect("Invalid input!");
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Ask until the input is a move (by name, unique prefix or number) or a command
+fn get_user_choice(rules: &Rules) -> Choice {
+    loop {
+        let mut choice = String::new();
+        let read = io::stdin()
+            .read_line(&mut choice)
+            .expect("Invalid input!");
+        // End of input counts as quitting
+        if read == 0 {
+            return Choice::Quit;
+        }
+
+        let choice = choice.trim().to_lowercase();
+        match choice.as_str() {
+            "quit" | "q" | "exit" => return Choice::Quit,
+            "stats" => return Choice::Stats,
+            _ => {}
+        }
 
-    let choice = choice.trim().to_lowercase();
+        if let Ok(number) = choice.parse::<usize>()
+            && (1..=rules.moves.len()).contains(&number)
+        {
+            return Choice::Move(number - 1);
+        }
 
-    match choice.as_str() {
-        "rock" | "paper" | "scissors" | "quit" => choice,
-        _ => {
-            println!("Invalid choice! Choice again:");
-            get_user_choice()
+        let matching: Vec<usize> = (0..rules.moves.len())
+            .filter(|id| !choice.is_empty() && rules.moves[*id].starts_with(&choice))
+            .collect();
+        match (rules.find(&choice), matching.as_slice()) {
+            (Some(id), _) | (None, &[id]) => return Choice::Move(id),
+            _ => {
+                print!("Invalid choice! Choose one of {}: ", rules.moves.join(", "));
+                io::stdout().flush().unwrap();
+            }
         }
     }
 }
-
@@ -0,0 +1,193 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::rules::{GameResult, Rules};
+use crate::{Choice, ask_yes_no, get_user_choice};
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// One line based connection to the other player.
+///
+/// Every round both sides first send `COMMIT <sha256(nonce:move)>` and only
+/// after receiving the other commitment send `REVEAL <move> <nonce>`, so neither
+/// player can see the other move before locking in their own.
+struct Peer {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Peer {
+    fn new(stream: TcpStream) -> io::Result<Peer> {
+        Ok(Peer { reader: BufReader::new(stream.try_clone()?), writer: stream })
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", line)
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::BrokenPipe | io::ErrorKind::ConnectionReset => {
+                    io::Error::new(e.kind(), "the other player disconnected")
+                }
+                _ => e,
+            })
+    }
+
+    /// Next message, split into its keyword and the rest of the line
+    fn receive(&mut self) -> io::Result<(String, String)> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the other player disconnected"));
+        }
+
+        let line = line.trim_end();
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        Ok((keyword.to_string(), rest.to_string()))
+    }
+
+    fn expect(&mut self, keyword: &str) -> io::Result<String> {
+        match self.receive()? {
+            (k, rest) if k == keyword => Ok(rest),
+            (k, _) if k == "QUIT" => Err(io::Error::new(io::ErrorKind::ConnectionAborted, "the other player left")),
+            (k, _) => Err(io::Error::other(format!("expected {} from the other player, got {}", keyword, k))),
+        }
+    }
+}
+
+fn commitment(nonce: &str, choice: &str) -> String {
+    hex::encode(Sha256::digest(format!("{}:{}", nonce, choice)))
+}
+
+/// Wait for a player to connect, then play with our rules. Only players on
+/// this machine can join unless `public` is set, anyone may connect and
+/// nothing checks who they are.
+pub fn host(port: u16, public: bool, rules: &Rules, best_of: Option<u32>) -> io::Result<()> {
+    let address = if public { "0.0.0.0" } else { "127.0.0.1" };
+    let listener = TcpListener::bind((address, port))?;
+    println!("Waiting for a player on {}:{}...", address, port);
+
+    let (stream, address) = listener.accept()?;
+    println!("{} joined!", address);
+
+    let mut peer = Peer::new(stream)?;
+    let text = rules.to_text();
+    peer.send(&format!("RULES {}", text.lines().count()))?;
+    for line in text.lines() {
+        peer.send(line)?;
+    }
+    peer.send(&format!("BESTOF {}", best_of.unwrap_or(0)))?;
+
+    play(&mut peer, rules, best_of)
+}
+
+/// Connect to a host and play with the rules it sends
+pub fn join(address: &str) -> io::Result<()> {
+    let stream = TcpStream::connect(address)?;
+    println!("Connected to {}", address);
+    let mut peer = Peer::new(stream)?;
+
+    let count: usize = peer.expect("RULES")?.parse().map_err(io::Error::other)?;
+    let mut text = Vec::new();
+    for _ in 0..count {
+        let (keyword, rest) = peer.receive()?;
+        text.push(if rest.is_empty() { keyword } else { format!("{} {}", keyword, rest) });
+    }
+    let rules = Rules::parse(&text.join("\n")).map_err(|e| io::Error::other(format!("invalid rules from host: {}", e)))?;
+
+    let best_of = match peer.expect("BESTOF")?.parse().map_err(io::Error::other)? {
+        0 => None,
+        n => Some(n),
+    };
+
+    play(&mut peer, &rules, best_of)
+}
+
+fn play(peer: &mut Peer, rules: &Rules, best_of: Option<u32>) -> io::Result<()> {
+    println!("{}: enter {}. Type 'quit' to leave.", rules.name, rules.moves.join(", "));
+    if let Some(n) = best_of {
+        println!("Best of {}: first to {} wins takes the match.", n, n / 2 + 1);
+    }
+
+    let mut rng = rand::rng();
+    let (mut wins, mut losses, mut draws) = (0, 0, 0);
+    let (mut match_wins, mut match_losses) = (0, 0);
+
+    loop {
+        println!("Make your choice: ");
+        let choice = loop {
+            match get_user_choice(rules) {
+                Choice::Move(id) => break Some(id),
+                Choice::Stats => println!("Score: you {} - {} opponent ({} draws)", wins, losses, draws),
+                Choice::Quit => break None,
+            }
+        };
+        let Some(choice) = choice else {
+            peer.send("QUIT")?;
+            break;
+        };
+
+        let nonce = hex::encode(rng.random::<[u8; 16]>());
+        let name = &rules.moves[choice];
+        peer.send(&format!("COMMIT {}", commitment(&nonce, name)))?;
+        println!("Waiting for the other player...");
+        let their_commit = peer.expect("COMMIT")?;
+
+        peer.send(&format!("REVEAL {} {}", name, nonce))?;
+        let reveal = peer.expect("REVEAL")?;
+        let (their_name, their_nonce) = reveal.split_once(' ').unwrap_or((&reveal, ""));
+        if commitment(their_nonce, their_name) != their_commit {
+            return Err(io::Error::other("the other player changed their move after committing"));
+        }
+        let their_choice = rules.find(their_name).ok_or_else(|| io::Error::other(format!("unknown move '{}'", their_name)))?;
+
+        println!("Opponent choice: {}", their_name);
+        if let Some(reason) = rules.describe(choice, their_choice) {
+            println!("{}.", reason);
+        }
+        match rules.determine_winner(choice, their_choice) {
+            GameResult::Win => {
+                println!("You win!");
+                wins += 1;
+                match_wins += 1;
+            }
+            GameResult::Lose => {
+                println!("You lose!");
+                losses += 1;
+                match_losses += 1;
+            }
+            GameResult::Draw => {
+                println!("It's a draw!");
+                draws += 1;
+            }
+        }
+        println!("Score: you {} - {} opponent ({} draws)", wins, losses, draws);
+
+        let Some(n) = best_of else {
+            continue;
+        };
+        println!("Match score: {} - {}", match_wins, match_losses);
+
+        let needed = n / 2 + 1;
+        if match_wins == needed || match_losses == needed {
+            println!("{}", if match_wins == needed { "You won the match!" } else { "Your opponent won the match!" });
+            (match_wins, match_losses) = (0, 0);
+
+            // Both players have to agree to a rematch
+            let again = ask_yes_no("Rematch? (y/n)");
+            peer.send(if again { "REMATCH yes" } else { "REMATCH no" })?;
+            if !again {
+                break;
+            }
+            println!("Waiting for the other player...");
+            if peer.expect("REMATCH")? != "yes" {
+                println!("The other player doesn't want a rematch.");
+                break;
+            }
+        }
+    }
+
+    println!("Final score: you {} - {} opponent ({} draws)", wins, losses, draws);
+    Ok(())
+}
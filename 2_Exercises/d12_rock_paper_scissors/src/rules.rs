@@ -0,0 +1,179 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+pub enum GameResult {
+    Win,
+    Lose,
+    Draw,
+}
+
+#[derive(Debug)]
+pub struct RulesError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+/// Who beats whom. Moves are referred to by their index in `moves`.
+#[derive(Debug)]
+pub struct Rules {
+    pub name: String,
+    pub moves: Vec<String>,
+    /// `verbs[a][b]` is set when `a` beats `b`, e.g. "cuts"
+    verbs: Vec<Vec<Option<String>>>,
+}
+
+impl Rules {
+    pub fn classic() -> Rules {
+        Rules::parse("name Rock Paper Scissors\nmoves rock paper scissors\nrock crushes scissors\nscissors cuts paper\npaper covers rock")
+            .expect("built-in rules are valid")
+    }
+
+    pub fn load(path: &Path) -> Result<Rules, RulesError> {
+        let text = fs::read_to_string(path).map_err(|e| RulesError { line: 0, message: e.to_string() })?;
+        Rules::parse(&text)
+    }
+
+    /// One rule per line, `#` starts a comment:
+    ///
+    /// ```text
+    /// name Rock Paper Scissors Lizard Spock
+    /// scissors cuts paper        # winner, verb, loser
+    /// cycle a b c d e            # every move beats the (n-1)/2 moves after it
+    /// moves rock paper scissors  # fixes the order moves are listed in
+    /// ```
+    pub fn parse(text: &str) -> Result<Rules, RulesError> {
+        let mut rules = Rules { name: "Custom".to_string(), moves: Vec::new(), verbs: Vec::new() };
+
+        for (i, line) in text.lines().enumerate() {
+            let error = |message: String| RulesError { line: i + 1, message };
+            let line = line.split('#').next().unwrap_or("").trim();
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            match words.as_slice() {
+                [] => {}
+                ["name", name @ ..] => rules.name = name.join(" "),
+                ["moves", moves @ ..] => {
+                    for name in moves {
+                        rules.add_move(name);
+                    }
+                }
+                ["cycle", moves @ ..] => {
+                    if moves.len() < 3 || moves.len() % 2 == 0 {
+                        return Err(error("a cycle needs an odd number of at least 3 moves".to_string()));
+                    }
+                    let ids: Vec<usize> = moves.iter().map(|m| rules.add_move(m)).collect();
+                    for (a, winner) in ids.iter().enumerate() {
+                        for step in 1..=ids.len() / 2 {
+                            let loser = ids[(a + step) % ids.len()];
+                            rules.add_rule(*winner, loser, "beats").map_err(error)?;
+                        }
+                    }
+                }
+                [winner, verb @ .., loser] if !verb.is_empty() => {
+                    let (winner, loser) = (rules.add_move(winner), rules.add_move(loser));
+                    if winner == loser {
+                        return Err(error("a move can't beat itself".to_string()));
+                    }
+                    rules.add_rule(winner, loser, &verb.join(" ")).map_err(error)?;
+                }
+                _ => return Err(error(format!("expected 'WINNER VERB LOSER', got '{}'", line))),
+            }
+        }
+
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// The table in the format `parse` reads, used to send it to another player
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![format!("name {}", self.name), format!("moves {}", self.moves.join(" "))];
+        for (winner, row) in self.verbs.iter().enumerate() {
+            for (loser, verb) in row.iter().enumerate() {
+                if let Some(verb) = verb {
+                    lines.push(format!("{} {} {}", self.moves[winner], verb, self.moves[loser]));
+                }
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn add_move(&mut self, name: &str) -> usize {
+        let name = name.to_lowercase();
+        if let Some(id) = self.find(&name) {
+            return id;
+        }
+
+        self.moves.push(name);
+        for row in &mut self.verbs {
+            row.push(None);
+        }
+        self.verbs.push(vec![None; self.moves.len()]);
+        self.moves.len() - 1
+    }
+
+    fn add_rule(&mut self, winner: usize, loser: usize, verb: &str) -> Result<(), String> {
+        if self.verbs[loser][winner].is_some() {
+            return Err(format!("{} and {} beat each other", self.moves[winner], self.moves[loser]));
+        }
+        self.verbs[winner][loser] = Some(verb.to_string());
+        Ok(())
+    }
+
+    /// Every pair of different moves needs a winner, otherwise the game can't be decided
+    fn validate(&self) -> Result<(), RulesError> {
+        if self.moves.len() < 3 {
+            return Err(RulesError { line: 0, message: "at least 3 moves are needed".to_string() });
+        }
+
+        for a in 0..self.moves.len() {
+            for b in a + 1..self.moves.len() {
+                if self.verbs[a][b].is_none() && self.verbs[b][a].is_none() {
+                    let message = format!("no rule between {} and {}", self.moves[a], self.moves[b]);
+                    return Err(RulesError { line: 0, message });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.moves.iter().position(|m| m == name)
+    }
+
+    pub fn beats(&self, a: usize, b: usize) -> bool {
+        self.verbs[a][b].is_some()
+    }
+
+    pub fn determine_winner(&self, user: usize, computer: usize) -> GameResult {
+        if user == computer {
+            GameResult::Draw
+        } else if self.beats(user, computer) {
+            GameResult::Win
+        } else {
+            GameResult::Lose
+        }
+    }
+
+    /// "Scissors cuts paper", `None` for a draw
+    pub fn describe(&self, a: usize, b: usize) -> Option<String> {
+        let (winner, loser) = if self.beats(a, b) { (a, b) } else { (b, a) };
+        let verb = self.verbs[winner][loser].as_ref()?;
+
+        let mut chars = self.moves[winner].chars();
+        let first = chars.next()?.to_uppercase();
+        Some(format!("{}{} {} {}", first, chars.as_str(), verb, self.moves[loser]))
+    }
+}
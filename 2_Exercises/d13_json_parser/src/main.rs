@@ -1,23 +1,157 @@
 use std::env;
-use std::fs;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process;
+
 use serde_json::Value;
 
+mod parser;
+mod pretty;
+mod query;
+mod schema;
+
+use parser::{Parser, read_value};
+use query::Query;
+use schema::Validator;
+
+fn print_usage() {
+    eprintln!("Usage: cargo run <path_to_json_file> [QUERY] [-r | --raw] [-c | --compact] [--lenient]");
+    eprintln!("       cargo run <path_to_json_file> --events [--lenient]");
+    eprintln!("       cargo run validate <schema_file> <path_to_json_file> [--lenient]");
+    eprintln!("  Use '-' as the path to read from stdin.");
+    eprintln!("  QUERY examples: .users[0].name   .users[] | select(.age > 30) | .name");
+    eprintln!("                  .items[2:5]   .config.*   .users | map(.name)   keys   length");
+    eprintln!("  --raw      print strings without quotes");
+    eprintln!("  --compact  print each result on one line");
+    eprintln!("  --lenient  accept comments, trailing commas, single quotes and unquoted keys (JSON5)");
+    eprintln!("  --events   print the parser events one per line, in constant memory");
+    eprintln!("  validate exits with 0 when the document is valid, 1 when it isn't and 2 on other errors");
+}
+
+fn open_input(path: &str) -> io::Result<Box<dyn BufRead>> {
+    if path == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::with_capacity(64 * 1024, File::open(path)?)))
+    }
+}
+
+fn load_json(path: &str, lenient: bool) -> Result<Value, String> {
+    let input = open_input(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    read_value(&mut Parser::new(input, lenient)).map_err(|e| format!("Invalid JSON in {}: {}", path, e))
+}
+
+/// Check a document against a schema and exit with the result
+fn validate(schema_path: &str, document_path: &str, lenient: bool) -> ! {
+    let (schema, document) = match (load_json(schema_path, lenient), load_json(document_path, lenient)) {
+        (Ok(schema), Ok(document)) => (schema, document),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+
+    match Validator::new(&schema).validate(&document) {
+        Ok(violations) if violations.is_empty() => {
+            println!("{} is valid", document_path);
+            process::exit(0);
+        }
+        Ok(violations) => {
+            for violation in &violations {
+                println!("{}", violation);
+            }
+            eprintln!("{} is invalid: {} error(s)", document_path, violations.len());
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    }
+}
+
 fn main() {
     // Get the arguments from the CLI
-    let args:Vec<String> = env::args().collect();
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    if args.len() != 2 {
-        eprintln!("cargo run <path_to_json_file>");
-        return;
+    let raw = args.iter().any(|a| a == "-r" || a == "--raw");
+    let compact = args.iter().any(|a| a == "-c" || a == "--compact");
+    let lenient = args.iter().any(|a| a == "--lenient" || a == "--json5");
+    let events = args.iter().any(|a| a == "--events");
+    let positional: Vec<&String> = args.iter().filter(|a| a.as_str() == "-" || !a.starts_with('-')).collect();
+
+    if let [command, schema, document] = positional.as_slice()
+        && command.as_str() == "validate"
+    {
+        validate(schema, document, lenient);
     }
 
-    let path = &args[1];
+    let (path, query) = match positional.as_slice() {
+        [path] => (path, None),
+        [path, query] => (path, Some(query)),
+        _ => {
+            print_usage();
+            process::exit(2);
+        }
+    };
+
+    let query = match query.map(|q| Query::parse(q)).transpose() {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("Invalid query: {}", e);
+            process::exit(2);
+        }
+    };
+
+    // Without a query the document is streamed, so files of any size work
+    let Some(query) = query else {
+        let input = match open_input(path) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", path, e);
+                process::exit(1);
+            }
+        };
+
+        let mut parser = Parser::new(input, lenient);
+        let mut out = BufWriter::new(io::stdout().lock());
+        // Streamed like jq: an error stops the output where it happened,
+        // after whatever came before it
+        let result = if events {
+            pretty::write_events(&mut parser, &mut out)
+        } else {
+            writeln!(out, "Parsed JSON:").map_err(Into::into).and_then(|_| pretty::write_pretty(&mut parser, &mut out))
+        };
+
+        out.flush().ok();
+        if let Err(e) = result {
+            eprintln!("\nInvalid JSON in {}: {}", path, e);
+            process::exit(1);
+        }
+        return;
+    };
+
+    let json = match load_json(path, lenient) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
 
-    match fs::read_to_string(path) {
-        Ok(content) => match serde_json::from_str::<Value>(&content) {
-            Ok(json) => println!("Parsed JSON:\n{}", serde_json::to_string_pretty(&json).unwrap()),
-            Err(e) => eprintln!("Invalid JSON: {}", e),
-        },
-        Err(e) => eprintln!("Failed to read file: {}", e),
+    match query.run(&json) {
+        Ok(results) => {
+            for result in results {
+                match result {
+                    Value::String(text) if raw => println!("{}", text),
+                    value if compact => println!("{}", value),
+                    value => println!("{}", serde_json::to_string_pretty(&value).unwrap()),
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("Query failed: {}", e);
+            process::exit(1);
+        }
     }
 }
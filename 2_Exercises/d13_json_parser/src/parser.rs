@@ -0,0 +1,627 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, BufRead};
+
+use serde_json::{Map, Number, Value};
+
+/// How much of the current line is remembered for error messages. Only this
+/// window is kept, so a huge single-line file still parses in constant memory.
+const CONTEXT_BYTES: usize = 60;
+
+/// Nesting limit, so hostile input can't grow the container stack without bound
+const MAX_DEPTH: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(String),
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+}
+
+/// A syntax or read error with the place it happened. `context` holds the
+/// line around it and `caret` the character offset of the error in `context`.
+#[derive(Debug)]
+pub struct ParseError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub context: String,
+    pub caret: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} at line {}, column {}", self.message, self.line, self.column)?;
+        writeln!(f, "  {}", self.context)?;
+        write!(f, "  {}^", " ".repeat(self.caret))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Bytes from a `BufRead`, with line and column tracking
+struct Input<R> {
+    reader: R,
+    line: usize,
+    column: usize,
+    /// The tail of the current line, for error messages
+    recent: VecDeque<u8>,
+}
+
+impl<R: BufRead> Input<R> {
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    fn bump(&mut self) -> io::Result<Option<u8>> {
+        let Some(byte) = self.peek()? else {
+            return Ok(None);
+        };
+        self.reader.consume(1);
+
+        if byte == b'\n' {
+            self.line += 1;
+            self.column = 1;
+            self.recent.clear();
+        } else {
+            // Continuation bytes of a UTF-8 sequence don't start a new column
+            if byte & 0xC0 != 0x80 {
+                self.column += 1;
+            }
+            if self.recent.len() == CONTEXT_BYTES {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(byte);
+        }
+        Ok(Some(byte))
+    }
+
+    /// Build an error pointing at the next unread character
+    fn error(&mut self, message: impl Into<String>) -> ParseError {
+        let before = String::from_utf8_lossy(self.recent.make_contiguous()).into_owned();
+
+        // Show a little of what comes after, without reading past the line
+        let mut after = Vec::new();
+        while after.len() < CONTEXT_BYTES / 3 {
+            match self.reader.fill_buf() {
+                Ok([byte, ..]) if *byte != b'\n' && *byte != b'\r' => {
+                    after.push(*byte);
+                    self.reader.consume(1);
+                }
+                _ => break,
+            }
+        }
+
+        let caret = before.chars().count();
+        let context = format!("{}{}", before, String::from_utf8_lossy(&after)).replace('\t', " ");
+        ParseError { message: message.into(), line: self.line, column: self.column, context, caret }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Container {
+    Object,
+    Array,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expect {
+    /// A value at the top level or after a key
+    Value,
+    /// Right after `[`: a value or `]`
+    FirstItem,
+    /// After a `,` in an array
+    Item,
+    /// Right after `{`: a key or `}`
+    FirstKey,
+    /// After a `,` in an object
+    Key,
+    /// After a complete value: `,`, a closing bracket or the end of input
+    AfterValue,
+    Done,
+}
+
+/// Pull parser: each call to `next_event` reads just enough input for one
+/// event. Memory use depends on nesting depth and the longest string, not on
+/// the size of the input.
+pub struct Parser<R> {
+    input: Input<R>,
+    stack: Vec<Container>,
+    expect: Expect,
+    /// Accept JSON5 style comments, trailing commas, single quoted strings and unquoted keys
+    lenient: bool,
+}
+
+impl<R: BufRead> Parser<R> {
+    pub fn new(reader: R, lenient: bool) -> Parser<R> {
+        let input = Input { reader, line: 1, column: 1, recent: VecDeque::new() };
+        Parser { input, stack: Vec::new(), expect: Expect::Value, lenient }
+    }
+
+    fn read<T>(&mut self, result: io::Result<T>) -> Result<T, ParseError> {
+        result.map_err(|e| self.input.error(format!("read error: {}", e)))
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, ParseError> {
+        let result = self.input.peek();
+        self.read(result)
+    }
+
+    fn bump(&mut self) -> Result<Option<u8>, ParseError> {
+        let result = self.input.bump();
+        self.read(result)
+    }
+
+    fn unexpected(&mut self, expected: &str) -> ParseError {
+        match self.peek() {
+            Ok(Some(byte)) if byte.is_ascii_graphic() => {
+                self.input.error(format!("expected {}, found '{}'", expected, byte as char))
+            }
+            Ok(Some(_)) => self.input.error(format!("expected {}", expected)),
+            Ok(None) => self.input.error(format!("unexpected end of input, expected {}", expected)),
+            Err(e) => e,
+        }
+    }
+
+    /// Skip whitespace, and comments in lenient mode
+    fn skip_whitespace(&mut self) -> Result<(), ParseError> {
+        loop {
+            match self.peek()? {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => {
+                    self.bump()?;
+                }
+                Some(b'/') if self.lenient => {
+                    self.bump()?;
+                    match self.bump()? {
+                        Some(b'/') => {
+                            while !matches!(self.bump()?, Some(b'\n') | None) {}
+                        }
+                        Some(b'*') => {
+                            let mut star = false;
+                            loop {
+                                match self.bump()? {
+                                    Some(b'/') if star => break,
+                                    Some(byte) => star = byte == b'*',
+                                    None => return Err(self.input.error("unterminated comment")),
+                                }
+                            }
+                        }
+                        _ => return Err(self.input.error("expected '//' or '/*'")),
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    pub fn next_event(&mut self) -> Result<Option<Event>, ParseError> {
+        self.skip_whitespace()?;
+
+        match self.expect {
+            Expect::Done => Ok(None),
+            Expect::Value => self.value().map(Some),
+            Expect::FirstItem | Expect::Item => {
+                let trailing_allowed = self.expect == Expect::FirstItem || self.lenient;
+                if trailing_allowed && self.peek()? == Some(b']') {
+                    self.bump()?;
+                    return Ok(Some(self.close(Container::Array)));
+                }
+                self.value().map(Some)
+            }
+            Expect::FirstKey | Expect::Key => {
+                let trailing_allowed = self.expect == Expect::FirstKey || self.lenient;
+                if trailing_allowed && self.peek()? == Some(b'}') {
+                    self.bump()?;
+                    return Ok(Some(self.close(Container::Object)));
+                }
+                self.key().map(Some)
+            }
+            Expect::AfterValue => {
+                let Some(&container) = self.stack.last() else {
+                    return match self.peek()? {
+                        None => {
+                            self.expect = Expect::Done;
+                            Ok(None)
+                        }
+                        Some(_) => Err(self.unexpected("end of input")),
+                    };
+                };
+
+                match (self.peek()?, container) {
+                    (Some(b','), Container::Array) => {
+                        self.bump()?;
+                        self.expect = Expect::Item;
+                        self.next_event()
+                    }
+                    (Some(b','), Container::Object) => {
+                        self.bump()?;
+                        self.expect = Expect::Key;
+                        self.next_event()
+                    }
+                    (Some(b']'), Container::Array) => {
+                        self.bump()?;
+                        Ok(Some(self.close(Container::Array)))
+                    }
+                    (Some(b'}'), Container::Object) => {
+                        self.bump()?;
+                        Ok(Some(self.close(Container::Object)))
+                    }
+                    (_, Container::Array) => Err(self.unexpected("',' or ']'")),
+                    (_, Container::Object) => Err(self.unexpected("',' or '}'")),
+                }
+            }
+        }
+    }
+
+    fn close(&mut self, container: Container) -> Event {
+        self.stack.pop();
+        self.expect = Expect::AfterValue;
+        match container {
+            Container::Object => Event::EndObject,
+            Container::Array => Event::EndArray,
+        }
+    }
+
+    fn open(&mut self, container: Container) -> Result<Event, ParseError> {
+        if self.stack.len() >= MAX_DEPTH {
+            return Err(self.input.error(format!("nesting deeper than {} levels", MAX_DEPTH)));
+        }
+        self.bump()?;
+        self.stack.push(container);
+
+        Ok(match container {
+            Container::Object => {
+                self.expect = Expect::FirstKey;
+                Event::StartObject
+            }
+            Container::Array => {
+                self.expect = Expect::FirstItem;
+                Event::StartArray
+            }
+        })
+    }
+
+    fn key(&mut self) -> Result<Event, ParseError> {
+        let key = match self.peek()? {
+            Some(b'"') => self.string(b'"')?,
+            Some(b'\'') if self.lenient => self.string(b'\'')?,
+            Some(byte) if self.lenient && (byte.is_ascii_alphabetic() || byte == b'_' || byte == b'$') => {
+                self.identifier()?
+            }
+            _ => return Err(self.unexpected(if self.lenient { "a key" } else { "a string key" })),
+        };
+
+        self.skip_whitespace()?;
+        if self.peek()? != Some(b':') {
+            return Err(self.unexpected("':'"));
+        }
+        self.bump()?;
+        self.expect = Expect::Value;
+        Ok(Event::Key(key))
+    }
+
+    fn value(&mut self) -> Result<Event, ParseError> {
+        let event = match self.peek()? {
+            Some(b'{') => return self.open(Container::Object),
+            Some(b'[') => return self.open(Container::Array),
+            Some(b'"') => Event::String(self.string(b'"')?),
+            Some(b'\'') if self.lenient => Event::String(self.string(b'\'')?),
+            Some(b'-' | b'0'..=b'9') => Event::Number(self.number()?),
+            Some(b'+' | b'.') if self.lenient => Event::Number(self.number()?),
+            Some(b't') => self.literal("true", Event::Bool(true))?,
+            Some(b'f') => self.literal("false", Event::Bool(false))?,
+            Some(b'n') => self.literal("null", Event::Null)?,
+            _ => return Err(self.unexpected("a value")),
+        };
+
+        self.expect = Expect::AfterValue;
+        Ok(event)
+    }
+
+    fn literal(&mut self, word: &str, event: Event) -> Result<Event, ParseError> {
+        for expected in word.bytes() {
+            if self.peek()? != Some(expected) {
+                return Err(self.unexpected(&format!("'{}'", word)));
+            }
+            self.bump()?;
+        }
+        Ok(event)
+    }
+
+    fn identifier(&mut self) -> Result<String, ParseError> {
+        let mut name = String::new();
+        while let Some(byte) = self.peek()?
+            && (byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$')
+        {
+            name.push(byte as char);
+            self.bump()?;
+        }
+        Ok(name)
+    }
+
+    fn digits(&mut self, text: &mut String) -> Result<usize, ParseError> {
+        let mut count = 0;
+        while let Some(byte) = self.peek()?
+            && byte.is_ascii_digit()
+        {
+            text.push(byte as char);
+            self.bump()?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`, lenient mode also
+    /// allows a leading `+` and a missing digit before or after the dot
+    fn number(&mut self) -> Result<Number, ParseError> {
+        let mut text = String::new();
+
+        match self.peek()? {
+            Some(b'-') => {
+                text.push('-');
+                self.bump()?;
+            }
+            Some(b'+') => {
+                self.bump()?;
+            }
+            _ => {}
+        }
+
+        let leading_zero = self.peek()? == Some(b'0');
+        let integer_digits = self.digits(&mut text)?;
+        if integer_digits > 1 && leading_zero {
+            return Err(self.input.error("numbers can't have leading zeros"));
+        }
+        if integer_digits == 0 && !(self.lenient && self.peek()? == Some(b'.')) {
+            return Err(self.unexpected("a digit"));
+        }
+
+        if self.peek()? == Some(b'.') {
+            self.bump()?;
+            text.push('.');
+            if self.digits(&mut text)? == 0 {
+                if !self.lenient || integer_digits == 0 {
+                    return Err(self.unexpected("a digit after the decimal point"));
+                }
+                text.push('0');
+            }
+        }
+
+        if let Some(b'e' | b'E') = self.peek()? {
+            self.bump()?;
+            text.push('e');
+            if let Some(sign @ (b'+' | b'-')) = self.peek()? {
+                text.push(sign as char);
+                self.bump()?;
+            }
+            if self.digits(&mut text)? == 0 {
+                return Err(self.unexpected("a digit in the exponent"));
+            }
+        }
+
+        let number = if let Ok(n) = text.parse::<i64>() {
+            Some(Number::from(n))
+        } else if let Ok(n) = text.parse::<u64>() {
+            Some(Number::from(n))
+        } else {
+            text.parse::<f64>().ok().and_then(Number::from_f64)
+        };
+        number.ok_or_else(|| self.input.error(format!("number {} is out of range", text)))
+    }
+
+    fn hex_escape(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.peek()?.and_then(|b| (b as char).to_digit(16));
+            let Some(digit) = digit else {
+                return Err(self.unexpected("a hex digit"));
+            };
+            self.bump()?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn string(&mut self, quote: u8) -> Result<String, ParseError> {
+        self.bump()?;
+        let mut bytes = Vec::new();
+
+        loop {
+            match self.peek()? {
+                None => return Err(self.input.error("unterminated string")),
+                Some(byte) if byte == quote => {
+                    self.bump()?;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.bump()?;
+                    let escaped = match self.peek()? {
+                        Some(b'"') => '"',
+                        Some(b'\'') if self.lenient => '\'',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            self.bump()?;
+                            let mut code = self.hex_escape()?;
+                            // Characters outside the BMP come as a surrogate pair
+                            if (0xD800..0xDC00).contains(&code) {
+                                if self.bump()? != Some(b'\\') || self.bump()? != Some(b'u') {
+                                    return Err(self.input.error("expected a low surrogate after a high surrogate"));
+                                }
+                                let low = self.hex_escape()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.input.error("invalid low surrogate"));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            let c = char::from_u32(code).ok_or_else(|| self.input.error("invalid unicode escape"))?;
+                            bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                            continue;
+                        }
+                        _ => return Err(self.unexpected("a valid escape sequence")),
+                    };
+                    self.bump()?;
+                    bytes.extend_from_slice(escaped.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                Some(byte) if byte < 0x20 => {
+                    return Err(self.input.error("control characters must be escaped in strings"));
+                }
+                Some(byte) => {
+                    self.bump()?;
+                    bytes.push(byte);
+                }
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| self.input.error("string is not valid UTF-8"))
+    }
+}
+
+/// Read a whole document into a `Value`, for when the tree is needed in memory
+pub fn read_value<R: BufRead>(parser: &mut Parser<R>) -> Result<Value, ParseError> {
+    enum Partial {
+        Object(Map<String, Value>, Option<String>),
+        Array(Vec<Value>),
+    }
+
+    let mut stack: Vec<Partial> = Vec::new();
+
+    while let Some(event) = parser.next_event()? {
+        let value = match event {
+            Event::StartObject => {
+                stack.push(Partial::Object(Map::new(), None));
+                continue;
+            }
+            Event::StartArray => {
+                stack.push(Partial::Array(Vec::new()));
+                continue;
+            }
+            Event::Key(key) => {
+                if let Some(Partial::Object(_, pending)) = stack.last_mut() {
+                    *pending = Some(key);
+                }
+                continue;
+            }
+            Event::EndObject | Event::EndArray => match stack.pop() {
+                Some(Partial::Object(map, _)) => Value::Object(map),
+                Some(Partial::Array(items)) => Value::Array(items),
+                None => unreachable!("the parser only emits balanced events"),
+            },
+            Event::Null => Value::Null,
+            Event::Bool(b) => Value::Bool(b),
+            Event::Number(n) => Value::Number(n),
+            Event::String(s) => Value::String(s),
+        };
+
+        match stack.last_mut() {
+            None => {
+                // Make sure nothing but whitespace follows the document
+                parser.next_event()?;
+                return Ok(value);
+            }
+            Some(Partial::Array(items)) => items.push(value),
+            Some(Partial::Object(map, key)) => {
+                map.insert(key.take().unwrap_or_default(), value);
+            }
+        }
+    }
+
+    unreachable!("next_event reports an error before the input ends mid-document")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(text: &str) -> Result<Value, ParseError> {
+        read_value(&mut Parser::new(text.as_bytes(), false))
+    }
+
+    fn parse_lenient(text: &str) -> Result<Value, ParseError> {
+        read_value(&mut Parser::new(text.as_bytes(), true))
+    }
+
+    /// Line, column and message of the error for invalid input
+    fn error(text: &str) -> (usize, usize, String) {
+        let e = parse(text).unwrap_err();
+        (e.line, e.column, e.message)
+    }
+
+    #[test]
+    fn parses_documents() {
+        let text = r#"{"a": [1, -2.5, 3e2, true, null], "b": {"c": "dé😀\n"}, "e": []}"#;
+        assert_eq!(parse(text).unwrap(), json!({"a": [1, -2.5, 300.0, true, null], "b": {"c": "dé😀\n"}, "e": []}));
+        assert_eq!(parse(" 18446744073709551615 ").unwrap(), json!(u64::MAX));
+        assert_eq!(parse("\"\"").unwrap(), json!(""));
+    }
+
+    #[test]
+    fn points_at_the_error() {
+        assert_eq!(error(r#"{"a": [1, 2,, 3]}"#), (1, 13, "expected a value, found ','".to_string()));
+        assert_eq!(error("{\n  \"a\": tru\n}"), (2, 11, "expected 'true'".to_string()));
+        assert_eq!(error("{\"a\" 1}"), (1, 6, "expected ':', found '1'".to_string()));
+        assert_eq!(error("[1, 2"), (1, 6, "unexpected end of input, expected ',' or ']'".to_string()));
+        assert_eq!(error("{} {}"), (1, 4, "expected end of input, found '{'".to_string()));
+        assert_eq!(error("[\"abc"), (1, 6, "unterminated string".to_string()));
+        assert_eq!(error("[01]"), (1, 4, "numbers can't have leading zeros".to_string()));
+        assert_eq!(error("\"a\tb\""), (1, 3, "control characters must be escaped in strings".to_string()));
+        assert_eq!(error(r#""\ud83d""#), (1, 9, "expected a low surrogate after a high surrogate".to_string()));
+    }
+
+    #[test]
+    fn columns_count_characters_not_bytes() {
+        let e = parse("[\"héllo\" x]").unwrap_err();
+        assert_eq!((e.line, e.column, e.caret), (1, 10, 9));
+        assert_eq!(e.to_string(), "expected ',' or ']', found 'x' at line 1, column 10\n  [\"héllo\" x]\n           ^");
+    }
+
+    #[test]
+    fn error_context_is_bounded() {
+        let text = format!("[{}x]", "1, ".repeat(1000));
+        let e = parse(&text).unwrap_err();
+        assert_eq!(e.column, 3002);
+        assert!(e.context.len() <= CONTEXT_BYTES + CONTEXT_BYTES / 3);
+        assert!(e.context.ends_with("1, x]"));
+    }
+
+    #[test]
+    fn limits_nesting() {
+        assert!(parse(&"[".repeat(MAX_DEPTH)).unwrap_err().message.contains("unexpected end of input"));
+        let e = parse(&"[".repeat(MAX_DEPTH + 1)).unwrap_err();
+        assert_eq!(e.message, format!("nesting deeper than {} levels", MAX_DEPTH));
+    }
+
+    #[test]
+    fn lenient_mode_accepts_json5() {
+        let text = "// settings\n{ name: 'Ada', 'tags': [1, +2, .5, 3.,], /* done */ }";
+        assert_eq!(parse_lenient(text).unwrap(), json!({"name": "Ada", "tags": [1, 2, 0.5, 3.0]}));
+        assert_eq!(error("[1,]"), (1, 4, "expected a value, found ']'".to_string()));
+        assert_eq!(error("{a: 1}"), (1, 2, "expected a string key, found 'a'".to_string()));
+        assert!(parse_lenient("[1] /* open").is_err());
+    }
+
+    #[test]
+    fn streams_events() {
+        let mut parser = Parser::new("{\"a\": [true]}".as_bytes(), false);
+        let mut events = Vec::new();
+        while let Some(event) = parser.next_event().unwrap() {
+            events.push(event);
+        }
+        assert_eq!(
+            events,
+            [Event::StartObject, Event::Key("a".to_string()), Event::StartArray, Event::Bool(true), Event::EndArray, Event::EndObject]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::error::Error;
+use std::io::{BufRead, Write};
+
+use crate::parser::{Event, Parser};
+
+/// JSON text of a string, with quotes and escapes
+fn quote(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_default()
+}
+
+/// Pretty print straight from the event stream, in the same layout as
+/// `serde_json::to_string_pretty`, without building the document in memory
+pub fn write_pretty<R: BufRead>(parser: &mut Parser<R>, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    // One entry per open container: is it still empty?
+    let mut empty: Vec<bool> = Vec::new();
+    let mut after_key = false;
+
+    while let Some(event) = parser.next_event()? {
+        let closing = matches!(event, Event::EndObject | Event::EndArray);
+
+        if closing {
+            let was_empty = empty.pop().unwrap_or(true);
+            if !was_empty {
+                write!(out, "\n{}", "  ".repeat(empty.len()))?;
+            }
+        } else if !after_key && let Some(first) = empty.pop() {
+            // Every array item and object key starts on its own line
+            write!(out, "{}\n{}", if first { "" } else { "," }, "  ".repeat(empty.len() + 1))?;
+            empty.push(false);
+        }
+        after_key = false;
+
+        match event {
+            Event::StartObject => {
+                write!(out, "{{")?;
+                empty.push(true);
+            }
+            Event::StartArray => {
+                write!(out, "[")?;
+                empty.push(true);
+            }
+            Event::EndObject => write!(out, "}}")?,
+            Event::EndArray => write!(out, "]")?,
+            Event::Key(key) => {
+                write!(out, "{}: ", quote(&key))?;
+                after_key = true;
+            }
+            Event::Null => write!(out, "null")?,
+            Event::Bool(b) => write!(out, "{}", b)?,
+            Event::Number(n) => write!(out, "{}", n)?,
+            Event::String(s) => write!(out, "{}", quote(&s))?,
+        }
+    }
+
+    writeln!(out)?;
+    Ok(())
+}
+
+/// One line per event, indented by depth, for looking at the structure of huge files
+pub fn write_events<R: BufRead>(parser: &mut Parser<R>, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let mut depth = 0;
+
+    while let Some(event) = parser.next_event()? {
+        if matches!(event, Event::EndObject | Event::EndArray) {
+            depth -= 1;
+        }
+
+        let indent = "  ".repeat(depth);
+        match &event {
+            Event::StartObject => writeln!(out, "{}start object", indent)?,
+            Event::EndObject => writeln!(out, "{}end object", indent)?,
+            Event::StartArray => writeln!(out, "{}start array", indent)?,
+            Event::EndArray => writeln!(out, "{}end array", indent)?,
+            Event::Key(key) => writeln!(out, "{}key {}", indent, quote(key))?,
+            Event::Null => writeln!(out, "{}null", indent)?,
+            Event::Bool(b) => writeln!(out, "{}bool {}", indent, b)?,
+            Event::Number(n) => writeln!(out, "{}number {}", indent, n)?,
+            Event::String(s) => writeln!(out, "{}string {}", indent, quote(s))?,
+        }
+
+        if matches!(event, Event::StartObject | Event::StartArray) {
+            depth += 1;
+        }
+    }
+    Ok(())
+}
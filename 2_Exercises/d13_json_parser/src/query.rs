@@ -0,0 +1,691 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde_json::Value;
+
+/// A problem with a query. Parse errors carry the character position in the query.
+#[derive(Debug)]
+pub struct QueryError {
+    pub message: String,
+    pub position: Option<usize>,
+}
+
+impl QueryError {
+    fn at(position: usize, message: impl Into<String>) -> QueryError {
+        QueryError { message: message.into(), position: Some(position) }
+    }
+
+    fn runtime(message: impl Into<String>) -> QueryError {
+        QueryError { message: message.into(), position: None }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{} at position {}", self.message, position + 1),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Dot,
+    Star,
+    Pipe,
+    Comma,
+    Colon,
+    LeftBracket,
+    RightBracket,
+    LeftParen,
+    RightParen,
+    Compare(CompareOp),
+    Ident(String),
+    Str(String),
+    Number(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+fn tokenize(query: &str) -> Result<Vec<(Token, usize)>, QueryError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '.' => Token::Dot,
+            '*' => Token::Star,
+            '|' => Token::Pipe,
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '=' | '!' | '<' | '>' => {
+                let op = match (c, next) {
+                    ('=', Some('=')) => CompareOp::Equal,
+                    ('!', Some('=')) => CompareOp::NotEqual,
+                    ('<', Some('=')) => CompareOp::LessEqual,
+                    ('>', Some('=')) => CompareOp::GreaterEqual,
+                    ('<', _) => CompareOp::Less,
+                    ('>', _) => CompareOp::Greater,
+                    _ => return Err(QueryError::at(i, format!("unexpected '{}'", c))),
+                };
+                if next == Some('=') {
+                    i += 1;
+                }
+                Token::Compare(op)
+            }
+            '"' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(QueryError::at(start, "unterminated string")),
+                        Some('"') => break,
+                        Some('\\') => {
+                            i += 1;
+                            match chars.get(i) {
+                                Some('n') => text.push('\n'),
+                                Some('t') => text.push('\t'),
+                                Some(c) => text.push(*c),
+                                None => return Err(QueryError::at(start, "unterminated string")),
+                            }
+                        }
+                        Some(c) => text.push(*c),
+                    }
+                    i += 1;
+                }
+                Token::Str(text)
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == 'e') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let number = text.parse().map_err(|_| QueryError::at(start, format!("invalid number '{}'", text)))?;
+                tokens.push((Token::Number(number), start));
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                    i += 1;
+                }
+                tokens.push((Token::Ident(chars[start..i].iter().collect()), start));
+                continue;
+            }
+            c => return Err(QueryError::at(i, format!("unexpected '{}'", c))),
+        };
+
+        tokens.push((token, start));
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug)]
+enum Step {
+    Field(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+    Iterate,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Identity,
+    Literal(Value),
+    Path(Box<Expr>, Step),
+    Pipe(Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Collect(Option<Box<Expr>>),
+    Call(String, Option<Box<Expr>>),
+}
+
+/// Recursive descent, from the loosest operator to the tightest:
+/// `|`, `,`, `or`, `and`, comparisons, then paths and function calls
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(_, p)| *p)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token, what: &str) -> Result<(), QueryError> {
+        if self.eat(token) { Ok(()) } else { Err(QueryError::at(self.position(), format!("expected {}", what))) }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(name)) if name == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn pipe(&mut self) -> Result<Expr, QueryError> {
+        let mut left = self.comma()?;
+        while self.eat(&Token::Pipe) {
+            left = Expr::Pipe(Box::new(left), Box::new(self.comma()?));
+        }
+        Ok(left)
+    }
+
+    fn comma(&mut self) -> Result<Expr, QueryError> {
+        let mut left = self.or()?;
+        while self.eat(&Token::Comma) {
+            left = Expr::Comma(Box::new(left), Box::new(self.or()?));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        let mut left = self.and()?;
+        while self.eat_keyword("or") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        let mut left = self.comparison()?;
+        while self.eat_keyword("and") {
+            left = Expr::And(Box::new(left), Box::new(self.comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, QueryError> {
+        let left = self.postfix()?;
+        if let Some(Token::Compare(op)) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            return Ok(Expr::Compare(Box::new(left), op, Box::new(self.postfix()?)));
+        }
+        Ok(left)
+    }
+
+    fn postfix(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.primary()?;
+
+        loop {
+            match self.peek() {
+                Some(Token::Dot) => {
+                    self.pos += 1;
+                    match self.peek().cloned() {
+                        Some(Token::Ident(name) | Token::Str(name)) => {
+                            self.pos += 1;
+                            expr = Expr::Path(Box::new(expr), Step::Field(name));
+                        }
+                        Some(Token::Star) => {
+                            self.pos += 1;
+                            expr = Expr::Path(Box::new(expr), Step::Iterate);
+                        }
+                        Some(Token::LeftBracket) => {}
+                        _ => return Err(QueryError::at(self.position(), "expected a field name after '.'")),
+                    }
+                }
+                Some(Token::LeftBracket) => {
+                    self.pos += 1;
+                    let step = self.bracket()?;
+                    expr = Expr::Path(Box::new(expr), step);
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    /// What follows `[`: `]`, `*]`, `"key"]`, `N]` or a slice `A:B]`
+    fn bracket(&mut self) -> Result<Step, QueryError> {
+        let position = self.position();
+        let integer = |n: f64| if n.fract() == 0.0 { Ok(n as i64) } else { Err(QueryError::at(position, "index must be an integer")) };
+
+        let step = match self.peek().cloned() {
+            Some(Token::RightBracket) => Step::Iterate,
+            Some(Token::Star) => {
+                self.pos += 1;
+                Step::Iterate
+            }
+            Some(Token::Str(key)) => {
+                self.pos += 1;
+                Step::Field(key)
+            }
+            Some(Token::Number(n)) => {
+                self.pos += 1;
+                let start = integer(n)?;
+                if self.eat(&Token::Colon) {
+                    Step::Slice(Some(start), self.slice_end(integer)?)
+                } else {
+                    Step::Index(start)
+                }
+            }
+            Some(Token::Colon) => {
+                self.pos += 1;
+                Step::Slice(None, self.slice_end(integer)?)
+            }
+            _ => return Err(QueryError::at(position, "expected an index, a slice, a string or ']'")),
+        };
+
+        self.expect(&Token::RightBracket, "']'")?;
+        Ok(step)
+    }
+
+    fn slice_end(&mut self, integer: impl Fn(f64) -> Result<i64, QueryError>) -> Result<Option<i64>, QueryError> {
+        match self.peek() {
+            Some(Token::Number(n)) => {
+                let n = integer(*n)?;
+                self.pos += 1;
+                Ok(Some(n))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, QueryError> {
+        let position = self.position();
+        let Some(token) = self.peek().cloned() else {
+            return Err(QueryError::at(position, "unexpected end of query"));
+        };
+        self.pos += 1;
+
+        match token {
+            Token::Dot => match self.peek().cloned() {
+                Some(Token::Ident(name) | Token::Str(name)) => {
+                    self.pos += 1;
+                    Ok(Expr::Path(Box::new(Expr::Identity), Step::Field(name)))
+                }
+                Some(Token::Star) => {
+                    self.pos += 1;
+                    Ok(Expr::Path(Box::new(Expr::Identity), Step::Iterate))
+                }
+                _ => Ok(Expr::Identity),
+            },
+            Token::Number(n) => Ok(Expr::Literal(number(n))),
+            Token::Str(text) => Ok(Expr::Literal(Value::String(text))),
+            Token::LeftParen => {
+                let inner = self.pipe()?;
+                self.expect(&Token::RightParen, "')'")?;
+                Ok(inner)
+            }
+            Token::LeftBracket => {
+                if self.eat(&Token::RightBracket) {
+                    return Ok(Expr::Collect(None));
+                }
+                let inner = self.pipe()?;
+                self.expect(&Token::RightBracket, "']'")?;
+                Ok(Expr::Collect(Some(Box::new(inner))))
+            }
+            Token::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                _ => {
+                    let argument = if self.eat(&Token::LeftParen) {
+                        let argument = self.pipe()?;
+                        self.expect(&Token::RightParen, "')'")?;
+                        Some(Box::new(argument))
+                    } else {
+                        None
+                    };
+                    check_function(&name, argument.is_some(), position)?;
+                    Ok(Expr::Call(name, argument))
+                }
+            },
+            _ => Err(QueryError::at(position, "unexpected token")),
+        }
+    }
+}
+
+const FUNCTIONS: [(&str, bool); 10] = [
+    ("length", false),
+    ("keys", false),
+    ("values", false),
+    ("type", false),
+    ("not", false),
+    ("sort", false),
+    ("first", false),
+    ("last", false),
+    ("map", true),
+    ("select", true),
+];
+
+fn check_function(name: &str, has_argument: bool, position: usize) -> Result<(), QueryError> {
+    match FUNCTIONS.iter().find(|(n, _)| *n == name) {
+        None => Err(QueryError::at(position, format!("unknown function '{}'", name))),
+        Some((_, true)) if !has_argument => Err(QueryError::at(position, format!("{} needs an argument", name))),
+        Some((_, false)) if has_argument => Err(QueryError::at(position, format!("{} takes no argument", name))),
+        _ => Ok(()),
+    }
+}
+
+/// A parsed query, e.g. `.users[] | select(.age > 30) | .name`
+#[derive(Debug)]
+pub struct Query {
+    expr: Expr,
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Query, QueryError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, pos: 0, end: text.chars().count() };
+        let expr = parser.pipe()?;
+
+        if parser.pos < parser.tokens.len() {
+            return Err(QueryError::at(parser.position(), "unexpected token"));
+        }
+        Ok(Query { expr })
+    }
+
+    /// Every value the query produces for `input`
+    pub fn run(&self, input: &Value) -> Result<Vec<Value>, QueryError> {
+        eval(&self.expr, input)
+    }
+}
+
+/// Whole numbers stay integers, so `1` prints as `1` and not `1.0`
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 { Value::from(n as i64) } else { Value::from(n) }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+/// jq ordering: null < false < true < numbers < strings < arrays < objects
+fn compare(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(false) => 1,
+            Value::Bool(true) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6,
+        }
+    }
+
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => {
+            x.as_f64().unwrap_or(0.0).total_cmp(&y.as_f64().unwrap_or(0.0))
+        }
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Array(x), Value::Array(y)) => {
+            x.iter().zip(y).map(|(a, b)| compare(a, b)).find(|o| o.is_ne()).unwrap_or(x.len().cmp(&y.len()))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            let mut xk: Vec<&String> = x.keys().collect();
+            let mut yk: Vec<&String> = y.keys().collect();
+            xk.sort();
+            yk.sort();
+            xk.cmp(&yk).then_with(|| {
+                xk.iter().map(|k| compare(&x[k.as_str()], &y[k.as_str()])).find(|o| o.is_ne()).unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Turn a possibly negative index into a position, `None` when out of range
+fn resolve(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+fn slice_bounds(start: Option<i64>, end: Option<i64>, len: usize) -> (usize, usize) {
+    let clamp = |i: i64| if i < 0 { (len as i64 + i).max(0) as usize } else { (i as usize).min(len) };
+    let start = start.map_or(0, clamp);
+    let end = end.map_or(len, clamp);
+    (start, end.max(start))
+}
+
+fn apply_step(step: &Step, value: &Value) -> Result<Vec<Value>, QueryError> {
+    let cannot = |what: &str| QueryError::runtime(format!("cannot {} {}", what, type_name(value)));
+
+    match (step, value) {
+        (Step::Field(name), Value::Object(map)) => Ok(vec![map.get(name).cloned().unwrap_or(Value::Null)]),
+        (Step::Field(_) | Step::Index(_) | Step::Slice(..), Value::Null) => Ok(vec![Value::Null]),
+        (Step::Field(name), _) => Err(cannot(&format!("get field '{}' of", name))),
+        (Step::Index(i), Value::Array(items)) => {
+            Ok(vec![resolve(*i, items.len()).map(|i| items[i].clone()).unwrap_or(Value::Null)])
+        }
+        (Step::Index(_), _) => Err(cannot("index")),
+        (Step::Slice(start, end), Value::Array(items)) => {
+            let (start, end) = slice_bounds(*start, *end, items.len());
+            Ok(vec![Value::Array(items[start..end].to_vec())])
+        }
+        (Step::Slice(start, end), Value::String(text)) => {
+            let chars: Vec<char> = text.chars().collect();
+            let (start, end) = slice_bounds(*start, *end, chars.len());
+            Ok(vec![Value::String(chars[start..end].iter().collect())])
+        }
+        (Step::Slice(..), _) => Err(cannot("slice")),
+        (Step::Iterate, Value::Array(items)) => Ok(items.clone()),
+        (Step::Iterate, Value::Object(map)) => Ok(map.values().cloned().collect()),
+        (Step::Iterate, _) => Err(cannot("iterate over")),
+    }
+}
+
+fn call(name: &str, argument: Option<&Expr>, input: &Value) -> Result<Vec<Value>, QueryError> {
+    let cannot = || QueryError::runtime(format!("{} is not defined for {}", name, type_name(input)));
+
+    let value = match (name, input) {
+        ("length", Value::Null) => Value::from(0),
+        ("length", Value::Bool(_)) => return Err(cannot()),
+        ("length", Value::Number(n)) => number(n.as_f64().unwrap_or(0.0).abs()),
+        ("length", Value::String(text)) => Value::from(text.chars().count()),
+        ("length", Value::Array(items)) => Value::from(items.len()),
+        ("length", Value::Object(map)) => Value::from(map.len()),
+        ("keys", Value::Object(map)) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            Value::from(keys.into_iter().cloned().collect::<Vec<_>>())
+        }
+        ("keys", Value::Array(items)) => Value::from((0..items.len()).collect::<Vec<_>>()),
+        ("values", Value::Object(map)) => Value::Array(map.values().cloned().collect()),
+        ("values", Value::Array(items)) => Value::Array(items.clone()),
+        ("type", value) => Value::from(type_name(value)),
+        ("not", value) => Value::Bool(!truthy(value)),
+        ("sort", Value::Array(items)) => {
+            let mut items = items.clone();
+            items.sort_by(compare);
+            Value::Array(items)
+        }
+        ("first", Value::Array(items)) => items.first().cloned().unwrap_or(Value::Null),
+        ("last", Value::Array(items)) => items.last().cloned().unwrap_or(Value::Null),
+        ("map", Value::Array(_) | Value::Object(_)) => {
+            let mut mapped = Vec::new();
+            for item in apply_step(&Step::Iterate, input)? {
+                mapped.extend(eval(argument.unwrap_or(&Expr::Identity), &item)?);
+            }
+            Value::Array(mapped)
+        }
+        ("select", _) => {
+            let keep = eval(argument.unwrap_or(&Expr::Identity), input)?.iter().any(truthy);
+            return Ok(if keep { vec![input.clone()] } else { Vec::new() });
+        }
+        _ => return Err(cannot()),
+    };
+
+    Ok(vec![value])
+}
+
+fn eval(expr: &Expr, input: &Value) -> Result<Vec<Value>, QueryError> {
+    match expr {
+        Expr::Identity => Ok(vec![input.clone()]),
+        Expr::Literal(value) => Ok(vec![value.clone()]),
+        Expr::Path(base, step) => {
+            let mut out = Vec::new();
+            for value in eval(base, input)? {
+                out.extend(apply_step(step, &value)?);
+            }
+            Ok(out)
+        }
+        Expr::Pipe(left, right) => {
+            let mut out = Vec::new();
+            for value in eval(left, input)? {
+                out.extend(eval(right, &value)?);
+            }
+            Ok(out)
+        }
+        Expr::Comma(left, right) => {
+            let mut out = eval(left, input)?;
+            out.extend(eval(right, input)?);
+            Ok(out)
+        }
+        Expr::Compare(left, op, right) => {
+            let mut out = Vec::new();
+            for b in eval(right, input)? {
+                for a in eval(left, input)? {
+                    let order = compare(&a, &b);
+                    let result = match op {
+                        CompareOp::Equal => order.is_eq(),
+                        CompareOp::NotEqual => order.is_ne(),
+                        CompareOp::Less => order.is_lt(),
+                        CompareOp::LessEqual => order.is_le(),
+                        CompareOp::Greater => order.is_gt(),
+                        CompareOp::GreaterEqual => order.is_ge(),
+                    };
+                    out.push(Value::Bool(result));
+                }
+            }
+            Ok(out)
+        }
+        Expr::And(left, right) | Expr::Or(left, right) => {
+            let is_and = matches!(expr, Expr::And(..));
+            let mut out = Vec::new();
+            for a in eval(left, input)? {
+                // Short circuit like jq: `false and x` never evaluates x
+                if truthy(&a) != is_and {
+                    out.push(Value::Bool(!is_and));
+                    continue;
+                }
+                for b in eval(right, input)? {
+                    out.push(Value::Bool(truthy(&b)));
+                }
+            }
+            Ok(out)
+        }
+        Expr::Collect(inner) => match inner {
+            Some(inner) => Ok(vec![Value::Array(eval(inner, input)?)]),
+            None => Ok(vec![Value::Array(Vec::new())]),
+        },
+        Expr::Call(name, argument) => call(name, argument.as_deref(), input),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(query: &str, input: &Value) -> Vec<Value> {
+        Query::parse(query).unwrap().run(input).unwrap()
+    }
+
+    fn error(query: &str) -> String {
+        Query::parse(query).unwrap_err().to_string()
+    }
+
+    fn users() -> Value {
+        json!({"users": [{"name": "Ada", "age": 36}, {"name": "Alan", "age": 41}, {"name": "Grace", "age": null}]})
+    }
+
+    #[test]
+    fn follows_paths() {
+        let input = users();
+        assert_eq!(run(".users[0].name", &input), [json!("Ada")]);
+        assert_eq!(run(".users[-1].name", &input), [json!("Grace")]);
+        assert_eq!(run(".users[5].name", &input), [Value::Null]);
+        assert_eq!(run(".users[].name", &input), [json!("Ada"), json!("Alan"), json!("Grace")]);
+        assert_eq!(run(".users[1:] | length", &input), [json!(2)]);
+        assert_eq!(run(".[\"users\"][0].age", &input), [json!(36)]);
+        assert_eq!(run("\"héllo\" | .[1:3]", &input), [json!("él")]);
+    }
+
+    #[test]
+    fn filters_and_maps() {
+        let input = users();
+        assert_eq!(run(".users[] | select(.age > 40) | .name", &input), [json!("Alan")]);
+        assert_eq!(run(".users | map(.name)", &input), [json!(["Ada", "Alan", "Grace"])]);
+        assert_eq!(run("[.users[].age] | sort", &input), [json!([null, 36, 41])]);
+        assert_eq!(run(".users[0] | keys", &input), [json!(["age", "name"])]);
+        assert_eq!(run(".users[2].age == null and (.users | length) >= 3", &input), [json!(true)]);
+        assert_eq!(run(".users[0].name, .users[1].name", &input), [json!("Ada"), json!("Alan")]);
+        assert_eq!(run(".users[] | .age | type", &input), [json!("number"), json!("number"), json!("null")]);
+    }
+
+    #[test]
+    fn reports_where_a_query_is_wrong() {
+        assert_eq!(error(".users[0"), "expected ']' at position 9");
+        assert_eq!(error(".users | frobnicate"), "unknown function 'frobnicate' at position 10");
+        assert_eq!(error("map"), "map needs an argument at position 1");
+        assert_eq!(error(".a ]"), "unexpected token at position 4");
+        assert_eq!(error(".a # b"), "unexpected '#' at position 4");
+        assert_eq!(error("\"open"), "unterminated string at position 1");
+        assert_eq!(error(".a[1.5]"), "index must be an integer at position 4");
+    }
+
+    #[test]
+    fn reports_type_errors_when_run() {
+        let query = |text: &str| Query::parse(text).unwrap().run(&users()).unwrap_err().to_string();
+        assert_eq!(query(".users.name"), "cannot get field 'name' of array");
+        assert_eq!(query(".users[0].age[]"), "cannot iterate over number");
+        assert_eq!(query(".users[0] | first"), "first is not defined for object");
+    }
+}
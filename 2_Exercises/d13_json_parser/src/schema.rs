@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+/// Guards against schemas such as `{"$ref": "#"}` that refer to themselves forever
+const MAX_REF_DEPTH: usize = 64;
+
+/// Keyword, the check a number has to pass against its value, and how to describe it
+type Limit = (&'static str, fn(f64, f64) -> bool, &'static str);
+
+/// One place where the document doesn't match the schema
+#[derive(Debug)]
+pub struct Violation {
+    /// JSON Pointer to the offending value, e.g. `/users/0/age`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = if self.path.is_empty() { "/" } else { &self.path };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+/// The schema itself is broken, so nothing can be validated
+#[derive(Debug)]
+pub struct SchemaError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid schema at '#{}': {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Escape a key for use in a JSON Pointer (RFC 6901)
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape_pointer(part: &str) -> String {
+    part.replace("~1", "/").replace("~0", "~")
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match (expected, value) {
+        ("number", Value::Number(_)) => true,
+        ("integer", Value::Number(n)) => n.as_f64().is_some_and(|f| f.fract() == 0.0),
+        (expected, value) => type_name(value) == expected,
+    }
+}
+
+/// Validates documents against a JSON Schema (a subset of draft 2020-12):
+/// `type`, `enum`, `const`, `required`, `properties`, `additionalProperties`,
+/// `items`, `prefixItems`, `minimum`/`maximum` and their exclusive forms,
+/// `minLength`/`maxLength`, `minItems`/`maxItems`, `pattern`, `$ref` and `$defs`.
+pub struct Validator<'a> {
+    root: &'a Value,
+    patterns: HashMap<String, Regex>,
+    violations: Vec<Violation>,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(root: &'a Value) -> Validator<'a> {
+        Validator { root, patterns: HashMap::new(), violations: Vec::new() }
+    }
+
+    /// Every violation in `document`, an empty list means it is valid
+    pub fn validate(mut self, document: &Value) -> Result<Vec<Violation>, SchemaError> {
+        self.check(self.root, "", document, "", 0)?;
+        Ok(self.violations)
+    }
+
+    fn report(&mut self, path: &str, message: String) {
+        self.violations.push(Violation { path: path.to_string(), message });
+    }
+
+    /// Find the schema a `$ref` such as `#/$defs/address` points to
+    fn resolve(&self, reference: &str, schema_path: &str) -> Result<(&'a Value, String), SchemaError> {
+        let error = |message: String| SchemaError { path: schema_path.to_string(), message };
+        let pointer = reference
+            .strip_prefix('#')
+            .ok_or_else(|| error(format!("only local references are supported, got '{}'", reference)))?;
+
+        let mut target = self.root;
+        for part in pointer.split('/').skip(1) {
+            let part = unescape_pointer(part);
+            target = match target {
+                Value::Object(map) => map.get(&part),
+                Value::Array(items) => part.parse::<usize>().ok().and_then(|i| items.get(i)),
+                _ => None,
+            }
+            .ok_or_else(|| error(format!("reference '{}' doesn't resolve", reference)))?;
+        }
+        Ok((target, pointer.to_string()))
+    }
+
+    fn check(
+        &mut self,
+        schema: &'a Value,
+        schema_path: &str,
+        value: &Value,
+        path: &str,
+        ref_depth: usize,
+    ) -> Result<(), SchemaError> {
+        let error = |message: &str| SchemaError { path: schema_path.to_string(), message: message.to_string() };
+
+        let schema = match schema {
+            Value::Bool(true) => return Ok(()),
+            Value::Bool(false) => {
+                self.report(path, "no value is allowed here".to_string());
+                return Ok(());
+            }
+            Value::Object(schema) => schema,
+            _ => return Err(error("a schema must be an object or a boolean")),
+        };
+
+        if let Some(reference) = schema.get("$ref") {
+            let reference = reference.as_str().ok_or_else(|| error("$ref must be a string"))?;
+            if ref_depth >= MAX_REF_DEPTH {
+                return Err(error("$ref nests too deeply, is it circular?"));
+            }
+            let (target, target_path) = self.resolve(reference, schema_path)?;
+            self.check(target, &target_path, value, path, ref_depth + 1)?;
+        }
+
+        self.check_type(schema, value, path, &error)?;
+        self.check_values(schema, value, path);
+        self.check_number(schema, value, path, &error)?;
+        self.check_string(schema, value, path, &error)?;
+
+        match value {
+            Value::Object(object) => self.check_object(schema, schema_path, object, path)?,
+            Value::Array(items) => self.check_array(schema, schema_path, items, path, &error)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn check_type(
+        &mut self,
+        schema: &Map<String, Value>,
+        value: &Value,
+        path: &str,
+        error: &impl Fn(&str) -> SchemaError,
+    ) -> Result<(), SchemaError> {
+        let expected: Vec<&str> = match schema.get("type") {
+            None => return Ok(()),
+            Some(Value::String(name)) => vec![name.as_str()],
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            Some(_) => return Err(error("type must be a string or an array of strings")),
+        };
+
+        if !expected.iter().any(|t| has_type(value, t)) {
+            self.report(path, format!("expected {}, found {}", expected.join(" or "), type_name(value)));
+        }
+        Ok(())
+    }
+
+    fn check_values(&mut self, schema: &Map<String, Value>, value: &Value, path: &str) {
+        if let Some(Value::Array(allowed)) = schema.get("enum")
+            && !allowed.contains(value)
+        {
+            let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            self.report(path, format!("{} is not one of {}", value, allowed.join(", ")));
+        }
+        if let Some(expected) = schema.get("const")
+            && expected != value
+        {
+            self.report(path, format!("expected {}, found {}", expected, value));
+        }
+    }
+
+    fn check_number(
+        &mut self,
+        schema: &Map<String, Value>,
+        value: &Value,
+        path: &str,
+        error: &impl Fn(&str) -> SchemaError,
+    ) -> Result<(), SchemaError> {
+        let Some(n) = value.as_f64() else {
+            return Ok(());
+        };
+
+        let limits: [Limit; 4] = [
+            ("minimum", |n, limit| n >= limit, "at least"),
+            ("maximum", |n, limit| n <= limit, "at most"),
+            ("exclusiveMinimum", |n, limit| n > limit, "greater than"),
+            ("exclusiveMaximum", |n, limit| n < limit, "less than"),
+        ];
+        for (keyword, ok, description) in limits {
+            let Some(limit) = schema.get(keyword) else {
+                continue;
+            };
+            let limit = limit.as_f64().ok_or_else(|| error(&format!("{} must be a number", keyword)))?;
+            if !ok(n, limit) {
+                self.report(path, format!("{} must be {} {}", value, description, limit));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_string(
+        &mut self,
+        schema: &Map<String, Value>,
+        value: &Value,
+        path: &str,
+        error: &impl Fn(&str) -> SchemaError,
+    ) -> Result<(), SchemaError> {
+        let Value::String(text) = value else {
+            return Ok(());
+        };
+
+        let length = text.chars().count() as u64;
+        if let Some(min) = schema.get("minLength") {
+            let min = min.as_u64().ok_or_else(|| error("minLength must be a non-negative integer"))?;
+            if length < min {
+                self.report(path, format!("string is {} characters long, the minimum is {}", length, min));
+            }
+        }
+        if let Some(max) = schema.get("maxLength") {
+            let max = max.as_u64().ok_or_else(|| error("maxLength must be a non-negative integer"))?;
+            if length > max {
+                self.report(path, format!("string is {} characters long, the maximum is {}", length, max));
+            }
+        }
+
+        if let Some(pattern) = schema.get("pattern") {
+            let pattern = pattern.as_str().ok_or_else(|| error("pattern must be a string"))?;
+            if !self.patterns.contains_key(pattern) {
+                let regex = Regex::new(pattern).map_err(|e| error(&format!("invalid pattern: {}", e)))?;
+                self.patterns.insert(pattern.to_string(), regex);
+            }
+            if !self.patterns[pattern].is_match(text) {
+                self.report(path, format!("\"{}\" doesn't match the pattern {}", text, pattern));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_object(
+        &mut self,
+        schema: &'a Map<String, Value>,
+        schema_path: &str,
+        object: &Map<String, Value>,
+        path: &str,
+    ) -> Result<(), SchemaError> {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    self.report(path, format!("missing required property '{}'", key));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, value) in object {
+            let value_path = format!("{}/{}", path, escape_pointer(key));
+
+            match properties.and_then(|p| p.get(key)) {
+                Some(property) => {
+                    let property_path = format!("{}/properties/{}", schema_path, escape_pointer(key));
+                    self.check(property, &property_path, value, &value_path, 0)?;
+                }
+                None => {
+                    if let Some(additional) = schema.get("additionalProperties") {
+                        let additional_path = format!("{}/additionalProperties", schema_path);
+                        if additional == &Value::Bool(false) {
+                            self.report(&value_path, format!("property '{}' is not allowed", key));
+                        } else {
+                            self.check(additional, &additional_path, value, &value_path, 0)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_array(
+        &mut self,
+        schema: &'a Map<String, Value>,
+        schema_path: &str,
+        items: &[Value],
+        path: &str,
+        error: &impl Fn(&str) -> SchemaError,
+    ) -> Result<(), SchemaError> {
+        let count = items.len() as u64;
+        if let Some(min) = schema.get("minItems") {
+            let min = min.as_u64().ok_or_else(|| error("minItems must be a non-negative integer"))?;
+            if count < min {
+                self.report(path, format!("array has {} items, the minimum is {}", count, min));
+            }
+        }
+        if let Some(max) = schema.get("maxItems") {
+            let max = max.as_u64().ok_or_else(|| error("maxItems must be a non-negative integer"))?;
+            if count > max {
+                self.report(path, format!("array has {} items, the maximum is {}", count, max));
+            }
+        }
+
+        // `prefixItems` describes the first items one by one, `items` all the others
+        let prefix = match schema.get("prefixItems") {
+            Some(Value::Array(prefix)) => prefix.as_slice(),
+            Some(_) => return Err(error("prefixItems must be an array")),
+            None => &[],
+        };
+        for (i, item) in items.iter().enumerate() {
+            let item_path = format!("{}/{}", path, i);
+            if let Some(item_schema) = prefix.get(i) {
+                self.check(item_schema, &format!("{}/prefixItems/{}", schema_path, i), item, &item_path, 0)?;
+            } else if let Some(item_schema) = schema.get("items") {
+                self.check(item_schema, &format!("{}/items", schema_path), item, &item_path, 0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// The violations as printed, "/path: message"
+    fn violations(schema: Value, document: Value) -> Vec<String> {
+        Validator::new(&schema).validate(&document).unwrap().iter().map(Violation::to_string).collect()
+    }
+
+    fn schema_error(schema: Value) -> String {
+        Validator::new(&schema).validate(&json!(1)).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn valid_documents_have_no_violations() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {"name": {"type": "string", "minLength": 1}, "age": {"type": "integer", "minimum": 0}},
+        });
+        assert!(violations(schema.clone(), json!({"name": "Ada", "age": 36})).is_empty());
+        assert!(violations(schema, json!({"name": "Ada", "age": 36.0})).is_empty());
+        assert!(violations(json!(true), json!([1, "two"])).is_empty());
+    }
+
+    #[test]
+    fn reports_every_violation_with_its_path() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name", "email"],
+            "additionalProperties": false,
+            "properties": {
+                "name": {"type": "string", "maxLength": 3},
+                "age": {"type": ["integer", "null"], "exclusiveMinimum": 0},
+                "a/b": {"enum": ["x", "y"]},
+                "email": {"pattern": "^[^@]+@[^@]+$"},
+            },
+        });
+        let document = json!({"name": "Lovelace", "age": 0, "a/b": "z", "extra": 1});
+        assert_eq!(
+            violations(schema, document),
+            [
+                "/: missing required property 'email'",
+                "/a~1b: \"z\" is not one of \"x\", \"y\"",
+                "/age: 0 must be greater than 0",
+                "/extra: property 'extra' is not allowed",
+                "/name: string is 8 characters long, the maximum is 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_arrays_and_types() {
+        let schema = json!({"prefixItems": [{"const": "id"}], "items": {"type": "number"}, "maxItems": 3});
+        assert_eq!(
+            violations(schema, json!(["key", 1, "2", null])),
+            ["/: array has 4 items, the maximum is 3", "/0: expected \"id\", found \"key\"", "/2: expected number, found string", "/3: expected number, found null"]
+        );
+        assert_eq!(violations(json!({"type": "integer"}), json!(1.5)), ["/: expected integer, found number"]);
+        assert_eq!(violations(json!({"items": false}), json!([1])), ["/0: no value is allowed here"]);
+    }
+
+    #[test]
+    fn follows_references() {
+        let schema = json!({
+            "$defs": {"node": {"type": "object", "properties": {"value": {"type": "integer"}, "next": {"$ref": "#/$defs/node"}}}},
+            "$ref": "#/$defs/node",
+        });
+        let document = json!({"value": 1, "next": {"value": 2, "next": {"value": "three"}}});
+        assert_eq!(violations(schema, document), ["/next/next/value: expected integer, found string"]);
+    }
+
+    #[test]
+    fn rejects_broken_schemas() {
+        assert_eq!(schema_error(json!({"$ref": "#"})), "invalid schema at '#': $ref nests too deeply, is it circular?");
+        assert_eq!(schema_error(json!({"$ref": "#/$defs/missing"})), "invalid schema at '#': reference '#/$defs/missing' doesn't resolve");
+        assert_eq!(schema_error(json!({"$ref": "other.json"})), "invalid schema at '#': only local references are supported, got 'other.json'");
+        assert_eq!(schema_error(json!({"minimum": "0"})), "invalid schema at '#': minimum must be a number");
+        assert_eq!(schema_error(json!({"type": 1})), "invalid schema at '#': type must be a string or an array of strings");
+        assert!(Validator::new(&json!({"pattern": "("})).validate(&json!("x")).unwrap_err().message.starts_with("invalid pattern"));
+        assert_eq!(schema_error(json!([])), "invalid schema at '#': a schema must be an object or a boolean");
+    }
+}
@@ -0,0 +1,166 @@
+use std::fs::{self, File, Metadata};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::search::{Options, read_line_lossy};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const CHUNK_SIZE: u64 = 8 * 1024;
+
+/// Identifies the file behind a path, so a rotated log (renamed away and
+/// replaced by a new file) can be told apart from one that just grew
+#[cfg(unix)]
+fn file_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Offset where the last `lines` lines start. Reads backwards from the end in
+/// chunks, so only the tail of the file is ever read.
+pub fn tail_start(file: &mut File, lines: usize) -> io::Result<u64> {
+    let len = file.seek(SeekFrom::End(0))?;
+    if lines == 0 {
+        return Ok(len);
+    }
+
+    let mut end = len;
+    let mut found = 0;
+    let mut chunk = vec![0; CHUNK_SIZE as usize];
+
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK_SIZE);
+        let size = (end - start) as usize;
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk[..size])?;
+
+        for i in (0..size).rev() {
+            let offset = start + i as u64;
+            // The newline ending the last line doesn't start another one
+            if chunk[i] == b'\n' && offset != len - 1 {
+                found += 1;
+                if found == lines {
+                    return Ok(offset + 1);
+                }
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+/// An open file being followed, and how far it has been read
+struct Followed {
+    reader: BufReader<File>,
+    id: Option<(u64, u64)>,
+    position: u64,
+    /// A line that hasn't got its newline yet
+    partial: Vec<u8>,
+}
+
+impl Followed {
+    fn open(path: &Path, start: Option<usize>) -> io::Result<Followed> {
+        let mut file = File::open(path)?;
+        let id = file_id(&file.metadata()?);
+        let position = match start {
+            Some(lines) => tail_start(&mut file, lines)?,
+            None => 0,
+        };
+        file.seek(SeekFrom::Start(position))?;
+        Ok(Followed { reader: BufReader::new(file), id, position, partial: Vec::new() })
+    }
+
+    /// Count and print one line if it matches
+    fn print_line(&mut self, bytes: Vec<u8>, options: &Options, number: &mut Option<usize>, out: &mut impl Write) -> io::Result<()> {
+        let mut buffer = Vec::new();
+        let line = read_line_lossy(&mut bytes.as_slice(), &mut buffer)?.unwrap_or_default();
+
+        if let Some(n) = number {
+            *n += 1;
+        }
+        if options.is_match(&line) {
+            options.print_line(out, "", number.unwrap_or(0), &line, ':')?;
+        }
+        Ok(())
+    }
+
+    /// Print every complete line that is available right now
+    fn print_new_lines(&mut self, options: &Options, number: &mut Option<usize>, out: &mut impl Write) -> io::Result<()> {
+        let mut buffer = Vec::new();
+
+        loop {
+            let read = self.reader.read_until(b'\n', &mut buffer)?;
+            if read == 0 {
+                return Ok(());
+            }
+            self.position += read as u64;
+
+            // Keep half written lines until the writer finishes them
+            if !buffer.ends_with(b"\n") {
+                self.partial.append(&mut buffer);
+                return Ok(());
+            }
+
+            let mut bytes = std::mem::take(&mut self.partial);
+            bytes.append(&mut buffer);
+            self.print_line(bytes, options, number, out)?;
+        }
+    }
+}
+
+/// Print the file (or its last `tail` lines), then with `follow` keep printing
+/// lines as they are appended, reopening the file when it is truncated or rotated
+pub fn run(path: &Path, tail: Option<usize>, follow: bool, options: &Options, out: &mut impl Write) -> io::Result<()> {
+    let mut file = Followed::open(path, tail)?;
+    // Line numbers are only known when reading from the start
+    let mut number = if tail.is_none() && options.line_numbers { Some(0) } else { None };
+    let mut options_without_numbers = None;
+    let options = if number.is_none() && options.line_numbers {
+        options_without_numbers.insert(Options { line_numbers: false, pattern: options.pattern.clone(), ..*options })
+    } else {
+        options
+    };
+
+    file.print_new_lines(options, &mut number, out)?;
+    if !follow {
+        // Not waiting for the rest, so the last line counts as it is
+        if !file.partial.is_empty() {
+            let partial = std::mem::take(&mut file.partial);
+            file.print_line(partial, options, &mut number, out)?;
+        }
+        return out.flush();
+    }
+    out.flush()?;
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        file.print_new_lines(options, &mut number, out)?;
+
+        // While a rotated file is missing the old handle is kept
+        let Ok(metadata) = fs::metadata(path) else {
+            out.flush()?;
+            continue;
+        };
+
+        if file_id(&metadata) != file.id {
+            eprintln!("{}: file was replaced, reopening", path.display());
+            file = Followed::open(path, None)?;
+            number = number.map(|_| 0);
+            file.print_new_lines(options, &mut number, out)?;
+        } else if metadata.len() < file.position {
+            eprintln!("{}: file was truncated", path.display());
+            file.reader.seek(SeekFrom::Start(0))?;
+            file.position = 0;
+            file.partial.clear();
+            number = number.map(|_| 0);
+            file.print_new_lines(options, &mut number, out)?;
+        }
+        out.flush()?;
+    }
+}
@@ -1,47 +1,222 @@
 use std::env;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
 
-fn main() {
-    // Read arguments
-    let args: Vec<String> = env::args().collect();
+use walkdir::WalkDir;
 
-    if args.len() < 2 {
-        println!("Invalid file");
-        return;
-    }
+mod follow;
+mod search;
+
+use search::{Options, build_pattern, looks_binary, search};
+
+fn print_usage() {
+    println!("Usage: d14_file_reader_cli [OPTIONS] <FILE>...");
+    println!("  --lines, -n          show line numbers");
+    println!("  --search, -e PATTERN only show lines matching the regex PATTERN");
+    println!("  -F, --fixed          treat PATTERN as plain text");
+    println!("  -i, --ignore-case    case-insensitive matching");
+    println!("  -v, --invert         show lines that don't match");
+    println!("  -A N, -B N, -C N     show N lines after, before or around each match");
+    println!("  -c, --count          only print the number of matching lines");
+    println!("  -r, --recursive      search directories recursively");
+    println!("  --color WHEN         highlight matches: auto (default), always or never");
+    println!("  --tail N             start from the last N lines of the file");
+    println!("  -f, --follow         keep printing lines appended to the file, like tail -f");
+    println!("Binary files are skipped. Use '-' to read stdin.");
+    println!("Exit status: 0 if a line matched, 1 if none did, 2 on errors.");
+}
 
-    let file_path = &args[1];
-    let show_lines = args.contains(&"--lines".to_string());
+struct Args {
+    paths: Vec<String>,
+    pattern: Option<String>,
+    fixed: bool,
+    ignore_case: bool,
+    recursive: bool,
+    color: Option<String>,
+    follow: bool,
+    tail: Option<usize>,
+    options: Options,
+}
 
-    let keyword = if let Some(pos) = args.iter().position(|x| x == "--search") {
-        args.get(pos + 1)
-    } else {
-        None
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args {
+        paths: Vec::new(),
+        pattern: None,
+        fixed: false,
+        ignore_case: false,
+        recursive: false,
+        color: None,
+        follow: false,
+        tail: None,
+        options: Options::default(),
     };
 
-    // Open file
-    let file = match File::open(file_path) {
-        Ok(f) => f,
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or(format!("{} needs a value", arg));
+        let count = |text: &str| text.parse::<usize>().map_err(|_| format!("Invalid line count '{}'", text));
+
+        match arg.as_str() {
+            "--lines" | "-n" => parsed.options.line_numbers = true,
+            "--search" | "-e" => parsed.pattern = Some(value()?.clone()),
+            "--fixed" | "-F" => parsed.fixed = true,
+            "--ignore-case" | "-i" => parsed.ignore_case = true,
+            "--invert" | "-v" => parsed.options.invert = true,
+            "--count" | "-c" => parsed.options.count_only = true,
+            "--recursive" | "-r" => parsed.recursive = true,
+            "--color" => parsed.color = Some(value()?.clone()),
+            "--follow" | "-f" => parsed.follow = true,
+            "--tail" => parsed.tail = Some(count(value()?)?),
+            "-A" => parsed.options.after = count(value()?)?,
+            "-B" => parsed.options.before = count(value()?)?,
+            "-C" => {
+                let n = count(value()?)?;
+                parsed.options.before = n;
+                parsed.options.after = n;
+            }
+            "--help" | "-h" => {
+                print_usage();
+                process::exit(0);
+            }
+            // -A3, -B3 and -C3 without a space
+            flag if flag.len() > 2 && ["-A", "-B", "-C"].contains(&&flag[..2]) => {
+                let n = count(&flag[2..])?;
+                match &flag[..2] {
+                    "-A" => parsed.options.after = n,
+                    "-B" => parsed.options.before = n,
+                    _ => (parsed.options.before, parsed.options.after) = (n, n),
+                }
+            }
+            flag if flag.starts_with('-') && flag != "-" => return Err(format!("Unknown option '{}'", flag)),
+            path => parsed.paths.push(path.to_string()),
+        }
+    }
+
+    if parsed.paths.is_empty() {
+        return Err("Invalid file".to_string());
+    }
+    Ok(parsed)
+}
+
+/// Every file to search, directories are expanded when searching recursively
+fn collect_files(paths: &[String], recursive: bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        let path = PathBuf::from(path);
+        if !path.is_dir() {
+            files.push(path);
+        } else if recursive {
+            for entry in WalkDir::new(&path).sort_by_file_name() {
+                match entry {
+                    Ok(entry) if entry.file_type().is_file() => files.push(entry.into_path()),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Failed to read {}", e),
+                }
+            }
+        } else {
+            eprintln!("{} is a directory, use -r to search it", path.display());
+        }
+    }
+    files
+}
+
+fn main() {
+    // Read arguments
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut args = match parse_args(&args) {
+        Ok(args) => args,
         Err(e) => {
-            eprintln!("Failed to open file {}", e);
-            return;
+            println!("{}", e);
+            print_usage();
+            process::exit(2);
+        }
+    };
+
+    if let Some(pattern) = &args.pattern {
+        match build_pattern(pattern, args.ignore_case, args.fixed) {
+            Ok(regex) => args.options.pattern = Some(regex),
+            Err(e) => {
+                eprintln!("Invalid pattern: {}", e);
+                process::exit(2);
+            }
         }
+    }
+
+    args.options.color = match args.color.as_deref() {
+        Some("always") => true,
+        Some("never") => false,
+        _ => io::stdout().is_terminal(),
     };
 
-    let reader = BufReader::new(file);
+    if args.follow || args.tail.is_some() {
+        let [path] = args.paths.as_slice() else {
+            eprintln!("--follow and --tail work on a single file");
+            process::exit(2);
+        };
+
+        let mut out = BufWriter::new(io::stdout().lock());
+        match follow::run(Path::new(path), args.tail, args.follow, &args.options, &mut out) {
+            Ok(()) => process::exit(0),
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => process::exit(0),
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", path, e);
+                process::exit(2);
+            }
+        }
+    }
+
+    let files = collect_files(&args.paths, args.recursive);
+    args.options.show_names = files.len() > 1 || args.recursive;
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    let mut matched = 0;
+    let mut failed = false;
+
+    for path in &files {
+        let name = path.display().to_string();
+        let reader: io::Result<Box<dyn BufRead>> = if name == "-" {
+            Ok(Box::new(io::stdin().lock()))
+        } else {
+            File::open(path).map(|f| Box::new(BufReader::new(f)) as Box<dyn BufRead>)
+        };
 
-    for(i, line) in reader.lines().enumerate() {
-        let line = line.unwrap();
-        let matched = keyword.map_or(true, |k| line.contains(k));
+        // Open file
+        let mut reader = match reader {
+            Ok(reader) => reader,
+            Err(e) => {
+                eprintln!("Failed to open file {}: {}", name, e);
+                failed = true;
+                continue;
+            }
+        };
 
-        if matched {
-            if show_lines {
-                println!("{}: {}", i + 1, line);
-            } else {
-                println!("{}", line);
+        match looks_binary(&mut reader) {
+            Ok(true) => continue,
+            Ok(false) => {}
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", name, e);
+                failed = true;
+                continue;
+            }
+        }
+
+        match search(&mut reader, &name, &args.options, &mut out) {
+            Ok(count) => matched += count,
+            // Stop quietly when the output is closed, e.g. piped into `head`
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => process::exit(0),
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", name, e);
+                failed = true;
             }
         }
     }
 
-}
\ No newline at end of file
+    if out.flush().is_err() {
+        process::exit(0);
+    }
+    process::exit(if failed { 2 } else if matched > 0 { 0 } else { 1 });
+}
@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+
+use regex::{Regex, RegexBuilder};
+
+const RESET: &str = "\x1b[0m";
+const MATCH_COLOR: &str = "\x1b[1;31m";
+const NAME_COLOR: &str = "\x1b[35m";
+const NUMBER_COLOR: &str = "\x1b[32m";
+const SEPARATOR_COLOR: &str = "\x1b[36m";
+
+/// How much of a file is looked at to decide whether it is binary
+pub const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub pattern: Option<Regex>,
+    pub invert: bool,
+    pub line_numbers: bool,
+    pub before: usize,
+    pub after: usize,
+    pub count_only: bool,
+    pub color: bool,
+    pub show_names: bool,
+}
+
+pub fn build_pattern(pattern: &str, ignore_case: bool, fixed: bool) -> Result<Regex, regex::Error> {
+    let pattern = if fixed { regex::escape(pattern) } else { pattern.to_string() };
+    RegexBuilder::new(&pattern).case_insensitive(ignore_case).build()
+}
+
+/// A NUL byte near the start is what grep uses to spot binary files too
+pub fn looks_binary(reader: &mut impl BufRead) -> io::Result<bool> {
+    let start = reader.fill_buf()?;
+    Ok(start[..start.len().min(BINARY_SNIFF_BYTES)].contains(&0))
+}
+
+/// Read one line without its line ending. Invalid UTF-8 is replaced instead of
+/// failing, so logs with the odd broken byte can still be searched.
+pub fn read_line_lossy(reader: &mut impl BufRead, buffer: &mut Vec<u8>) -> io::Result<Option<String>> {
+    buffer.clear();
+    if reader.read_until(b'\n', buffer)? == 0 {
+        return Ok(None);
+    }
+    if buffer.ends_with(b"\n") {
+        buffer.pop();
+        if buffer.ends_with(b"\r") {
+            buffer.pop();
+        }
+    }
+    Ok(Some(String::from_utf8_lossy(buffer).into_owned()))
+}
+
+impl Options {
+    pub fn is_match(&self, line: &str) -> bool {
+        self.pattern.as_ref().is_none_or(|p| p.is_match(line)) != self.invert
+    }
+
+    fn highlight(&self, line: &str) -> String {
+        match &self.pattern {
+            Some(pattern) if self.color && !self.invert => {
+                pattern.replace_all(line, |caps: &regex::Captures| format!("{}{}{}", MATCH_COLOR, &caps[0], RESET)).into_owned()
+            }
+            _ => line.to_string(),
+        }
+    }
+
+    /// Print a line with the optional file name and line number prefix.
+    /// `separator` is ':' for matching lines and '-' for context lines, like grep.
+    pub fn print_line(&self, out: &mut impl Write, name: &str, number: usize, line: &str, separator: char) -> io::Result<()> {
+        let paint = |text: &str, color: &str| if self.color { format!("{}{}{}", color, text, RESET) } else { text.to_string() };
+
+        if self.show_names {
+            write!(out, "{}{}", paint(name, NAME_COLOR), paint(&separator.to_string(), SEPARATOR_COLOR))?;
+        }
+        if self.line_numbers {
+            write!(out, "{}{}", paint(&number.to_string(), NUMBER_COLOR), paint(&separator.to_string(), SEPARATOR_COLOR))?;
+            // Keep the original "N: line" look when only numbers are shown
+            if !self.show_names {
+                write!(out, " ")?;
+            }
+        }
+
+        let line = if separator == ':' { self.highlight(line) } else { line.to_string() };
+        writeln!(out, "{}", line)
+    }
+}
+
+/// Search one input and print the results, returns how many lines matched
+pub fn search(reader: &mut impl BufRead, name: &str, options: &Options, out: &mut impl Write) -> io::Result<usize> {
+    let mut buffer = Vec::new();
+    let mut before: VecDeque<(usize, String)> = VecDeque::new();
+    let mut after_left = 0;
+    let mut last_printed = 0;
+    let mut count = 0;
+    let mut number = 0;
+    let has_context = options.before > 0 || options.after > 0;
+
+    while let Some(line) = read_line_lossy(reader, &mut buffer)? {
+        number += 1;
+
+        if !options.is_match(&line) {
+            if after_left > 0 {
+                after_left -= 1;
+                options.print_line(out, name, number, &line, '-')?;
+                last_printed = number;
+            } else if options.before > 0 {
+                if before.len() == options.before {
+                    before.pop_front();
+                }
+                before.push_back((number, line));
+            }
+            continue;
+        }
+
+        count += 1;
+        if options.count_only {
+            continue;
+        }
+
+        // "--" between groups of lines that aren't next to each other
+        let first = before.front().map_or(number, |(n, _)| *n);
+        if has_context && last_printed > 0 && first > last_printed + 1 {
+            writeln!(out, "{}", if options.color { format!("{}--{}", SEPARATOR_COLOR, RESET) } else { "--".to_string() })?;
+        }
+        for (n, context) in before.drain(..) {
+            options.print_line(out, name, n, &context, '-')?;
+        }
+
+        options.print_line(out, name, number, &line, ':')?;
+        last_printed = number;
+        after_left = options.after;
+    }
+
+    if options.count_only {
+        if options.show_names {
+            writeln!(out, "{}:{}", name, count)?;
+        } else {
+            writeln!(out, "{}", count)?;
+        }
+    }
+    Ok(count)
+}
@@ -0,0 +1,88 @@
+use std::cell::{Cell, RefCell};
+use std::mem::{self, MaybeUninit};
+
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A bump allocator: memory is taken from big chunks by moving an offset
+/// forward, and everything is freed at once when the arena is dropped or
+/// reset. Thousands of small values cost a handful of real allocations.
+///
+/// Values are never dropped individually, so only `Copy` types (which have
+/// nothing to clean up) can be stored.
+pub struct Arena {
+    // Boxed so the memory stays put when the Vec grows
+    chunks: RefCell<Vec<Box<[MaybeUninit<u8>]>>>,
+    chunk_size: usize,
+    /// Bytes used in the last chunk
+    offset: Cell<usize>,
+}
+
+impl Arena {
+    pub fn new() -> Arena {
+        Arena::with_chunk_size(DEFAULT_CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(chunk_size: usize) -> Arena {
+        Arena { chunks: RefCell::new(Vec::new()), chunk_size: chunk_size.max(1), offset: Cell::new(0) }
+    }
+
+    /// Reserve `size` bytes aligned to `align`, starting a new chunk when
+    /// the current one is full
+    fn bump(&self, size: usize, align: usize) -> *mut u8 {
+        let mut chunks = self.chunks.borrow_mut();
+
+        if let Some(chunk) = chunks.last_mut() {
+            let base = chunk.as_mut_ptr() as usize;
+            let start = (base + self.offset.get()).next_multiple_of(align) - base;
+            if start + size <= chunk.len() {
+                self.offset.set(start + size);
+                return chunk[start..].as_mut_ptr().cast();
+            }
+        }
+
+        // Values bigger than a chunk get a chunk of their own
+        let mut chunk = Box::new_uninit_slice(self.chunk_size.max(size + align));
+        let base = chunk.as_mut_ptr() as usize;
+        let start = base.next_multiple_of(align) - base;
+        self.offset.set(start + size);
+        let ptr = chunk[start..].as_mut_ptr().cast();
+        chunks.push(chunk);
+        ptr
+    }
+
+    pub fn alloc<T: Copy>(&self, value: T) -> &T {
+        let ptr = self.bump(mem::size_of::<T>(), mem::align_of::<T>()).cast::<T>();
+        // SAFETY: the memory is aligned for T, big enough, not handed out
+        // before, and lives until the arena is reset (which needs &mut self)
+        unsafe {
+            ptr.write(value);
+            &*ptr
+        }
+    }
+
+    pub fn alloc_str(&self, text: &str) -> &str {
+        let ptr = self.bump(text.len(), 1);
+        // SAFETY: as above, and the bytes are copied from a valid str
+        unsafe {
+            ptr.copy_from_nonoverlapping(text.as_ptr(), text.len());
+            std::str::from_utf8_unchecked(std::slice::from_raw_parts(ptr, text.len()))
+        }
+    }
+
+    /// Chunks allocated from the real allocator
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.borrow().len()
+    }
+
+    /// Free everything but the first chunk, ready to be filled again
+    pub fn reset(&mut self) {
+        self.chunks.get_mut().truncate(1);
+        self.offset.set(0);
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Arena {
+        Arena::new()
+    }
+}
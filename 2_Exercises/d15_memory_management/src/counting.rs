@@ -0,0 +1,154 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// The counters are shared by the whole process, so `track` can read them
+// without needing a handle to the allocator
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static DEALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static REALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static BYTES_FREED: AtomicUsize = AtomicUsize::new(0);
+static IN_USE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Wraps another allocator and counts everything that goes through it.
+/// Install it with `#[global_allocator]`:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAlloc<System> = CountingAlloc::new(System);
+/// ```
+pub struct CountingAlloc<A = System> {
+    inner: A,
+}
+
+impl<A> CountingAlloc<A> {
+    pub const fn new(inner: A) -> Self {
+        CountingAlloc { inner }
+    }
+}
+
+fn record_alloc(size: usize) {
+    BYTES_ALLOCATED.fetch_add(size, Ordering::Relaxed);
+    let in_use = IN_USE.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(in_use, Ordering::Relaxed);
+}
+
+fn record_free(size: usize) {
+    BYTES_FREED.fetch_add(size, Ordering::Relaxed);
+    IN_USE.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAlloc<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc(layout) };
+        if !ptr.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.inner.dealloc(ptr, layout) };
+        DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        record_free(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { self.inner.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            // Counted as freeing the old block and allocating the new one
+            REALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            record_free(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// A snapshot of the counters, or the difference between two of them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stats {
+    pub allocations: usize,
+    pub deallocations: usize,
+    pub reallocations: usize,
+    pub bytes_allocated: usize,
+    pub bytes_freed: usize,
+    /// Bytes allocated by the whole program at the time
+    pub in_use: usize,
+    /// Most bytes allocated at the same time
+    pub peak: usize,
+}
+
+impl Stats {
+    /// Counters for the whole program so far
+    pub fn now() -> Stats {
+        Stats {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+            reallocations: REALLOCATIONS.load(Ordering::Relaxed),
+            bytes_allocated: BYTES_ALLOCATED.load(Ordering::Relaxed),
+            bytes_freed: BYTES_FREED.load(Ordering::Relaxed),
+            in_use: IN_USE.load(Ordering::Relaxed),
+            peak: PEAK.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Bytes allocated minus bytes freed, negative when memory from
+    /// before was released
+    pub fn net_bytes(&self) -> isize {
+        self.bytes_allocated as isize - self.bytes_freed as isize
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} allocs, {} deallocs, {} reallocs, {} bytes allocated, {} bytes freed, net {:+} bytes, peak {} bytes",
+            self.allocations,
+            self.deallocations,
+            self.reallocations,
+            self.bytes_allocated,
+            self.bytes_freed,
+            self.net_bytes(),
+            self.peak
+        )
+    }
+}
+
+/// Run `f` and return what it allocated. `peak` is the highest usage reached
+/// above what was in use when `f` started. Calls can be nested. Allocations
+/// from other threads running at the same time are counted too.
+pub fn track<R>(f: impl FnOnce() -> R) -> (R, Stats) {
+    let before = Stats::now();
+    // Restart the peak for this scope, and put the outer one back afterwards
+    PEAK.store(before.in_use, Ordering::Relaxed);
+
+    let result = f();
+
+    let after = Stats::now();
+    PEAK.fetch_max(before.peak, Ordering::Relaxed);
+
+    let stats = Stats {
+        allocations: after.allocations - before.allocations,
+        deallocations: after.deallocations - before.deallocations,
+        reallocations: after.reallocations - before.reallocations,
+        bytes_allocated: after.bytes_allocated - before.bytes_allocated,
+        bytes_freed: after.bytes_freed - before.bytes_freed,
+        in_use: after.in_use,
+        peak: after.peak.saturating_sub(before.in_use),
+    };
+    (result, stats)
+}
@@ -1,50 +1,141 @@
+use std::alloc::System;
 use std::rc::Rc;
 use std::cell::RefCell;
- 
+
+mod arena;
+mod counting;
+
+use arena::Arena;
+use counting::{CountingAlloc, Stats, track};
+
+#[global_allocator]
+static ALLOCATOR: CountingAlloc<System> = CountingAlloc::new(System);
+
+const NODES: usize = 10_000;
+
+/// Run one demo section and print what it allocated
+fn section<R>(name: &str, f: impl FnOnce() -> R) -> R {
+    let (result, stats) = track(f);
+    println!("  [{}] {}\n", name, stats);
+    result
+}
+
+/// A node for the Box vs arena comparison
+#[derive(Clone, Copy)]
+struct Node<'a> {
+    value: usize,
+    next: Option<&'a Node<'a>>,
+}
+
+struct BoxNode {
+    value: usize,
+    next: Option<Box<BoxNode>>,
+}
+
 fn main() {
-    println!("Memory Management Demo in Rust");
- 
-    // Ownership
-    let s1 = String::from("Ownership Example");
-    let s2 = s1; // s1 moved
-    println!("Ownership transferred: {}", s2);
-    // println!("{}", s1); // Error: s1 moved
- 
-    // Borrowing
-    let s3 = String::from("Borrowing Example");
-    borrow_demo(&s3);
-    println!("After borrow: {}", s3); // still accessible
- 
-    // Mutable Borrowing
-    let mut s4 = String::from("Hello");
-    mutate_demo(&mut s4);
-    println!("After mutation: {}", s4);
- 
-    // Lifetimes
-    let result;
-    let a = String::from("abcd");
-    {
-        let b = String::from("xyz");
-        result = longest(&a, &b);
-        println!("⏳ Longest string: {}", result);    
-    }
- 
-    // Box (heap allocation)
-    let boxed = Box::new(42);
-    println!("Boxed value: {}", &boxed);
-    println!("Deboxed {}", *boxed);
-    println!("Dereference {}", boxed);
-
-    // Rc (reference-counted pointer)
-    let rc_val = Rc::new(String::from("Shared"));
-    let rc_clone = Rc::clone(&rc_val);
-    println!("Rc values: {}, {}", rc_val, rc_clone);
-    println!("Ref count: {}", Rc::strong_count(&rc_val));
- 
-    // RefCell (interior mutability)
-    let cell = RefCell::new(100);
-    *cell.borrow_mut() += 50;
-    println!("RefCell value: {}", cell.borrow());
+    println!("Memory Management Demo in Rust\n");
+
+    section("ownership", || {
+        let s1 = String::from("Ownership Example");
+        let s2 = s1; // s1 moved, no new allocation
+        println!("Ownership transferred: {}", s2);
+        // println!("{}", s1); // Error: s1 moved
+    });
+
+    section("borrowing", || {
+        let s3 = String::from("Borrowing Example");
+        borrow_demo(&s3);
+        println!("After borrow: {}", s3); // still accessible
+    });
+
+    section("mutable borrowing", || {
+        let mut s4 = String::from("Hello");
+        mutate_demo(&mut s4);
+        println!("After mutation: {}", s4); // push_str grew the buffer
+    });
+
+    section("lifetimes", || {
+        let result;
+        let a = String::from("abcd");
+        {
+            let b = String::from("xyz");
+            result = longest(&a, &b);
+            println!("⏳ Longest string: {}", result);
+        }
+    });
+
+    section("Box", || {
+        let boxed = Box::new(42);
+        println!("Boxed value: {}", &boxed);
+        println!("Deboxed {}", *boxed);
+        println!("Dereference {}", boxed);
+    });
+
+    section("Rc", || {
+        let rc_val = Rc::new(String::from("Shared"));
+        let rc_clone = Rc::clone(&rc_val); // shares the allocation
+        println!("Rc values: {}, {}", rc_val, rc_clone);
+        println!("Ref count: {}", Rc::strong_count(&rc_val));
+    });
+
+    section("RefCell", || {
+        let cell = RefCell::new(100); // lives on the stack
+        *cell.borrow_mut() += 50;
+        println!("RefCell value: {}", cell.borrow());
+    });
+
+    // Box vs arena: the same linked list built both ways
+    println!("Building a list of {} nodes", NODES);
+
+    let sum = section("Box list", || {
+        let mut head: Option<Box<BoxNode>> = None;
+        for value in 0..NODES {
+            head = Some(Box::new(BoxNode { value, next: head }));
+        }
+        let mut sum = 0;
+        let mut node = head.as_deref();
+        while let Some(n) = node {
+            sum += n.value;
+            node = n.next.as_deref();
+        }
+        // Drop one node at a time, a recursive drop could overflow the stack
+        while let Some(mut n) = head {
+            head = n.next.take();
+        }
+        sum
+    });
+    println!("Box list sum: {}", sum);
+
+    let sum = section("arena list", || {
+        let arena = Arena::new();
+        let mut head: Option<&Node> = None;
+        for value in 0..NODES {
+            head = Some(arena.alloc(Node { value, next: head }));
+        }
+        let mut sum = 0;
+        let mut node = head;
+        while let Some(n) = node {
+            sum += n.value;
+            node = n.next;
+        }
+        println!("Arena used {} chunks", arena.chunk_count());
+        sum
+    });
+    println!("Arena list sum: {}", sum);
+
+    // Resetting keeps the first chunk, so refilling the arena is free
+    let words = ["stack", "heap", "owner", "borrow", "lifetime"];
+    let mut arena = Arena::new();
+    arena.alloc_str("warm up");
+    section("arena reuse", || {
+        for _ in 0..3 {
+            arena.reset();
+            let stored: Vec<&str> = (0..1000).map(|i| arena.alloc_str(words[i % words.len()])).collect();
+            println!("Stored {} words, last was {}", stored.len(), stored[stored.len() - 1]);
+        }
+    });
+
+    println!("\nWhole program: {}", Stats::now());
 }
 
 fn borrow_demo(data: &String) {
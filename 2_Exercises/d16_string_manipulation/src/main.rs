@@ -1,6 +1,105 @@
-use std::io::{self, Write};
- 
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::process;
+
+mod ops;
+mod pipeline;
+
+use pipeline::{Pipeline, STAGES};
+
+fn print_usage() {
+    println!("Usage: d16_string_manipulation [--whole] '<op> | <op> ...' [FILE]...");
+    println!("Runs every line of the files (or stdin) through the operations in order.");
+    println!("Without arguments an interactive menu is shown.");
+    println!("  --whole    transform the whole input at once instead of line by line");
+    println!("Operations:");
+    for (stage, help) in STAGES {
+        println!("  {:<48} {}", stage, help);
+    }
+    println!("Example: d16_string_manipulation 'replace \"(\\w+)@(\\w+)\" \"$2 at $1\" | title_case' notes.txt");
+}
+
+fn open_input(path: &str) -> io::Result<Box<dyn BufRead>> {
+    if path == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+fn run_pipeline(pipeline: &Pipeline, paths: &[String], whole: bool) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    for path in paths {
+        let mut input = open_input(path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+
+        if whole {
+            let mut text = String::new();
+            input.read_to_string(&mut text)?;
+            let text = text.strip_suffix('\n').unwrap_or(&text);
+            writeln!(out, "{}", pipeline.apply(text))?;
+        } else {
+            for line in input.lines() {
+                writeln!(out, "{}", pipeline.apply(&line?))?;
+            }
+        }
+    }
+    out.flush()
+}
+
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        run_menu();
+        return;
+    }
+
+    let mut whole = false;
+    let mut positional = Vec::new();
+    for arg in &args {
+        match arg.as_str() {
+            "--whole" => whole = true,
+            "--help" | "-h" => {
+                print_usage();
+                return;
+            }
+            flag if flag.starts_with("--") => {
+                eprintln!("Unknown option '{}'", flag);
+                print_usage();
+                process::exit(2);
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let Some((pipeline, paths)) = positional.split_first() else {
+        print_usage();
+        process::exit(2);
+    };
+
+    let pipeline = match Pipeline::parse(pipeline) {
+        Ok(pipeline) => pipeline,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+
+    let stdin = ["-".to_string()];
+    let paths = if paths.is_empty() { &stdin[..] } else { paths };
+    match run_pipeline(&pipeline, paths, whole) {
+        Ok(()) => {}
+        // Stop quietly when the output is closed, e.g. piped into `head`
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_menu() {
     println!("🛠️ String Manipulation Tool");
  
     loop {
@@ -18,7 +117,7 @@ fn main() {
         match choice.trim() {
             "1" => {
                 let s = prompt("Enter a string: ");
-                println!("Reversed: {}", s.chars().rev().collect::<String>());
+                println!("Reversed: {}", ops::reverse(&s));
             }
             "2" => {
                 let s = prompt("Enter a string: ");
@@ -0,0 +1,184 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Reverse by grapheme cluster, so emoji sequences and letters with
+/// combining marks stay intact
+pub fn reverse(text: &str) -> String {
+    text.graphemes(true).rev().collect()
+}
+
+/// Split into words for the case conversions. Anything that isn't a letter
+/// or digit separates words, and so do case changes: "parseHTTPResponse"
+/// gives "parse", "HTTP", "Response".
+pub fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current: Vec<char> = Vec::new();
+    let chars: Vec<char> = text.chars().collect();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(current.drain(..).collect());
+            }
+            continue;
+        }
+
+        if let Some(&previous) = current.last() {
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            let starts_word = c.is_uppercase()
+                && (previous.is_lowercase() || previous.is_numeric() || (previous.is_uppercase() && next_is_lower));
+            if starts_word {
+                words.push(current.drain(..).collect());
+            }
+        }
+        current.push(c);
+    }
+
+    if !current.is_empty() {
+        words.push(current.into_iter().collect());
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+pub fn snake_case(text: &str) -> String {
+    words(text).iter().map(|w| w.to_lowercase()).collect::<Vec<_>>().join("_")
+}
+
+pub fn kebab_case(text: &str) -> String {
+    words(text).iter().map(|w| w.to_lowercase()).collect::<Vec<_>>().join("-")
+}
+
+pub fn camel_case(text: &str) -> String {
+    words(text)
+        .iter()
+        .enumerate()
+        .map(|(i, w)| if i == 0 { w.to_lowercase() } else { capitalize(w) })
+        .collect()
+}
+
+/// Capitalise every word in place, keeping spaces and punctuation as they
+/// are. An apostrophe doesn't start a word, "don't" gives "Don't".
+pub fn title_case(text: &str) -> String {
+    let mut title = String::with_capacity(text.len());
+    let mut previous: Option<char> = None;
+
+    for c in text.chars() {
+        let starts_word = previous.is_none_or(|p| !p.is_alphanumeric() && !matches!(p, '\'' | '\u{2019}'));
+        if starts_word {
+            title.extend(c.to_uppercase());
+        } else {
+            title.extend(c.to_lowercase());
+        }
+        previous = Some(c);
+    }
+    title
+}
+
+/// Lowercase ASCII words joined by dashes, for URLs and file names.
+/// Accents are removed ("Crème Brûlée" gives "creme-brulee") and anything
+/// else outside ASCII is dropped.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    let mut dash = false;
+
+    // NFKD splits "é" into "e" and a combining accent, which is then dropped
+    for c in text.nfkd().filter(char::is_ascii) {
+        if c.is_ascii_alphanumeric() {
+            if dash && !slug.is_empty() {
+                slug.push('-');
+            }
+            slug.push(c.to_ascii_lowercase());
+            dash = false;
+        } else {
+            dash = true;
+        }
+    }
+    slug
+}
+
+/// Break text into lines of at most `width` columns, at word boundaries.
+/// Words longer than the width get a line of their own.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.width() + 1 + word.width() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Wrap, then widen the gaps between words so every line but the last
+/// fills `width` exactly. The leftmost gaps get the extra spaces.
+pub fn justify(text: &str, width: usize) -> Vec<String> {
+    let mut lines = wrap(text, width);
+    let last = lines.len().saturating_sub(1);
+
+    for line in &mut lines[..last] {
+        let words: Vec<&str> = line.split(' ').collect();
+        let gaps = words.len() - 1;
+        let missing = width.saturating_sub(line.width());
+        if gaps == 0 || missing == 0 {
+            continue;
+        }
+
+        let mut justified = String::new();
+        for (i, word) in words.iter().enumerate() {
+            justified.push_str(word);
+            if i < gaps {
+                let extra = missing / gaps + usize::from(i < missing % gaps);
+                justified.push_str(&" ".repeat(1 + extra));
+            }
+        }
+        *line = justified;
+    }
+    lines
+}
+
+/// Edits (insert, delete or substitute one grapheme) needed to turn `a` into `b`
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<&str> = a.graphemes(true).collect();
+    let b: Vec<&str> = b.graphemes(true).collect();
+
+    // Only the previous row of the table is needed
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut row = vec![0; b.len() + 1];
+
+    for (i, ga) in a.iter().enumerate() {
+        row[0] = i + 1;
+        for (j, gb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ga != gb);
+            row[j + 1] = substitute.min(previous[j + 1] + 1).min(row[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut row);
+    }
+    previous[b.len()]
+}
+
+/// 1.0 for equal strings down to 0.0 for nothing in common
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.graphemes(true).count().max(b.graphemes(true).count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / longest as f64
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+
+use regex::Regex;
+
+use crate::ops;
+
+/// Every stage, with its arguments, for the usage text
+pub const STAGES: &[(&str, &str)] = &[
+    ("reverse", "reverse, keeping emoji and accented letters whole"),
+    ("upper, lower", "change case"),
+    ("trim", "remove leading and trailing whitespace"),
+    ("snake_case, camel_case, kebab_case", "convert identifier style"),
+    ("title_case", "capitalise every word, keeping punctuation"),
+    ("slugify", "lowercase ASCII words joined by dashes"),
+    ("wrap WIDTH", "wrap at word boundaries"),
+    ("justify WIDTH", "wrap and pad lines to exactly WIDTH"),
+    ("distance TEXT", "Levenshtein distance to TEXT"),
+    ("similarity TEXT", "similarity to TEXT, from 0.0 to 1.0"),
+    ("replace REGEX WITH", "replace matches, $1 or ${name} insert groups"),
+];
+
+#[derive(Debug)]
+pub struct PipelineError {
+    pub message: String,
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+fn error(message: String) -> PipelineError {
+    PipelineError { message }
+}
+
+#[derive(Debug)]
+enum Stage {
+    Reverse,
+    Upper,
+    Lower,
+    Trim,
+    SnakeCase,
+    CamelCase,
+    KebabCase,
+    TitleCase,
+    Slugify,
+    Wrap(usize),
+    Justify(usize),
+    Distance(String),
+    Similarity(String),
+    Replace(Regex, String),
+}
+
+/// A backslash only escapes characters that mean something here, so regex
+/// escapes like `\w` can be written as they are
+fn escaped(next: Option<char>) -> String {
+    match next {
+        Some(c) if c.is_whitespace() || "\\|'\"".contains(c) => c.to_string(),
+        Some(c) => format!("\\{}", c),
+        None => "\\".to_string(),
+    }
+}
+
+/// Split a pipeline into words and `|` separators. Quotes group words with
+/// spaces or `|` in them.
+fn tokenize(text: &str) -> Result<Vec<Option<String>>, PipelineError> {
+    let mut tokens = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '|' => {
+                tokens.extend(word.take().map(Some));
+                tokens.push(None);
+            }
+            c if c.is_whitespace() => tokens.extend(word.take().map(Some)),
+            '\'' | '"' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        // Single quotes keep backslashes as they are, like a shell
+                        Some('\\') if c == '"' => word.push_str(&escaped(chars.next())),
+                        Some(other) => word.push(other),
+                        None => return Err(error(format!("Missing closing {}", c))),
+                    }
+                }
+            }
+            '\\' => word.get_or_insert_default().push_str(&escaped(chars.next())),
+            c => word.get_or_insert_default().push(c),
+        }
+    }
+
+    tokens.extend(word.map(Some));
+    Ok(tokens)
+}
+
+fn parse_stage(words: &[String]) -> Result<Stage, PipelineError> {
+    let (name, args) = match words.split_first() {
+        Some((name, args)) => (name.as_str(), args),
+        None => return Err(error("Empty stage in pipeline".to_string())),
+    };
+
+    let expect = |count: usize, usage: &str| {
+        if args.len() == count {
+            Ok(())
+        } else {
+            Err(error(format!("Usage: {}", usage)))
+        }
+    };
+    let width = |usage: &str| {
+        expect(1, usage)?;
+        match args[0].parse::<usize>() {
+            Ok(width) if width > 0 => Ok(width),
+            _ => Err(error(format!("Invalid width '{}'", args[0]))),
+        }
+    };
+
+    let stage = match name {
+        "wrap" => Stage::Wrap(width("wrap WIDTH")?),
+        "justify" => Stage::Justify(width("justify WIDTH")?),
+        "distance" => {
+            expect(1, "distance TEXT")?;
+            Stage::Distance(args[0].clone())
+        }
+        "similarity" => {
+            expect(1, "similarity TEXT")?;
+            Stage::Similarity(args[0].clone())
+        }
+        "replace" => {
+            expect(2, "replace REGEX WITH")?;
+            let regex = Regex::new(&args[0]).map_err(|e| error(format!("Invalid pattern: {}", e)))?;
+            Stage::Replace(regex, args[1].clone())
+        }
+        _ => {
+            let stage = match name {
+                "reverse" => Stage::Reverse,
+                "upper" | "uppercase" => Stage::Upper,
+                "lower" | "lowercase" => Stage::Lower,
+                "trim" => Stage::Trim,
+                "snake_case" | "snake" => Stage::SnakeCase,
+                "camel_case" | "camel" => Stage::CamelCase,
+                "kebab_case" | "kebab" => Stage::KebabCase,
+                "title_case" | "title" => Stage::TitleCase,
+                "slugify" | "slug" => Stage::Slugify,
+                _ => return Err(error(format!("Unknown operation '{}'", name))),
+            };
+            expect(0, name)?;
+            stage
+        }
+    };
+    Ok(stage)
+}
+
+/// Operations applied one after the other, like `reverse | snake_case | trim`
+#[derive(Debug)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    pub fn parse(text: &str) -> Result<Pipeline, PipelineError> {
+        let mut stages = Vec::new();
+        let mut words = Vec::new();
+
+        // A final None flushes the last stage
+        for token in tokenize(text)?.into_iter().chain([None]) {
+            match token {
+                Some(word) => words.push(word),
+                None => stages.push(parse_stage(&std::mem::take(&mut words))?),
+            }
+        }
+        Ok(Pipeline { stages })
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+
+        for stage in &self.stages {
+            text = match stage {
+                Stage::Reverse => ops::reverse(&text),
+                Stage::Upper => text.to_uppercase(),
+                Stage::Lower => text.to_lowercase(),
+                Stage::Trim => text.trim().to_string(),
+                Stage::SnakeCase => ops::snake_case(&text),
+                Stage::CamelCase => ops::camel_case(&text),
+                Stage::KebabCase => ops::kebab_case(&text),
+                Stage::TitleCase => ops::title_case(&text),
+                Stage::Slugify => ops::slugify(&text),
+                Stage::Wrap(width) => ops::wrap(&text, *width).join("\n"),
+                Stage::Justify(width) => ops::justify(&text, *width).join("\n"),
+                Stage::Distance(other) => ops::levenshtein(&text, other).to_string(),
+                Stage::Similarity(other) => format!("{:.3}", ops::similarity(&text, other)),
+                Stage::Replace(regex, with) => regex.replace_all(&text, with.as_str()).into_owned(),
+            };
+        }
+        text
+    }
+}
@@ -0,0 +1,78 @@
+use std::error::Error;
+use std::path::Path;
+
+use crate::store::Contact;
+
+/// Which columns hold what. Exports from phones and mail clients all name
+/// their columns differently, so headers are matched loosely.
+struct Columns {
+    name: Option<usize>,
+    first_name: Option<usize>,
+    last_name: Option<usize>,
+    phone: Option<usize>,
+    email: Option<usize>,
+}
+
+impl Columns {
+    fn find(headers: &csv::StringRecord) -> Columns {
+        let headers: Vec<String> = headers.iter().map(|h| h.trim().to_lowercase()).collect();
+        let position = |test: &dyn Fn(&str) -> bool| headers.iter().position(|h| test(h));
+
+        Columns {
+            name: position(&|h| ["name", "full name", "display name", "fn"].contains(&h)),
+            first_name: position(&|h| ["first name", "given name"].contains(&h)),
+            last_name: position(&|h| ["last name", "family name", "surname"].contains(&h)),
+            phone: position(&|h| h.contains("phone") || h.contains("mobile") || h == "tel"),
+            email: position(&|h| h.contains("mail")),
+        }
+    }
+}
+
+/// Read contacts from a CSV file with a header row. Only name, phone and
+/// email are read, other columns are ignored.
+pub fn import(path: &Path) -> Result<Vec<Contact>, Box<dyn Error>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_path(path)?;
+    let columns = Columns::find(reader.headers()?);
+
+    let has_name = columns.name.is_some() || columns.first_name.is_some() || columns.last_name.is_some();
+    if !has_name && columns.phone.is_none() && columns.email.is_none() {
+        return Err("no name, phone or email column found in the header".into());
+    }
+
+    let mut contacts = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let field = |column: Option<usize>| column.and_then(|i| record.get(i)).unwrap_or_default().trim().to_string();
+
+        let mut contact = Contact {
+            name: field(columns.name),
+            phone: field(columns.phone),
+            email: field(columns.email),
+            ..Contact::default()
+        };
+        if contact.name.is_empty() {
+            let parts = [field(columns.first_name), field(columns.last_name)];
+            contact.name = parts.iter().filter(|p| !p.is_empty()).cloned().collect::<Vec<_>>().join(" ");
+        }
+        if contact.name.is_empty() {
+            contact.name = if contact.email.is_empty() { contact.phone.clone() } else { contact.email.clone() };
+        }
+        // Skip blank rows
+        if !contact.name.is_empty() {
+            contacts.push(contact);
+        }
+    }
+    Ok(contacts)
+}
+
+/// Write name, phone and email. Extra vCard fields have no column and are
+/// left out, use vCard export to keep them.
+pub fn export(path: &Path, contacts: &[Contact]) -> Result<(), Box<dyn Error>> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(["name", "phone", "email"])?;
+    for contact in contacts {
+        writer.write_record([&contact.name, &contact.phone, &contact.email])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
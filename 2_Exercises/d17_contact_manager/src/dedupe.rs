@@ -0,0 +1,166 @@
+use std::fmt;
+
+use crate::fuzzy::{normalize_phone, phones, similarity};
+use crate::store::Contact;
+use crate::vcard;
+
+/// Names at least this similar (after ignoring case and word order) are
+/// taken to be the same person
+const NAME_SIMILARITY: f64 = 0.85;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Name,
+    Phone,
+    Email,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Field::Name => write!(f, "Name"),
+            Field::Phone => write!(f, "Phone"),
+            Field::Email => write!(f, "Email"),
+        }
+    }
+}
+
+/// "Lovelace, Ada" and "ada lovelace" both give "ada lovelace"
+fn name_key(name: &str) -> String {
+    let mut words: Vec<String> = name.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).map(str::to_lowercase).collect();
+    words.sort();
+    words.join(" ")
+}
+
+/// Why two contacts look like the same person, if they do
+pub fn reason(a: &Contact, b: &Contact) -> Option<&'static str> {
+    let phones_a: Vec<String> = phones(a).filter_map(|p| normalize_phone(&p)).collect();
+    if phones(b).filter_map(|p| normalize_phone(&p)).any(|p| phones_a.contains(&p)) {
+        return Some("same phone number");
+    }
+    if !a.email.is_empty() && a.email.trim().eq_ignore_ascii_case(b.email.trim()) {
+        return Some("same email");
+    }
+    let (key_a, key_b) = (name_key(&a.name), name_key(&b.name));
+    if !key_a.is_empty() && similarity(&key_a, &key_b) >= NAME_SIMILARITY {
+        return Some("similar names");
+    }
+    None
+}
+
+/// Groups of contacts (as indices) that are likely duplicates of each
+/// other. A matches B and B matches C puts all three in one group.
+pub fn find_groups(contacts: &[Contact]) -> Vec<Vec<usize>> {
+    // Union-find over the indices
+    let mut parent: Vec<usize> = (0..contacts.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for i in 0..contacts.len() {
+        for j in i + 1..contacts.len() {
+            if reason(&contacts[i], &contacts[j]).is_some() {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for i in 0..contacts.len() {
+        let r = root(&mut parent, i);
+        match groups.iter_mut().find(|g| g[0] == r) {
+            Some(group) => group.push(i),
+            None => groups.push(vec![i]),
+        }
+    }
+    groups.retain(|g| g.len() > 1);
+    groups
+}
+
+/// Combine a group into one contact, which keeps the first contact's id.
+/// `choose` picks between differing values of a field and returns an index
+/// into them. Phones and emails that aren't picked are kept as extra vCard
+/// lines, so nothing is lost.
+pub fn merge(group: &[&Contact], mut choose: impl FnMut(Field, &[&str]) -> usize) -> Contact {
+    let mut merged = group[0].clone();
+    merged.extra.clear();
+
+    let mut pick = |field: Field, value: fn(&Contact) -> &str| -> (String, Vec<String>) {
+        let mut values: Vec<&str> = Vec::new();
+        for contact in group {
+            let v = value(contact);
+            if !v.is_empty() && !values.contains(&v) {
+                values.push(v);
+            }
+        }
+        let chosen = match values.len() {
+            0 => return (String::new(), Vec::new()),
+            1 => 0,
+            _ => choose(field, &values).min(values.len() - 1),
+        };
+        let others = values.iter().enumerate().filter(|(i, _)| *i != chosen).map(|(_, v)| v.to_string()).collect();
+        (values[chosen].to_string(), others)
+    };
+
+    let (name, _) = pick(Field::Name, |c| &c.name);
+    let (phone, other_phones) = pick(Field::Phone, |c| &c.phone);
+    let (email, other_emails) = pick(Field::Email, |c| &c.email);
+
+    // The structured name goes with the display name that was kept
+    let name_owner = group.iter().find(|c| c.name == name).unwrap_or(&group[0]);
+    merged.extra.extend(name_owner.extra.iter().filter(|l| vcard::property_name(l) == "N").cloned());
+
+    for contact in group {
+        for line in contact.extra.iter().filter(|l| vcard::property_name(l) != "N") {
+            if !merged.extra.contains(line) {
+                merged.extra.push(line.clone());
+            }
+        }
+    }
+    // Parameters go with the value they came with
+    let phone_params = |phone: &str| group.iter().find(|c| c.phone == phone).map_or("", |c| c.phone_params.as_str());
+    let email_params = |email: &str| group.iter().find(|c| c.email == email).map_or("", |c| c.email_params.as_str());
+    let others = other_phones
+        .iter()
+        .map(|p| format!("{}:{}", vcard::with_params("TEL", phone_params(p)), vcard::escape(p)))
+        .chain(other_emails.iter().map(|e| format!("{}:{}", vcard::with_params("EMAIL", email_params(e)), vcard::escape(e))));
+    for line in others {
+        if !merged.extra.contains(&line) {
+            merged.extra.push(line);
+        }
+    }
+
+    merged.name_params = name_owner.name_params.clone();
+    merged.phone_params = phone_params(&phone).to_string();
+    merged.email_params = email_params(&email).to_string();
+    merged.name = name;
+    merged.phone = phone;
+    merged.email = email;
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(name: &str, phone: &str, phone_params: &str) -> Contact {
+        Contact { name: name.to_string(), phone: phone.to_string(), phone_params: phone_params.to_string(), ..Contact::default() }
+    }
+
+    #[test]
+    fn merge_keeps_the_other_values_as_escaped_lines() {
+        let a = contact("Ada Lovelace", "+44 20 7946 0018", "TYPE=CELL");
+        let b = contact("ada lovelace", "555-0100; ext. 2", "TYPE=WORK");
+        assert_eq!(reason(&a, &b), Some("similar names"));
+
+        let merged = merge(&[&a, &b], |_, _| 0);
+        assert_eq!((merged.phone.as_str(), merged.phone_params.as_str()), ("+44 20 7946 0018", "TYPE=CELL"));
+        assert_eq!(merged.extra, ["TEL;TYPE=WORK:555-0100\\; ext. 2"]);
+        assert_eq!(vcard::property_value(&merged.extra[0]).as_deref(), Some("555-0100; ext. 2"));
+    }
+}
@@ -0,0 +1,247 @@
+use std::env;
+use std::sync::OnceLock;
+
+use crate::store::Contact;
+use crate::vcard;
+
+/// Set to a country, "GB" or "US", to read numbers written without a
+/// country code, "020 7946 0018" or "555-0100", as numbers of that country
+const REGION_VAR: &str = "CONTACTS_REGION";
+
+/// How numbers are dialled in a country
+struct Region {
+    code: &'static str,
+    country_code: &'static str,
+    /// Dialled before the area code inside the country, "0" in most of
+    /// Europe, "1" in North America and nothing in Italy or Spain
+    trunk_prefix: &'static str,
+    /// Dialled before a country code
+    international_prefix: &'static str,
+}
+
+const REGIONS: &[Region] = &[
+    Region { code: "US", country_code: "1", trunk_prefix: "1", international_prefix: "011" },
+    Region { code: "CA", country_code: "1", trunk_prefix: "1", international_prefix: "011" },
+    Region { code: "GB", country_code: "44", trunk_prefix: "0", international_prefix: "00" },
+    Region { code: "IE", country_code: "353", trunk_prefix: "0", international_prefix: "00" },
+    Region { code: "DE", country_code: "49", trunk_prefix: "0", international_prefix: "00" },
+    Region { code: "FR", country_code: "33", trunk_prefix: "0", international_prefix: "00" },
+    Region { code: "NL", country_code: "31", trunk_prefix: "0", international_prefix: "00" },
+    Region { code: "IT", country_code: "39", trunk_prefix: "", international_prefix: "00" },
+    Region { code: "ES", country_code: "34", trunk_prefix: "", international_prefix: "00" },
+    Region { code: "AU", country_code: "61", trunk_prefix: "0", international_prefix: "0011" },
+    Region { code: "NZ", country_code: "64", trunk_prefix: "0", international_prefix: "00" },
+    Region { code: "IN", country_code: "91", trunk_prefix: "0", international_prefix: "00" },
+    Region { code: "JP", country_code: "81", trunk_prefix: "0", international_prefix: "010" },
+];
+
+/// The region from CONTACTS_REGION, None when it isn't set or unknown
+fn region() -> Option<&'static Region> {
+    static REGION: OnceLock<Option<&'static Region>> = OnceLock::new();
+    *REGION.get_or_init(|| {
+        let code = env::var(REGION_VAR).ok()?.trim().to_uppercase();
+        REGIONS.iter().find(|r| r.code == code)
+    })
+}
+
+/// A query word has to score at least this much against some word of a
+/// contact for the contact to match
+const MIN_WORD_SCORE: f64 = 0.5;
+
+/// A phone number in E.164 form, "+442079460018", or just its digits when
+/// it has no country code and no region is set. "+44 (0)20" is understood,
+/// and with a region its international and trunk prefixes are too. None
+/// when there are no digits.
+pub fn normalize_phone(phone: &str) -> Option<String> {
+    normalize_phone_in(phone, region())
+}
+
+fn normalize_phone_in(phone: &str, region: Option<&Region>) -> Option<String> {
+    let phone = phone.replace("(0)", "");
+    let trimmed = phone.trim();
+    let digits: String = trimmed.chars().filter(char::is_ascii_digit).collect();
+    if digits.is_empty() {
+        return None;
+    }
+    if trimmed.starts_with('+') {
+        return Some(format!("+{}", digits));
+    }
+
+    // Without a region a national number can't be told apart from one
+    // of any other country, so it is only ever equal to itself
+    let Some(region) = region else {
+        return Some(digits);
+    };
+    if let Some(rest) = digits.strip_prefix(region.international_prefix) {
+        return Some(format!("+{}", rest));
+    }
+    let national = digits.strip_prefix(region.trunk_prefix).unwrap_or(&digits);
+    Some(format!("+{}{}", region.country_code, national))
+}
+
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut row = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            row[j + 1] = (previous[j] + usize::from(ca != cb)).min(previous[j + 1] + 1).min(row[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut row);
+    }
+    previous[b.len()]
+}
+
+/// 1.0 for equal strings down to 0.0 for nothing in common
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / longest as f64
+}
+
+/// How well one query word matches one word of a contact. Typos are
+/// forgiven: one edit in short words, two in longer ones.
+fn word_score(query: &str, word: &str) -> f64 {
+    if word == query {
+        return 1.0;
+    }
+    if word.starts_with(query) {
+        return 0.9;
+    }
+    if word.contains(query) {
+        return 0.8;
+    }
+
+    let allowed = if query.chars().count() <= 4 { 1 } else { 2 };
+    // Compare with the start of longer words too, so "lovl" finds "lovelace"
+    let prefix: String = word.chars().take(query.chars().count()).collect();
+    let distance = levenshtein(query, word).min(levenshtein(query, &prefix) + 1);
+    if distance <= allowed { 0.7 - 0.1 * distance as f64 } else { 0.0 }
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).map(str::to_lowercase)
+}
+
+/// vCard properties kept in `extra` that are worth searching. Others hold
+/// binary data (PHOTO, KEY) or identifiers (UID) that short queries would
+/// match by chance.
+const SEARCHED_PROPERTIES: &[&str] = &["ORG", "NICKNAME", "ADR", "NOTE", "TITLE", "ROLE"];
+
+/// Every searchable word of a contact with a weight, name words count the most
+fn contact_words(contact: &Contact) -> Vec<(String, f64)> {
+    let mut all: Vec<(String, f64)> = words(&contact.name).map(|w| (w, 1.0)).collect();
+    all.extend(words(&contact.email).map(|w| (w, 0.9)));
+    for line in contact.extra.iter().filter(|line| SEARCHED_PROPERTIES.contains(&vcard::property_name(line).as_str())) {
+        let value = vcard::property_value(line).unwrap_or_default();
+        all.extend(words(&value).map(|w| (w, 0.8)));
+    }
+    all
+}
+
+/// The main phone number and any others kept from a vCard
+pub fn phones(contact: &Contact) -> impl Iterator<Item = String> + '_ {
+    let others = contact
+        .extra
+        .iter()
+        .filter(|line| vcard::property_name(line) == "TEL")
+        .filter_map(|line| vcard::property_value(line))
+        .map(|value| value.strip_prefix("tel:").map(str::to_string).unwrap_or(value));
+    std::iter::once(contact.phone.clone()).chain(others)
+}
+
+/// How well a contact matches the query, 0.0 when it doesn't. Every word
+/// of the query has to match something.
+pub fn score(contact: &Contact, query: &str) -> f64 {
+    let query_words: Vec<String> = words(query).collect();
+    if query_words.is_empty() {
+        return 0.0;
+    }
+
+    // Mostly digits: compare as a phone number
+    let digits = query.chars().filter(char::is_ascii_digit).count();
+    if digits >= 3 && digits * 2 >= query.trim().len() {
+        let query_digits: String = query.chars().filter(char::is_ascii_digit).collect();
+        // "020 7946" is part of "+44 20 7946 0018" once the trunk prefix is gone
+        let query_digits = query_digits.trim_start_matches('0');
+        for phone in phones(contact) {
+            if let (Some(a), Some(b)) = (normalize_phone(&phone), normalize_phone(query)) {
+                if a == b {
+                    return 1.0;
+                }
+                if !query_digits.is_empty() && a.contains(query_digits) {
+                    return 0.8;
+                }
+            }
+        }
+        return 0.0;
+    }
+
+    let fields = contact_words(contact);
+    let mut total = 0.0;
+    for query_word in &query_words {
+        let best = fields.iter().map(|(word, weight)| word_score(query_word, word) * weight).fold(0.0, f64::max);
+        if best < MIN_WORD_SCORE {
+            return 0.0;
+        }
+        total += best;
+    }
+    total / query_words.len() as f64
+}
+
+/// Contacts matching the query, best match first
+pub fn search<'a>(contacts: &'a [Contact], query: &str) -> Vec<&'a Contact> {
+    let mut scored: Vec<(f64, &Contact)> =
+        contacts.iter().map(|c| (score(c, query), c)).filter(|(score, _)| *score > 0.0).collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.name.cmp(&b.1.name)));
+    scored.into_iter().map(|(_, c)| c).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_region(phone: &str, code: &str) -> Option<String> {
+        normalize_phone_in(phone, REGIONS.iter().find(|r| r.code == code))
+    }
+
+    #[test]
+    fn national_numbers_use_the_region() {
+        let london = Some("+442079460018".to_string());
+        assert_eq!(in_region("020 7946 0018", "GB"), london);
+        assert_eq!(in_region("+44 (0)20 7946 0018", "GB"), london);
+        assert_eq!(in_region("0044 20 7946 0018", "GB"), london);
+        assert_eq!(in_region("+44 20 7946 0018", "US"), london);
+        assert_eq!(in_region("(555) 010-0199", "US"), Some("+15550100199".to_string()));
+        assert_eq!(in_region("1 555 010 0199", "CA"), Some("+15550100199".to_string()));
+        // No trunk prefix in Italy, the leading 0 is part of the number
+        assert_eq!(in_region("06 1234 5678", "IT"), Some("+390612345678".to_string()));
+    }
+
+    #[test]
+    fn national_numbers_without_a_region_stay_as_they_are() {
+        assert_eq!(normalize_phone_in("020 7946 0018", None), Some("02079460018".to_string()));
+        assert_eq!(normalize_phone_in("+44 20 7946 0018", None), Some("+442079460018".to_string()));
+        assert_eq!(normalize_phone_in("n/a", None), None);
+    }
+
+    #[test]
+    fn reads_phones_from_kept_lines() {
+        let extra = ["TEL;TYPE=\"a:b\":+1 555 0101", "item1.TEL:tel:+1-555-0102", "NOTE:call 555 0103"];
+        let contact = Contact { phone: "+1 555 0100".to_string(), extra: extra.map(String::from).to_vec(), ..Contact::default() };
+        assert_eq!(phones(&contact).collect::<Vec<_>>(), ["+1 555 0100", "+1 555 0101", "+1-555-0102"]);
+    }
+
+    #[test]
+    fn similarity_ranges_from_zero_to_one() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+        assert_eq!(similarity("zoë", "zoe"), 1.0 - 1.0 / 3.0);
+    }
+}
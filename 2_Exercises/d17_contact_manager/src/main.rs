@@ -1,100 +1,405 @@
+use std::env;
+use std::fs;
 use std::io::{self, Write};
+use std::path::Path;
+use std::process;
 
+mod csv_io;
+mod dedupe;
+mod fuzzy;
+mod store;
+mod vcard;
 
-#[derive(Debug)]
-struct Contact {
-    id: usize,
-    name: String,
-    phone: String,
-    email: String
-}
+use dedupe::Field;
+use store::{Contact, ContactBook};
+use vcard::Version;
+
+const CONTACTS_FILE: &str = "contacts.json";
 
 fn main() {
-    // Create a vector of contacts
-    let mut contacts: Vec<Contact> = Vec::new();
-    let mut next_id = 1;
+    let args: Vec<String> = env::args().skip(1).collect();
+    let path = Path::new(CONTACTS_FILE);
+
+    let mut book = match ContactBook::load(path) {
+        Ok(book) => book,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", CONTACTS_FILE, e);
+            process::exit(1);
+        }
+    };
+
+    if args.is_empty() {
+        menu(&mut book, path);
+        return;
+    }
+
+    match run_command(&mut book, &args) {
+        // Listing and searching leave contacts.json untouched
+        Ok(true) => {
+            if let Err(e) = book.save(path) {
+                eprintln!("Failed to save contacts: {}", e);
+                process::exit(1);
+            }
+        }
+        Ok(false) => {}
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
 
+fn print_usage() {
+    println!("Usage:");
+    println!("  contacts                                         interactive menu");
+    println!("  contacts add <NAME> [--phone PHONE] [--email EMAIL]");
+    println!("  contacts ls");
+    println!("  contacts search <WORDS>                          fuzzy search over every field");
+    println!("  contacts edit <ID> [--name NAME] [--phone PHONE] [--email EMAIL]");
+    println!("  contacts rm <ID>...");
+    println!("  contacts dedupe                                  find and merge duplicates");
+    println!("  contacts import <FILE.vcf | FILE.csv>");
+    println!("  contacts export <FILE.vcf | FILE.csv> [--vcard-version 3.0|4.0]");
+    println!("Set CONTACTS_REGION (GB, US, DE, ...) to match numbers without a country code, like 020 7946 0018.");
+}
+
+fn parse_id(text: &str) -> Result<usize, String> {
+    text.parse().map_err(|_| format!("Invalid contact id '{}'", text))
+}
+
+/// The format of a file, from its extension
+fn is_vcard(path: &str) -> Result<bool, String> {
+    let extension = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    match extension.as_str() {
+        "vcf" | "vcard" => Ok(true),
+        "csv" => Ok(false),
+        _ => Err(format!("Unknown format for '{}', use a .vcf or .csv file", path)),
+    }
+}
+
+fn import_file(book: &mut ContactBook, path: &str) -> Result<usize, String> {
+    let contacts = if is_vcard(path)? {
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        vcard::parse(&text).map_err(|e| format!("{}: {}", path, e))?
+    } else {
+        csv_io::import(Path::new(path)).map_err(|e| format!("{}: {}", path, e))?
+    };
+
+    let count = contacts.len();
+    for contact in contacts {
+        book.add(contact);
+    }
+    Ok(count)
+}
+
+fn export_file(book: &ContactBook, path: &str, version: Version) -> Result<(), String> {
+    let result = if is_vcard(path)? {
+        fs::write(path, vcard::export(&book.contacts, version)).map_err(|e| e.to_string())
+    } else {
+        csv_io::export(Path::new(path), &book.contacts).map_err(|e| e.to_string())
+    };
+    result.map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Run one subcommand, returns whether the contacts changed
+fn run_command(book: &mut ContactBook, args: &[String]) -> Result<bool, String> {
+    let rest = &args[1..];
+
+    // --flag VALUE pairs, anything else is collected as a word
+    let mut words = Vec::new();
+    let mut flags: Vec<(&str, &String)> = Vec::new();
+    let mut iter = rest.iter();
+    while let Some(arg) = iter.next() {
+        if arg.starts_with("--") {
+            flags.push((arg, iter.next().ok_or(format!("{} needs a value", arg))?));
+        } else {
+            words.push(arg.as_str());
+        }
+    }
+    let flag = |name: &str| flags.iter().find(|(f, _)| *f == name).map(|(_, v)| v.to_string());
+    if let Some((unknown, _)) = flags.iter().find(|(f, _)| !["--name", "--phone", "--email", "--vcard-version"].contains(f)) {
+        return Err(format!("Unknown option '{}'", unknown));
+    }
+
+    match args[0].as_str() {
+        "add" => {
+            let name = words.join(" ");
+            if name.trim().is_empty() {
+                return Err("Contact name can't be empty".to_string());
+            }
+            let contact = book.add(Contact {
+                name: name.trim().to_string(),
+                phone: flag("--phone").unwrap_or_default(),
+                email: flag("--email").unwrap_or_default(),
+                ..Contact::default()
+            });
+            println!("Contact added with Id: {}", contact.id);
+            Ok(true)
+        }
+        "ls" | "list" => {
+            view_contacts(&book.contacts.iter().collect::<Vec<_>>());
+            Ok(false)
+        }
+        "search" => {
+            view_contacts(&fuzzy::search(&book.contacts, &words.join(" ")));
+            Ok(false)
+        }
+        "dedupe" => Ok(dedupe_contacts(book)),
+        "edit" => {
+            let id = parse_id(words.first().ok_or("Missing contact id")?)?;
+            let contact = book.get_mut(id).ok_or(format!("No contact with id {}", id))?;
+            if let Some(name) = flag("--name") {
+                contact.rename(name);
+            }
+            if let Some(phone) = flag("--phone") {
+                contact.phone = phone;
+            }
+            if let Some(email) = flag("--email") {
+                contact.email = email;
+            }
+            println!("Contact updated!");
+            Ok(true)
+        }
+        "rm" | "delete" => {
+            if words.is_empty() {
+                return Err("Missing contact id".to_string());
+            }
+            for word in words {
+                let id = parse_id(word)?;
+                book.remove(id).ok_or(format!("No contact with id {}", id))?;
+            }
+            println!("Contact deleted!");
+            Ok(true)
+        }
+        "import" => {
+            let path = words.first().ok_or("Missing file to import")?;
+            let count = import_file(book, path)?;
+            println!("Imported {} contacts from {}", count, path);
+            Ok(true)
+        }
+        "export" => {
+            let path = words.first().ok_or("Missing output file")?;
+            let version = match flag("--vcard-version") {
+                Some(v) => Version::parse(&v).ok_or(format!("Unsupported vCard version '{}'", v))?,
+                None => Version::V4,
+            };
+            export_file(book, path, version)?;
+            println!("Exported {} contacts to {}", book.contacts.len(), path);
+            Ok(false)
+        }
+        "help" | "--help" | "-h" => {
+            print_usage();
+            Ok(false)
+        }
+        other => {
+            print_usage();
+            Err(format!("Unknown command '{}'", other))
+        }
+    }
+}
+
+fn view_contacts(contacts: &[&Contact]) {
+    if contacts.is_empty() {
+        println!("No contacts!");
+    }
+    for c in contacts {
+        println!("[{}] {} | {} | {}", c.id, c.name, c.phone, c.email);
+    }
+}
+
+/// Saves after every change, so leaving with Ctrl-C loses nothing
+fn menu(book: &mut ContactBook, path: &Path) {
     loop {
         println!("\nContact Manager:");
         println!("1. Add Contact");
         println!("2. View Contacts");
         println!("3. Search Contact");
-        println!("4. Delete Contact");
-        println!("5. Exit");
+        println!("4. Edit Contact");
+        println!("5. Delete Contact");
+        println!("6. Import (.vcf or .csv)");
+        println!("7. Export (.vcf or .csv)");
+        println!("8. Find duplicates");
+        println!("9. Exit");
 
-        let choice = input("Enter your choice: ");
-        match choice.trim() {
+        // Every change is already saved, so end of input is just an exit
+        let Some(choice) = read_line("Enter your choice: ") else {
+            println!();
+            break;
+        };
+        let changed = match choice.trim() {
             "1" => {
                 let name = input("Name: ");
                 let phone = input("Phone: ");
                 let email = input("Email: ");
-                contacts.push(Contact {
-                    id: next_id,
-                    name,
-                    phone,
-                    email
-                });
-                println!("Contact added with Id: {}", next_id);
-                next_id +=1;
+                let contact = book.add(Contact { name, phone, email, ..Contact::default() });
+                println!("Contact added with Id: {}", contact.id);
+                true
             }
             "2" => {
-                if contacts.len() == 0 {
-                    println!("No contacts!");
-                } else {
-                    for c in &contacts {
-                        println!("[{}] {} | {} | {}", c.id, c.name, c.phone, c.email);
-                    }
-                }
+                view_contacts(&book.contacts.iter().collect::<Vec<_>>());
+                false
             }
             "3" => {
                 let query = input("Enter your word ");
-
-                let results: Vec<&Contact> = contacts
-                    .iter()
-                    .filter(
-                        |c| c.name.contains(&query) ||
-                            c.email.contains(&query))
-                    .collect();
-                
-                if results.len() == 0 {
+                let results = fuzzy::search(&book.contacts, &query);
+                if results.is_empty() {
                     println!("No results!");
                 } else {
-                    for c in results {
-                        println!("[{}] {} | {} | {}", c.id, c.name, c.phone, c.email);
-                    }
+                    view_contacts(&results);
                 }
+                false
             }
-            "4" => {
+            "4" => edit_contact(book),
+            "5" => {
                 let id = input("Enter Id to delete").parse::<usize>().unwrap_or(0);
-                let len_before = contacts.len();
-                contacts.retain(|c| c.id != id);
-
-                if contacts.len() < len_before {
+                if book.remove(id).is_some() {
                     println!("Contact deleted!");
+                    true
                 } else {
                     println!("Id not found!");
+                    false
                 }
             }
-            "5" => {
+            "6" => {
+                let path = input("File to import: ");
+                match import_file(book, &path) {
+                    Ok(count) => {
+                        println!("Imported {} contacts", count);
+                        true
+                    }
+                    Err(e) => {
+                        println!("{}", e);
+                        false
+                    }
+                }
+            }
+            "7" => {
+                let path = input("File to export to: ");
+                let version = if is_vcard(&path).unwrap_or(false) {
+                    Version::parse(&input("vCard version (3.0/4.0, empty for 4.0): ")).unwrap_or(Version::V4)
+                } else {
+                    Version::V4
+                };
+                match export_file(book, &path, version) {
+                    Ok(()) => println!("Exported {} contacts", book.contacts.len()),
+                    Err(e) => println!("{}", e),
+                }
+                false
+            }
+            "8" => dedupe_contacts(book),
+            "9" => {
                 println!("Good bye!");
                 break;
             }
             _ => {
                 println!("Incorrect choice!");
+                false
             }
+        };
+
+        if changed && let Err(e) = book.save(path) {
+            println!("Failed to save contacts: {}", e);
+        }
+    }
+}
+
+/// Returns whether the contact was found and updated
+fn edit_contact(book: &mut ContactBook) -> bool {
+    let id = input("Enter Id to edit: ").parse::<usize>().unwrap_or(0);
+    let Some(contact) = book.get_mut(id) else {
+        println!("Id not found!");
+        return false;
+    };
+
+    // An empty answer keeps the current value
+    let name = input(&format!("Name [{}]: ", contact.name));
+    if !name.is_empty() {
+        contact.rename(name);
+    }
+    let phone = input(&format!("Phone [{}]: ", contact.phone));
+    if !phone.is_empty() {
+        contact.phone = phone;
+    }
+    let email = input(&format!("Email [{}]: ", contact.email));
+    if !email.is_empty() {
+        contact.email = email;
+    }
+    println!("Contact updated!");
+    true
+}
 
+/// Ask which of the differing values of a field to keep
+fn choose_value(field: Field, values: &[&str]) -> usize {
+    println!("{}:", field);
+    for (i, value) in values.iter().enumerate() {
+        println!("  {}. {}", i + 1, value);
+    }
+    loop {
+        let answer = input("Keep which? [1]: ");
+        if answer.is_empty() {
+            return 0;
+        }
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=values.len()).contains(&n) => return n - 1,
+            _ => println!("Enter a number from 1 to {}", values.len()),
         }
     }
 }
 
-fn input(prompt: &str) -> String{
+/// Go through groups of likely duplicates and merge the ones confirmed,
+/// returns whether anything was merged
+fn dedupe_contacts(book: &mut ContactBook) -> bool {
+    // Ids, since merging removes contacts and shifts the indices
+    let groups: Vec<Vec<usize>> = dedupe::find_groups(&book.contacts)
+        .iter()
+        .map(|group| group.iter().map(|&i| book.contacts[i].id).collect())
+        .collect();
+    if groups.is_empty() {
+        println!("No duplicates found!");
+        return false;
+    }
+
+    let mut changed = false;
+    for (n, ids) in groups.iter().enumerate() {
+        let group: Vec<Contact> = ids.iter().filter_map(|id| book.contacts.iter().find(|c| c.id == *id)).cloned().collect();
+        println!("\nPossible duplicates {}/{}:", n + 1, groups.len());
+        for (i, c) in group.iter().enumerate() {
+            // Say what it has in common with one of the contacts before it
+            let why = group[..i].iter().find_map(|other| dedupe::reason(c, other).map(|why| format!(" ({} as {})", why, other.id)));
+            println!("[{}] {} | {} | {}{}", c.id, c.name, c.phone, c.email, why.unwrap_or_default());
+        }
+        match input("Merge them? (y/n/q): ").to_lowercase().as_str() {
+            "y" | "yes" => {}
+            "q" | "quit" => break,
+            _ => continue,
+        }
+
+        let merged = dedupe::merge(&group.iter().collect::<Vec<_>>(), choose_value);
+        for contact in &group[1..] {
+            book.remove(contact.id);
+        }
+        if let Some(first) = book.get_mut(merged.id) {
+            *first = merged;
+        }
+        println!("Merged into contact {}", ids[0]);
+        changed = true;
+    }
+    changed
+}
+
+fn input(prompt: &str) -> String {
+    read_line(prompt).unwrap_or_default()
+}
+
+/// None at the end of input (Ctrl-D)
+fn read_line(prompt: &str) -> Option<String> {
     print!("{}", prompt);
     io::stdout().flush().unwrap();
     let mut buf = String::new();
 
-    io::stdin()
-        .read_line(&mut buf)
-        .unwrap();
+    if io::stdin().read_line(&mut buf).unwrap() == 0 {
+        return None;
+    }
 
-    buf.trim().to_string()
+    Some(buf.trim().to_string())
 }
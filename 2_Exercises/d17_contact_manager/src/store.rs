@@ -0,0 +1,98 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Contact {
+    pub id: usize,
+    pub name: String,
+    pub phone: String,
+    pub email: String,
+    /// vCard parameters of the name, phone and email, "TYPE=CELL", so an
+    /// export writes them back
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name_params: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub phone_params: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub email_params: String,
+    /// vCard lines for properties that aren't modelled here (addresses,
+    /// birthdays, photos...), kept as they were so an export gives them back
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<String>,
+}
+
+/// Everything stored in contacts.json. Ids of deleted and merged contacts
+/// are retired with them, an id seen in an earlier `ls` never comes back
+/// as somebody else.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContactBook {
+    pub next_id: usize,
+    pub contacts: Vec<Contact>,
+}
+
+impl Default for ContactBook {
+    fn default() -> ContactBook {
+        ContactBook { next_id: 1, contacts: Vec::new() }
+    }
+}
+
+impl ContactBook {
+    pub fn load(path: &Path) -> io::Result<ContactBook> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(ContactBook::default()),
+            Err(e) => return Err(e),
+        };
+
+        let mut book: ContactBook = serde_json::from_str(&content).map_err(io::Error::other)?;
+        // The file may have been edited by hand, or written without next_id
+        let max_id = book.contacts.iter().map(|c| c.id).max().unwrap_or(0);
+        book.next_id = book.next_id.max(max_id + 1);
+        Ok(book)
+    }
+
+    /// Goes through contacts.json.tmp, renamed over the real file
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+
+        let mut tmp_name = path.as_os_str().to_owned();
+        tmp_name.push(".tmp");
+        let tmp_path = Path::new(&tmp_name);
+
+        let mut file = File::create(tmp_path)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(tmp_path, path)
+    }
+
+    /// Add a contact, its id is replaced by a fresh one
+    pub fn add(&mut self, mut contact: Contact) -> &Contact {
+        contact.id = self.next_id;
+        self.next_id += 1;
+        self.contacts.push(contact);
+        self.contacts.last().unwrap()
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Contact> {
+        self.contacts.iter_mut().find(|c| c.id == id)
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Contact> {
+        let index = self.contacts.iter().position(|c| c.id == id)?;
+        Some(self.contacts.remove(index))
+    }
+}
+
+impl Contact {
+    /// Change the name. A structured name (`N`) kept from an imported vCard
+    /// would no longer match, so it is dropped and rebuilt on export.
+    pub fn rename(&mut self, name: String) {
+        self.name = name;
+        self.extra.retain(|line| crate::vcard::property_name(line) != "N");
+    }
+}
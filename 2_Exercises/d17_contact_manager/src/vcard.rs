@@ -0,0 +1,342 @@
+use std::fmt;
+
+use crate::store::Contact;
+
+/// Lines longer than this (in bytes, without the line break) are folded
+const FOLD_WIDTH: usize = 75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    V3,
+    V4,
+}
+
+impl Version {
+    pub fn parse(text: &str) -> Option<Version> {
+        match text {
+            "3" | "3.0" => Some(Version::V3),
+            "4" | "4.0" => Some(Version::V4),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct VcardError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for VcardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for VcardError {}
+
+/// One unfolded content line, `group.NAME;PARAM=x:value`
+struct Property<'a> {
+    name: String,
+    /// "PARAM=x", everything between the name and the value
+    params: &'a str,
+    value: &'a str,
+}
+
+/// Index of the ':' between the name and the value, skipping any inside
+/// quoted parameter values
+fn value_start(line: &str) -> Option<usize> {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The property name of a content line, uppercased and without its group
+pub fn property_name(line: &str) -> String {
+    let head = &line[..value_start(line).unwrap_or(line.len())];
+    let name = head.split(';').next().unwrap_or_default();
+    name.rsplit('.').next().unwrap_or_default().to_uppercase()
+}
+
+/// The unescaped value of a content line
+pub fn property_value(line: &str) -> Option<String> {
+    value_start(line).map(|colon| unescape(&line[colon + 1..]))
+}
+
+fn parse_property(line: &str) -> Option<Property<'_>> {
+    let colon = value_start(line)?;
+    let params = line[..colon].split_once(';').map_or("", |(_, params)| params);
+    Some(Property { name: property_name(line), params, value: &line[colon + 1..] })
+}
+
+/// Parameters to keep for a property that is modelled. VALUE only says
+/// how the value was written, export decides that again.
+fn kept_params(params: &str) -> String {
+    let kept: Vec<&str> = params.split(';').filter(|p| !p.is_empty() && !p.to_uppercase().starts_with("VALUE=")).collect();
+    kept.join(";")
+}
+
+/// "TEL" and "TYPE=CELL" give "TEL;TYPE=CELL"
+pub fn with_params(name: &str, params: &str) -> String {
+    if params.is_empty() { name.to_string() } else { format!("{};{}", name, params) }
+}
+
+fn unescape(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(',', "\\,").replace(';', "\\;").replace('\n', "\\n")
+}
+
+/// Join folded lines back together: a line starting with a space or tab
+/// continues the one before. Each line keeps the number it started on.
+fn unfold(text: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some((_, previous))) => previous.push_str(rest),
+            _ => lines.push((i + 1, line.to_string())),
+        }
+    }
+    lines
+}
+
+/// "Lovelace;Ada;King;Lady;" gives "Lady Ada King Lovelace"
+fn name_from_structured(value: &str) -> String {
+    let parts: Vec<String> = value.split(';').map(unescape).collect();
+    let order = [3, 1, 2, 0, 4];
+    order.iter().filter_map(|&i| parts.get(i)).filter(|p| !p.is_empty()).cloned().collect::<Vec<_>>().join(" ")
+}
+
+/// Read every card in a .vcf file. Works for 3.0 and 4.0 (and mostly for
+/// 2.1). Properties other than the name and the first phone and email are
+/// kept in `extra`.
+pub fn parse(text: &str) -> Result<Vec<Contact>, VcardError> {
+    let mut contacts = Vec::new();
+    let mut current: Option<(usize, Contact)> = None;
+    let mut structured_name = None;
+
+    for (number, line) in unfold(text) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let error = |message: &str| VcardError { line: number, message: message.to_string() };
+        let property = parse_property(&line).ok_or_else(|| error("expected NAME:value"))?;
+
+        let Some((_, contact)) = current.as_mut() else {
+            if property.name == "BEGIN" && property.value.eq_ignore_ascii_case("VCARD") {
+                current = Some((number, Contact::default()));
+                structured_name = None;
+                continue;
+            }
+            return Err(error("expected BEGIN:VCARD"));
+        };
+
+        match property.name.as_str() {
+            "BEGIN" => return Err(error("BEGIN:VCARD inside another card")),
+            "END" => {
+                let (start, mut contact) = current.take().unwrap();
+                if contact.name.is_empty() {
+                    contact.name = structured_name.take().unwrap_or_default();
+                }
+                if contact.name.is_empty() {
+                    // Cards from phones sometimes only have a number
+                    contact.name = if contact.email.is_empty() { contact.phone.clone() } else { contact.email.clone() };
+                }
+                if contact.name.is_empty() {
+                    return Err(VcardError { line: start, message: "card has no name, phone or email".to_string() });
+                }
+                contacts.push(contact);
+            }
+            // Written again on export, in the version asked for
+            "VERSION" => {}
+            "FN" if contact.name.is_empty() => {
+                contact.name = unescape(property.value);
+                contact.name_params = kept_params(property.params);
+            }
+            "TEL" if contact.phone.is_empty() => {
+                // 4.0 phones are often URIs, "tel:+1-555-0100"
+                let value = property.value.strip_prefix("tel:").unwrap_or(property.value);
+                contact.phone = unescape(value);
+                contact.phone_params = kept_params(property.params);
+            }
+            "EMAIL" if contact.email.is_empty() => {
+                contact.email = unescape(property.value);
+                contact.email_params = kept_params(property.params);
+            }
+            name => {
+                if name == "N" {
+                    structured_name = Some(name_from_structured(property.value));
+                }
+                contact.extra.push(line.clone());
+            }
+        }
+    }
+
+    if let Some((start, _)) = current {
+        return Err(VcardError { line: start, message: "card is missing END:VCARD".to_string() });
+    }
+    Ok(contacts)
+}
+
+/// Split a line into pieces of at most FOLD_WIDTH bytes, without cutting
+/// through a character
+fn fold(line: &str, out: &mut String) {
+    let mut rest = line;
+    let mut width = FOLD_WIDTH;
+
+    while rest.len() > width {
+        let mut cut = width;
+        while !rest.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        out.push_str(&rest[..cut]);
+        out.push_str("\r\n ");
+        rest = &rest[cut..];
+        // The leading space counts towards the limit
+        width = FOLD_WIDTH - 1;
+    }
+    out.push_str(rest);
+    out.push_str("\r\n");
+}
+
+/// "Ada King Lovelace" gives "Lovelace;Ada King;;;"
+fn structured_name(name: &str) -> String {
+    match name.trim().rsplit_once(' ') {
+        Some((given, family)) => format!("{};{};;;", escape(family), escape(given)),
+        None => format!("{};;;;", escape(name.trim())),
+    }
+}
+
+pub fn export(contacts: &[Contact], version: Version) -> String {
+    let mut out = String::new();
+
+    for contact in contacts {
+        let mut lines = vec![
+            "BEGIN:VCARD".to_string(),
+            format!("VERSION:{}", if version == Version::V3 { "3.0" } else { "4.0" }),
+            format!("{}:{}", with_params("FN", &contact.name_params), escape(&contact.name)),
+        ];
+
+        // N is required in 3.0
+        let has_n = contact.extra.iter().any(|line| property_name(line) == "N");
+        if version == Version::V3 && !has_n {
+            lines.push(format!("N:{}", structured_name(&contact.name)));
+        }
+        if !contact.phone.is_empty() {
+            let tel = with_params("TEL", &contact.phone_params);
+            // 4.0 prefers a tel: URI, but only a number that already is one
+            // can be written that way without changing it
+            let is_uri = contact.phone.chars().all(|c| c.is_ascii_digit() || "+-.()".contains(c));
+            lines.push(match version {
+                Version::V4 if is_uri => format!("{};VALUE=uri:tel:{}", tel, contact.phone),
+                Version::V4 => format!("{};VALUE=text:{}", tel, escape(&contact.phone)),
+                Version::V3 => format!("{}:{}", tel, escape(&contact.phone)),
+            });
+        }
+        if !contact.email.is_empty() {
+            lines.push(format!("{}:{}", with_params("EMAIL", &contact.email_params), escape(&contact.email)));
+        }
+        lines.extend(contact.extra.iter().cloned());
+        lines.push("END:VCARD".to_string());
+
+        for line in lines {
+            fold(&line, &mut out);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARD: &str = "BEGIN:VCARD\r\n\
+VERSION:3.0\r\n\
+FN;CHARSET=UTF-8:Ada Lovelace\r\n\
+N:Lovelace;Ada;;;\r\n\
+TEL;TYPE=CELL,VOICE:+44 20 7946 0018\r\n\
+EMAIL;TYPE=\"work,pref\":ada@example.com\r\n\
+NOTE:Wrote the first program\\, for the Analytical\r\n  Engine\r\n\
+item1.ADR;TYPE=HOME:;;12 St James's Square;London;;SW1Y 4JH;UK\r\n\
+END:VCARD\r\n";
+
+    #[test]
+    fn parses_params_and_keeps_other_lines() {
+        let contacts = parse(CARD).unwrap();
+        assert_eq!(contacts.len(), 1);
+        let ada = &contacts[0];
+        assert_eq!(ada.name, "Ada Lovelace");
+        assert_eq!(ada.name_params, "CHARSET=UTF-8");
+        assert_eq!(ada.phone, "+44 20 7946 0018");
+        assert_eq!(ada.phone_params, "TYPE=CELL,VOICE");
+        assert_eq!(ada.email_params, "TYPE=\"work,pref\"");
+        assert_eq!(ada.extra.len(), 3);
+        assert_eq!(property_value(&ada.extra[1]).as_deref(), Some("Wrote the first program, for the Analytical Engine"));
+        assert_eq!(property_name(&ada.extra[2]), "ADR");
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let contacts = parse(CARD).unwrap();
+        for version in [Version::V3, Version::V4] {
+            let again = parse(&export(&contacts, version)).unwrap();
+            assert_eq!(again.len(), 1);
+            let (before, after) = (&contacts[0], &again[0]);
+            assert_eq!(after.name, before.name);
+            assert_eq!(after.name_params, before.name_params);
+            assert_eq!((&after.phone, &after.phone_params), (&before.phone, &before.phone_params));
+            assert_eq!((&after.email, &after.email_params), (&before.email, &before.email_params));
+            assert_eq!(after.extra, before.extra);
+        }
+        assert!(export(&contacts, Version::V4).contains("TEL;TYPE=CELL,VOICE;VALUE=text:+44 20 7946 0018\r\n"));
+        let uri = Contact { name: "Alan".to_string(), phone: "+1-555-0100".to_string(), ..Contact::default() };
+        assert!(export(&[uri], Version::V4).contains("TEL;VALUE=uri:tel:+1-555-0100\r\n"));
+    }
+
+    #[test]
+    fn folds_long_lines_on_char_boundaries() {
+        let contact = Contact { name: "é".repeat(100), ..Contact::default() };
+        let text = export(std::slice::from_ref(&contact), Version::V4);
+        assert!(text.split("\r\n").all(|line| line.len() <= FOLD_WIDTH));
+        assert_eq!(parse(&text).unwrap()[0].name, contact.name);
+    }
+
+    #[test]
+    fn falls_back_to_the_structured_name() {
+        let contacts = parse("BEGIN:VCARD\nN:Lovelace;Ada;King;Lady;\nEND:VCARD\n").unwrap();
+        assert_eq!(contacts[0].name, "Lady Ada King Lovelace");
+        let contacts = parse("BEGIN:VCARD\nTEL:555-0100\nEND:VCARD\n").unwrap();
+        assert_eq!(contacts[0].name, "555-0100");
+    }
+
+    #[test]
+    fn reports_the_line_of_an_error() {
+        let error = |text: &str| parse(text).unwrap_err();
+        assert_eq!(error("FN:Ada\n").line, 1);
+        assert_eq!(error("BEGIN:VCARD\nFN:Ada\nno colon\nEND:VCARD\n").line, 3);
+        assert_eq!(error("BEGIN:VCARD\nFN:Ada\n\nBEGIN:VCARD\n").line, 4);
+        assert_eq!(error("\nBEGIN:VCARD\nFN:Ada\n").line, 2);
+        assert_eq!(error("BEGIN:VCARD\nNOTE:nothing else\nEND:VCARD\n").line, 1);
+    }
+}
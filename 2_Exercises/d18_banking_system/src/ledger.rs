@@ -0,0 +1,617 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::money::Money;
+use crate::products::{self, Interval, Product, StandingOrder};
+
+/// Most days one `advance` runs, about ten years
+pub const MAX_ADVANCE_DAYS: u64 = 3660;
+
+/// Something money can be posted to. Money paid in or out over the counter
+/// comes from or goes to `Cash`, interest is paid from `Interest` and fees
+/// go to `Fees`, so every transaction balances.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Party {
+    Account(usize),
+    Cash,
+    Interest,
+    Fees,
+}
+
+/// One side of a transaction, positive amounts are credited to the party
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Posting {
+    pub party: Party,
+    pub amount: Money,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Deposit,
+    Withdrawal,
+    Transfer,
+    Interest,
+    Fee,
+    StandingOrder,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Transaction {
+    pub id: usize,
+    pub date: NaiveDate,
+    pub kind: Kind,
+    pub description: String,
+    pub postings: Vec<Posting>,
+}
+
+impl Transaction {
+    /// What this transaction did to one party, zero if it wasn't involved.
+    /// A party has at most one posting per transaction.
+    pub fn amount_for(&self, party: Party) -> Money {
+        self.postings.iter().find(|p| p.party == party).map_or(Money::ZERO, |p| p.amount)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Account {
+    pub id: usize,
+    pub name: String,
+    pub opened: NaiveDate,
+    #[serde(default)]
+    pub product: Product,
+}
+
+/// One line of the ledger file
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Entry {
+    Open(Account),
+    Transaction(Transaction),
+    StandingOrder(StandingOrder),
+    CancelOrder { id: usize },
+    /// A standing order couldn't be paid on `date`, kept for the audit trail
+    OrderFailed { id: usize, date: NaiveDate, reason: String },
+    /// The simulated clock was moved forward to `date`, written once
+    /// everything up to that day has been posted
+    Clock { date: NaiveDate },
+}
+
+#[derive(Debug)]
+pub enum BankError {
+    AccountNotFound(usize),
+    InvalidAmount(Money),
+    InsufficientFunds { account: usize, available: Money, amount: Money },
+    SameAccount,
+    OrderNotFound(usize),
+    /// A balance would go past what `Money` can hold
+    Overflow,
+    /// More days than `advance` runs at once
+    AdvanceTooFar(u64),
+    /// A line of the ledger file that can't be read or doesn't balance
+    Corrupt { line: usize, message: String },
+    Io(io::Error),
+}
+
+impl fmt::Display for BankError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BankError::AccountNotFound(id) => write!(f, "No account with id {}", id),
+            BankError::InvalidAmount(amount) => write!(f, "Amount must be more than zero, got {}", amount),
+            BankError::InsufficientFunds { account, available, amount } => {
+                write!(f, "Not enough funds in account {}: available {}, needed {}", account, available, amount)
+            }
+            BankError::SameAccount => write!(f, "Can't transfer to the same account"),
+            BankError::OrderNotFound(id) => write!(f, "No standing order with id {}", id),
+            BankError::Overflow => write!(f, "Amount too large, a balance would overflow"),
+            BankError::AdvanceTooFar(days) => write!(f, "Can't advance by {} days, at most {} at once", days, MAX_ADVANCE_DAYS),
+            BankError::Corrupt { line, message } => write!(f, "Ledger line {}: {}", line, message),
+            BankError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for BankError {}
+
+impl From<io::Error> for BankError {
+    fn from(e: io::Error) -> BankError {
+        BankError::Io(e)
+    }
+}
+
+/// A statement line: the transaction, what it did to the account and the
+/// balance after it
+pub struct StatementLine<'a> {
+    pub transaction: &'a Transaction,
+    pub amount: Money,
+    pub balance: Money,
+}
+
+pub struct Statement<'a> {
+    pub opening: Money,
+    pub lines: Vec<StatementLine<'a>>,
+    pub closing: Money,
+}
+
+/// Accounts and every transaction ever made, kept in an append-only file
+/// with one JSON entry per line. Nothing is ever changed or removed, a
+/// mistake is undone with a new transaction, so the file is a full audit
+/// trail. Balances aren't stored in the file, they are summed from the
+/// postings as it is replayed.
+pub struct Ledger {
+    /// Not set while the file is being replayed
+    path: Option<PathBuf>,
+    accounts: Vec<Account>,
+    transactions: Vec<Transaction>,
+    /// Every party's balance after the last transaction
+    balances: HashMap<Party, Money>,
+    orders: Vec<StandingOrder>,
+    /// Cancelled orders are gone from `orders`, but their ids aren't reused
+    next_order_id: usize,
+    /// Standing orders that failed and the day they were due
+    failed_orders: Vec<(usize, NaiveDate)>,
+    /// Date of the simulated clock, once it has been advanced
+    clock: Option<NaiveDate>,
+}
+
+impl Ledger {
+    /// Replay the ledger file, new entries are appended to it
+    pub fn load(path: &Path) -> Result<Ledger, BankError> {
+        let mut ledger = Ledger {
+            path: None,
+            accounts: Vec::new(),
+            transactions: Vec::new(),
+            balances: HashMap::new(),
+            orders: Vec::new(),
+            next_order_id: 1,
+            failed_orders: Vec::new(),
+            clock: None,
+        };
+
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        for (i, line) in content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let corrupt = |message: String| BankError::Corrupt { line: i + 1, message };
+            let entry: Entry = serde_json::from_str(line).map_err(|e| corrupt(e.to_string()))?;
+            if let Entry::Transaction(t) = &entry {
+                match t.postings.iter().map(|p| p.amount).sum::<Option<Money>>() {
+                    Some(Money::ZERO) => {}
+                    Some(total) => return Err(corrupt(format!("transaction {} doesn't balance, off by {}", t.id, total))),
+                    None => return Err(corrupt(format!("transaction {} overflows", t.id))),
+                }
+                let balances = ledger.balances_after(&t.postings).ok_or_else(|| corrupt(format!("transaction {} overflows a balance", t.id)))?;
+                ledger.balances.extend(balances);
+            }
+            ledger.apply(entry);
+        }
+
+        ledger.path = Some(path.to_path_buf());
+        Ok(ledger)
+    }
+
+    fn apply(&mut self, entry: Entry) {
+        match entry {
+            Entry::Open(account) => self.accounts.push(account),
+            Entry::Transaction(transaction) => self.transactions.push(transaction),
+            Entry::StandingOrder(order) => {
+                self.next_order_id = self.next_order_id.max(order.id + 1);
+                self.orders.push(order);
+            }
+            Entry::CancelOrder { id } => self.orders.retain(|o| o.id != id),
+            Entry::OrderFailed { id, date, .. } => self.failed_orders.push((id, date)),
+            Entry::Clock { date } => self.clock = Some(date),
+        }
+    }
+
+    /// Write the entry to the end of the file (and to disk) before it is
+    /// applied, so what is in memory is never ahead of the file
+    fn append(&mut self, entry: Entry) -> Result<(), BankError> {
+        if let Some(path) = &self.path {
+            let mut line = serde_json::to_string(&entry).map_err(io::Error::other)?;
+            line.push('\n');
+            let mut file: File = OpenOptions::new().create(true).append(true).open(path)?;
+            file.write_all(line.as_bytes())?;
+            file.sync_data()?;
+        }
+        self.apply(entry);
+        Ok(())
+    }
+
+    pub fn accounts(&self) -> &[Account] {
+        &self.accounts
+    }
+
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    pub fn orders(&self) -> &[StandingOrder] {
+        &self.orders
+    }
+
+    /// The simulated date once `advance` has been used, the real one before
+    pub fn today(&self) -> NaiveDate {
+        self.clock.unwrap_or_else(|| Local::now().date_naive())
+    }
+
+    pub fn account(&self, id: usize) -> Result<&Account, BankError> {
+        self.accounts.iter().find(|a| a.id == id).ok_or(BankError::AccountNotFound(id))
+    }
+
+    pub fn open_account(&mut self, name: &str, product: Product) -> Result<&Account, BankError> {
+        let id = self.accounts.iter().map(|a| a.id).max().unwrap_or(0) + 1;
+        let opened = self.today();
+        self.append(Entry::Open(Account { id, name: name.trim().to_string(), opened, product }))?;
+        Ok(self.accounts.last().unwrap())
+    }
+
+    pub fn balance(&self, party: Party) -> Money {
+        self.balances.get(&party).copied().unwrap_or_default()
+    }
+
+    /// Balance at the end of `date`
+    pub fn balance_on(&self, party: Party, date: NaiveDate) -> Result<Money, BankError> {
+        let amounts = self.transactions.iter().filter(|t| t.date <= date).map(|t| t.amount_for(party));
+        amounts.sum::<Option<Money>>().ok_or(BankError::Overflow)
+    }
+
+    /// The balances of the parties in `postings` once they are posted,
+    /// `None` if one doesn't fit or a party is posted to twice
+    fn balances_after(&self, postings: &[Posting]) -> Option<HashMap<Party, Money>> {
+        let mut balances = HashMap::new();
+        for posting in postings {
+            let balance = self.balance(posting.party).checked_add(posting.amount)?;
+            if balances.insert(posting.party, balance).is_some() {
+                return None;
+            }
+        }
+        Some(balances)
+    }
+
+    /// Checked before anything is written, a posting that overflowed
+    /// would be in the file for good
+    fn post(&mut self, date: NaiveDate, kind: Kind, description: &str, postings: Vec<Posting>) -> Result<(), BankError> {
+        debug_assert_eq!(postings.iter().map(|p| p.amount).sum::<Option<Money>>(), Some(Money::ZERO));
+        let balances = self.balances_after(&postings).ok_or(BankError::Overflow)?;
+        let id = self.transactions.len() + 1;
+        let transaction = Transaction { id, date, kind, description: description.to_string(), postings };
+        self.append(Entry::Transaction(transaction))?;
+        self.balances.extend(balances);
+        Ok(())
+    }
+
+    /// Money moved from one party to another, after checking both exist
+    /// and that an account paying out has enough, overdraft included. A
+    /// checking account left below zero is charged its overdraft fee.
+    fn move_money(&mut self, from: Party, to: Party, amount: Money, kind: Kind, description: &str, date: NaiveDate) -> Result<(), BankError> {
+        if amount <= Money::ZERO {
+            return Err(BankError::InvalidAmount(amount));
+        }
+        if from == to {
+            return Err(BankError::SameAccount);
+        }
+        for party in [from, to] {
+            if let Party::Account(id) = party {
+                self.account(id)?;
+            }
+        }
+        if let Party::Account(id) = from {
+            // The overdraft is never negative, so this can only overflow upwards
+            let available = self.balance(from).checked_add(self.account(id)?.product.overdraft()).unwrap_or(Money::MAX);
+            if available < amount {
+                return Err(BankError::InsufficientFunds { account: id, available, amount });
+            }
+        }
+
+        let postings = vec![Posting { party: from, amount: -amount }, Posting { party: to, amount }];
+        self.post(date, kind, description, postings)?;
+
+        if let Party::Account(id) = from
+            && let Product::Checking { overdraft_fee, .. } = self.account(id)?.product
+            && overdraft_fee > Money::ZERO
+            && self.balance(from).is_negative()
+        {
+            self.charge_fee(id, overdraft_fee, "Overdraft fee", date)?;
+        }
+        Ok(())
+    }
+
+    /// Fees may take an account past its overdraft limit
+    fn charge_fee(&mut self, id: usize, fee: Money, description: &str, date: NaiveDate) -> Result<(), BankError> {
+        let postings = vec![Posting { party: Party::Account(id), amount: -fee }, Posting { party: Party::Fees, amount: fee }];
+        self.post(date, Kind::Fee, description, postings)?;
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, id: usize, amount: Money) -> Result<(), BankError> {
+        self.move_money(Party::Cash, Party::Account(id), amount, Kind::Deposit, "Deposit", self.today())
+    }
+
+    pub fn withdraw(&mut self, id: usize, amount: Money) -> Result<(), BankError> {
+        self.move_money(Party::Account(id), Party::Cash, amount, Kind::Withdrawal, "Withdrawal", self.today())
+    }
+
+    pub fn transfer(&mut self, from: usize, to: usize, amount: Money) -> Result<(), BankError> {
+        let description = format!("Transfer from {} to {}", from, to);
+        self.move_money(Party::Account(from), Party::Account(to), amount, Kind::Transfer, &description, self.today())
+    }
+
+    /// Pay `amount` from one account to another on a schedule, the first
+    /// payment on `start`
+    pub fn add_standing_order(&mut self, from: usize, to: usize, amount: Money, interval: Interval, start: NaiveDate) -> Result<&StandingOrder, BankError> {
+        if amount <= Money::ZERO {
+            return Err(BankError::InvalidAmount(amount));
+        }
+        if from == to {
+            return Err(BankError::SameAccount);
+        }
+        self.account(from)?;
+        self.account(to)?;
+
+        let id = self.next_order_id;
+        self.append(Entry::StandingOrder(StandingOrder { id, from, to, amount, interval, start }))?;
+        Ok(self.orders.last().unwrap())
+    }
+
+    pub fn cancel_order(&mut self, id: usize) -> Result<(), BankError> {
+        if !self.orders.iter().any(|o| o.id == id) {
+            return Err(BankError::OrderNotFound(id));
+        }
+        self.append(Entry::CancelOrder { id })
+    }
+
+    /// Whether `description` was already posted to `party` on `date`. The
+    /// clock is only written once `advance` is done, so a run that was cut
+    /// short leaves postings dated after the clock, and they must not be
+    /// posted again when those days are run once more.
+    fn posted_on(&self, date: NaiveDate, party: Party, description: &str) -> bool {
+        let today = self.today();
+        let recent = self.transactions.iter().rev().take_while(|t| t.date >= today);
+        recent.filter(|t| t.date == date).any(|t| t.description == description && t.amount_for(party) != Money::ZERO)
+    }
+
+    /// Move the simulated clock forward a day at a time. Each day runs the
+    /// standing orders due that day, and the last day of a month pays
+    /// savings interest and charges checking fees. Returns what happened.
+    pub fn advance(&mut self, days: u64) -> Result<Vec<String>, BankError> {
+        if days > MAX_ADVANCE_DAYS {
+            return Err(BankError::AdvanceTooFar(days));
+        }
+        let mut events = Vec::new();
+        let mut date = self.today();
+
+        for _ in 0..days {
+            date = date.checked_add_days(Days::new(1)).ok_or(BankError::Io(io::Error::other("date out of range")))?;
+
+            let due: Vec<StandingOrder> = self.orders.iter().filter(|o| o.is_due(date)).cloned().collect();
+            for order in due {
+                let description = format!("Standing order {}: {} to {}", order.id, order.from, order.to);
+                if self.posted_on(date, Party::Account(order.from), &description) || self.failed_orders.contains(&(order.id, date)) {
+                    continue;
+                }
+                match self.move_money(Party::Account(order.from), Party::Account(order.to), order.amount, Kind::StandingOrder, &description, date) {
+                    Ok(()) => events.push(format!("{}  {}, {}", date, description, order.amount)),
+                    Err(e) => {
+                        self.append(Entry::OrderFailed { id: order.id, date, reason: e.to_string() })?;
+                        events.push(format!("{}  Standing order {} failed: {}", date, order.id, e));
+                    }
+                }
+            }
+
+            if products::is_month_end(date) {
+                self.month_end(date, &mut events)?;
+            }
+        }
+        if days > 0 {
+            self.append(Entry::Clock { date })?;
+        }
+        Ok(events)
+    }
+
+    fn month_end(&mut self, date: NaiveDate, events: &mut Vec<String>) -> Result<(), BankError> {
+        let accounts: Vec<Account> = self.accounts.clone();
+
+        for account in accounts {
+            let party = Party::Account(account.id);
+            match account.product {
+                Product::Savings { rate } if !self.posted_on(date, party, "Interest") => {
+                    let start = products::first_of_month(date).max(account.opened);
+                    let days = start.iter_days().take_while(|d| *d <= date);
+                    let balances = days.map(|day| self.balance_on(party, day)).collect::<Result<Vec<_>, _>>()?;
+                    let interest = products::monthly_interest(rate, balances.into_iter());
+                    if interest > Money::ZERO {
+                        let postings = vec![Posting { party: Party::Interest, amount: -interest }, Posting { party, amount: interest }];
+                        self.post(date, Kind::Interest, "Interest", postings)?;
+                        events.push(format!("{}  Interest paid to account {}, {}", date, account.id, interest));
+                    }
+                }
+                Product::Checking { monthly_fee, .. } if monthly_fee > Money::ZERO && !self.posted_on(date, party, "Monthly fee") => {
+                    self.charge_fee(account.id, monthly_fee, "Monthly fee", date)?;
+                    events.push(format!("{}  Monthly fee charged to account {}, {}", date, account.id, monthly_fee));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Transactions of one account between two dates (both included)
+    pub fn statement(&self, id: usize, from: NaiveDate, to: NaiveDate) -> Result<Statement<'_>, BankError> {
+        self.account(id)?;
+        let party = Party::Account(id);
+
+        let opening = self.transactions.iter().filter(|t| t.date < from).map(|t| t.amount_for(party)).sum::<Option<Money>>();
+        let opening = opening.ok_or(BankError::Overflow)?;
+        let mut balance = opening;
+        let mut lines = Vec::new();
+
+        for transaction in self.transactions.iter().filter(|t| t.date >= from && t.date <= to) {
+            if !transaction.postings.iter().any(|p| p.party == party) {
+                continue;
+            }
+            let amount = transaction.amount_for(party);
+            balance = balance.checked_add(amount).ok_or(BankError::Overflow)?;
+            lines.push(StatementLine { transaction, amount, balance });
+        }
+        Ok(Statement { opening, lines, closing: balance })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+
+    /// A ledger file of its own for each test, removed if it is left over
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("bank-{}-{}.jsonl", process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    /// A ledger whose clock is set, so transactions get a known date
+    fn ledger_on(path: &Path, today: &str) -> Ledger {
+        let mut ledger = Ledger::load(path).unwrap();
+        ledger.append(Entry::Clock { date: date(today) }).unwrap();
+        ledger
+    }
+
+    #[test]
+    fn replays_the_file() {
+        let path = temp_path("replay");
+        let mut ledger = ledger_on(&path, "2026-01-10");
+        ledger.open_account("Ada", Product::Basic).unwrap();
+        ledger.open_account("Bob", Product::Basic).unwrap();
+        ledger.deposit(1, Money(10_000)).unwrap();
+        ledger.transfer(1, 2, Money(2_500)).unwrap();
+        ledger.withdraw(2, Money(500)).unwrap();
+
+        let replayed = Ledger::load(&path).unwrap();
+        assert_eq!(replayed.accounts().len(), 2);
+        assert_eq!(replayed.transactions().len(), 3);
+        assert_eq!(replayed.balance(Party::Account(1)), Money(7_500));
+        assert_eq!(replayed.balance(Party::Account(2)), Money(2_000));
+        assert_eq!(replayed.balance(Party::Cash), Money(-9_500));
+        assert_eq!(replayed.today(), date("2026-01-10"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_a_transaction_that_does_not_balance() {
+        let path = temp_path("unbalanced");
+        let mut ledger = ledger_on(&path, "2026-01-10");
+        ledger.open_account("Ada", Product::Basic).unwrap();
+        let line = r#"{"type":"transaction","id":1,"date":"2026-01-10","kind":"deposit","description":"Deposit","postings":[{"party":"cash","amount":-100},{"party":{"account":1},"amount":200}]}"#;
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{}", line).unwrap();
+
+        match Ledger::load(&path) {
+            Err(BankError::Corrupt { line, message }) => {
+                assert_eq!(line, 3);
+                assert!(message.contains("doesn't balance"), "{}", message);
+            }
+            other => panic!("expected a corrupt ledger, got {:?}", other.map(|_| ())),
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn overflow_is_rejected_before_it_is_written() {
+        let path = temp_path("overflow");
+        let mut ledger = ledger_on(&path, "2026-01-10");
+        ledger.open_account("Ada", Product::Basic).unwrap();
+        let huge = Money::parse("90000000000000000").unwrap();
+        ledger.deposit(1, huge).unwrap();
+        let before = fs::read_to_string(&path).unwrap();
+
+        assert!(matches!(ledger.deposit(1, huge), Err(BankError::Overflow)));
+        assert_eq!(fs::read_to_string(&path).unwrap(), before);
+        assert_eq!(Ledger::load(&path).unwrap().balance(Party::Account(1)), huge);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn checks_payments() {
+        let mut ledger = Ledger::load(&temp_path("unsaved")).unwrap();
+        // Nothing is written while there is no path, like during a replay
+        ledger.path = None;
+        ledger.open_account("Ada", Product::Basic).unwrap();
+        ledger.open_account("Bob", Product::Basic).unwrap();
+        ledger.deposit(1, Money(1_000)).unwrap();
+
+        assert!(matches!(ledger.withdraw(1, Money(1_001)), Err(BankError::InsufficientFunds { account: 1, .. })));
+        assert!(matches!(ledger.transfer(1, 1, Money(1)), Err(BankError::SameAccount)));
+        assert!(matches!(ledger.transfer(1, 3, Money(1)), Err(BankError::AccountNotFound(3))));
+        assert!(matches!(ledger.deposit(1, Money::ZERO), Err(BankError::InvalidAmount(_))));
+        assert!(matches!(ledger.deposit(1, Money(-5)), Err(BankError::InvalidAmount(_))));
+        assert_eq!(ledger.balance(Party::Account(1)), Money(1_000));
+        assert_eq!(ledger.transactions().len(), 1);
+    }
+
+    #[test]
+    fn statement_has_running_balances() {
+        let path = temp_path("statement");
+        let mut ledger = ledger_on(&path, "2026-01-10");
+        ledger.open_account("Ada", Product::Basic).unwrap();
+        ledger.deposit(1, Money(5_000)).unwrap();
+        ledger.append(Entry::Clock { date: date("2026-01-20") }).unwrap();
+        ledger.withdraw(1, Money(1_500)).unwrap();
+        ledger.deposit(1, Money(200)).unwrap();
+
+        let statement = ledger.statement(1, date("2026-01-15"), date("2026-01-31")).unwrap();
+        assert_eq!(statement.opening, Money(5_000));
+        let balances: Vec<Money> = statement.lines.iter().map(|l| l.balance).collect();
+        assert_eq!(balances, [Money(3_500), Money(3_700)]);
+        assert_eq!(statement.closing, Money(3_700));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn advance_writes_one_clock_and_resumes_without_posting_twice() {
+        let path = temp_path("advance");
+        let mut ledger = ledger_on(&path, "2026-01-10");
+        ledger.open_account("Ada", Product::Basic).unwrap();
+        ledger.open_account("Bob", Product::Savings { rate: 250 }).unwrap();
+        ledger.deposit(1, Money(10_000)).unwrap();
+        ledger.add_standing_order(1, 2, Money(1_000), Interval::Weeks(1), date("2026-01-12")).unwrap();
+        let before = fs::read_to_string(&path).unwrap();
+
+        // Orders on Jan 12, 19, 26, Feb 2 and 9, interest on Jan 31
+        let events = ledger.advance(30).unwrap();
+        assert_eq!(events.len(), 6);
+        let after = fs::read_to_string(&path).unwrap();
+        let added: Vec<&str> = after[before.len()..].lines().collect();
+        assert_eq!(added.len(), 7);
+        assert!(added[6].contains(r#""type":"clock","date":"2026-02-09""#), "{}", added[6]);
+        let balance = ledger.balance(Party::Account(2));
+
+        // Cut short just before the clock: running the days again posts nothing
+        fs::write(&path, &after[..after.len() - added[6].len() - 1]).unwrap();
+        let mut resumed = Ledger::load(&path).unwrap();
+        assert_eq!(resumed.today(), date("2026-01-10"));
+        assert!(resumed.advance(30).unwrap().is_empty());
+        assert_eq!(resumed.transactions().len(), 7);
+        assert_eq!(resumed.balance(Party::Account(2)), balance);
+        assert_eq!(resumed.today(), date("2026-02-09"));
+
+        assert!(matches!(resumed.advance(MAX_ADVANCE_DAYS + 1), Err(BankError::AdvanceTooFar(_))));
+        assert_eq!(resumed.today(), date("2026-02-09"));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,76 +1,381 @@
+use std::env;
 use std::io::{self, Write};
- 
-#[derive(Debug)]
-struct Account {
-    id: usize,
-    name: String,
-    balance: f64,
-}
+use std::path::Path;
+use std::process;
+
+use chrono::NaiveDate;
+
+mod ledger;
+mod money;
+mod products;
+
+use ledger::{Ledger, MAX_ADVANCE_DAYS, Party};
+use money::Money;
+use products::{Interval, Product, parse_rate};
+
+const LEDGER_FILE: &str = "ledger.jsonl";
 
 fn main() {
-    let mut accounts: Vec<Account> = Vec::new();
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut ledger = match Ledger::load(Path::new(LEDGER_FILE)) {
+        Ok(ledger) => ledger,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", LEDGER_FILE, e);
+            process::exit(1);
+        }
+    };
+
+    // Every change is written to the ledger file as it happens
+    if args.is_empty() {
+        menu(&mut ledger);
+    } else if let Err(e) = run_command(&mut ledger, &args) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn print_usage() {
+    println!("Usage:");
+    println!("  bank                                       interactive menu");
+    println!("  bank open <NAME> [--deposit AMOUNT] [--savings RATE%]");
+    println!("  bank open <NAME> --checking [--overdraft AMOUNT] [--fee AMOUNT] [--overdraft-fee AMOUNT]");
+    println!("  bank accounts");
+    println!("  bank balance <ID>");
+    println!("  bank deposit <ID> <AMOUNT>");
+    println!("  bank withdraw <ID> <AMOUNT>");
+    println!("  bank transfer <FROM> <TO> <AMOUNT>");
+    println!("  bank statement <ID> [--from DATE] [--to DATE]");
+    println!("  bank order <FROM> <TO> <AMOUNT> --every INTERVAL [--start DATE]");
+    println!("  bank orders                                list standing orders");
+    println!("  bank cancel-order <ID>");
+    println!("  bank advance <INTERVAL>                    move the simulated clock forward, {} days at most", MAX_ADVANCE_DAYS);
+    println!("  bank date                                  show the clock");
+    println!("  bank audit                                 check that the ledger balances");
+    println!("AMOUNT: 12.34, DATE: 2026-10-01, INTERVAL: 30d, 2w, 1m");
+    println!("Savings pay interest and checking fees are charged at the end of each month.");
+}
+
+fn parse_id(text: &str) -> Result<usize, String> {
+    text.parse().map_err(|_| format!("Invalid account id '{}'", text))
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").map_err(|_| format!("Invalid date '{}', use YYYY-MM-DD", text))
+}
+
+/// Run one subcommand
+fn run_command(ledger: &mut Ledger, args: &[String]) -> Result<(), String> {
+    let rest = &args[1..];
+    let arg = |i: usize, what: &str| rest.get(i).ok_or(format!("Missing {}", what));
+    let flag = |name: &str| rest.iter().position(|a| a == name).and_then(|i| rest.get(i + 1));
+
+    match args[0].as_str() {
+        "open" => {
+            let mut words = Vec::new();
+            let mut deposit = None;
+            let mut product = Product::Basic;
+            let mut checking = false;
+            let (mut overdraft, mut monthly_fee, mut overdraft_fee) = (Money::ZERO, Money::ZERO, Money::ZERO);
+
+            let mut iter = rest.iter();
+            while let Some(arg) = iter.next() {
+                let mut value = || iter.next().ok_or(format!("{} needs a value", arg));
+                // A negative overdraft would be a minimum balance and a negative fee a payment
+                let mut not_negative = || match Money::parse(value()?)? {
+                    amount if amount.is_negative() => Err(format!("{} can't be negative", arg)),
+                    amount => Ok(amount),
+                };
+                match arg.as_str() {
+                    "--deposit" => deposit = Some(Money::parse(value()?)?),
+                    "--savings" => product = Product::Savings { rate: parse_rate(value()?)? },
+                    "--checking" => checking = true,
+                    "--overdraft" => overdraft = not_negative()?,
+                    "--fee" => monthly_fee = not_negative()?,
+                    "--overdraft-fee" => overdraft_fee = not_negative()?,
+                    flag if flag.starts_with("--") => return Err(format!("Unknown option '{}'", flag)),
+                    word => words.push(word),
+                }
+            }
+            if checking {
+                product = Product::Checking { overdraft, monthly_fee, overdraft_fee };
+            } else if overdraft != Money::ZERO || monthly_fee != Money::ZERO || overdraft_fee != Money::ZERO {
+                return Err("--overdraft and the fees only apply with --checking".to_string());
+            }
+
+            let name = words.join(" ");
+            if name.trim().is_empty() {
+                return Err("Account holder name can't be empty".to_string());
+            }
+            open_account(ledger, &name, product, deposit)
+        }
+        "accounts" | "ls" => {
+            view_accounts(ledger);
+            Ok(())
+        }
+        "balance" => {
+            let id = parse_id(arg(0, "account id")?)?;
+            let account = ledger.account(id).map_err(|e| e.to_string())?;
+            println!("{} balance is {}", account.name, ledger.balance(Party::Account(id)));
+            Ok(())
+        }
+        "deposit" => {
+            let (id, amount) = (parse_id(arg(0, "account id")?)?, Money::parse(arg(1, "amount")?)?);
+            ledger.deposit(id, amount).map_err(|e| e.to_string())?;
+            println!("New balance: {}", ledger.balance(Party::Account(id)));
+            Ok(())
+        }
+        "withdraw" => {
+            let (id, amount) = (parse_id(arg(0, "account id")?)?, Money::parse(arg(1, "amount")?)?);
+            ledger.withdraw(id, amount).map_err(|e| e.to_string())?;
+            println!("New balance: {}", ledger.balance(Party::Account(id)));
+            Ok(())
+        }
+        "transfer" => {
+            let from = parse_id(arg(0, "account to transfer from")?)?;
+            let to = parse_id(arg(1, "account to transfer to")?)?;
+            let amount = Money::parse(arg(2, "amount")?)?;
+            ledger.transfer(from, to, amount).map_err(|e| e.to_string())?;
+            println!("Transferred {} from {} to {}", amount, from, to);
+            Ok(())
+        }
+        "statement" => {
+            let id = parse_id(arg(0, "account id")?)?;
+            let opened = ledger.account(id).map_err(|e| e.to_string())?.opened;
+            let from = flag("--from").map(|d| parse_date(d)).transpose()?.unwrap_or(opened);
+            let to = flag("--to").map(|d| parse_date(d)).transpose()?.unwrap_or(ledger.today());
+            print_statement(ledger, id, from, to)
+        }
+        "order" => {
+            let from = parse_id(arg(0, "account to pay from")?)?;
+            let to = parse_id(arg(1, "account to pay to")?)?;
+            let amount = Money::parse(arg(2, "amount")?)?;
+            let every = flag("--every").ok_or("Missing --every INTERVAL")?;
+            let interval = Interval::parse(every).ok_or(format!("Invalid interval '{}'", every))?;
+            let start = match flag("--start") {
+                Some(date) => parse_date(date)?,
+                None => interval.nth_after(ledger.today(), 1).ok_or("Date out of range")?,
+            };
+            if start <= ledger.today() {
+                return Err(format!("The first payment has to be after today ({})", ledger.today()));
+            }
+            let order = ledger.add_standing_order(from, to, amount, interval, start).map_err(|e| e.to_string())?;
+            println!("Standing order {} created, first payment on {}", order.id, order.start);
+            Ok(())
+        }
+        "orders" => {
+            view_orders(ledger);
+            Ok(())
+        }
+        "cancel-order" => {
+            let id = arg(0, "standing order id")?;
+            let id = id.parse().map_err(|_| format!("Invalid standing order id '{}'", id))?;
+            ledger.cancel_order(id).map_err(|e| e.to_string())?;
+            println!("Standing order {} cancelled", id);
+            Ok(())
+        }
+        "advance" => {
+            let text = arg(0, "interval, like 30d")?;
+            let interval = Interval::parse(text).ok_or(format!("Invalid interval '{}'", text))?;
+            let today = ledger.today();
+            let target = interval.nth_after(today, 1).ok_or("Date out of range")?;
+            for event in ledger.advance((target - today).num_days() as u64).map_err(|e| e.to_string())? {
+                println!("{}", event);
+            }
+            println!("Date is now {}", ledger.today());
+            Ok(())
+        }
+        "date" => {
+            println!("{}", ledger.today());
+            Ok(())
+        }
+        "audit" => audit(ledger),
+        "help" | "--help" | "-h" => {
+            print_usage();
+            Ok(())
+        }
+        other => {
+            print_usage();
+            Err(format!("Unknown command '{}'", other))
+        }
+    }
+}
+
+fn open_account(ledger: &mut Ledger, name: &str, product: Product, deposit: Option<Money>) -> Result<(), String> {
+    let id = ledger.open_account(name, product).map_err(|e| e.to_string())?.id;
+    if let Some(amount) = deposit.filter(|a| *a > Money::ZERO) {
+        ledger.deposit(id, amount).map_err(|e| e.to_string())?;
+    }
+    println!("Account created with ID: {}", id);
+    Ok(())
+}
+
+fn view_accounts(ledger: &Ledger) {
+    if ledger.accounts().is_empty() {
+        println!("No accounts!");
+    }
+    for account in ledger.accounts() {
+        println!(
+            "[{}] {:<20} {:>12}  opened {}, {}",
+            account.id,
+            account.name,
+            ledger.balance(Party::Account(account.id)),
+            account.opened,
+            account.product
+        );
+    }
+}
+
+fn view_orders(ledger: &Ledger) {
+    if ledger.orders().is_empty() {
+        println!("No standing orders!");
+    }
+    for order in ledger.orders() {
+        let next = order.next_on_or_after(ledger.today().succ_opt().unwrap_or(ledger.today()));
+        let next = next.map_or("never".to_string(), |d| d.to_string());
+        println!("[{}] {} from {} to {}, {}, next on {}", order.id, order.amount, order.from, order.to, order.interval, next);
+    }
+}
+
+fn print_statement(ledger: &Ledger, id: usize, from: NaiveDate, to: NaiveDate) -> Result<(), String> {
+    let account = ledger.account(id).map_err(|e| e.to_string())?;
+    let statement = ledger.statement(id, from, to).map_err(|e| e.to_string())?;
+
+    println!("Statement for account {} ({}), {} to {}", id, account.name, from, to);
+    println!("{:<10}  {:>5}  {:<32} {:>12} {:>12}", "Date", "Tx", "Description", "Amount", "Balance");
+    println!("{:<10}  {:>5}  {:<32} {:>12} {:>12}", "", "", "Opening balance", "", statement.opening);
+    for line in &statement.lines {
+        let t = line.transaction;
+        println!("{:<10}  {:>5}  {:<32} {:>12} {:>12}", t.date, t.id, t.description, line.amount, line.balance);
+    }
+    println!("{:<10}  {:>5}  {:<32} {:>12} {:>12}", "", "", "Closing balance", "", statement.closing);
+    Ok(())
+}
 
-    let mut next_id = 1;
+/// Every transaction balances on its own (checked when the ledger is
+/// loaded), so all balances together, the bank's included, must add up to zero
+fn audit(ledger: &Ledger) -> Result<(), String> {
+    let customers = ledger.accounts().iter().map(|a| ledger.balance(Party::Account(a.id))).sum::<Option<Money>>();
+    let customers = customers.ok_or("Customer balances add up to more than can be shown")?;
+    let cash = ledger.balance(Party::Cash);
+    let interest = ledger.balance(Party::Interest);
+    let fees = ledger.balance(Party::Fees);
 
+    println!("{} accounts, {} transactions", ledger.accounts().len(), ledger.transactions().len());
+    println!("Customer balances: {:>12}", customers);
+    println!("Cash paid in:      {:>12}", -cash);
+    println!("Interest paid:     {:>12}", -interest);
+    println!("Fees charged:      {:>12}", fees);
+    match [customers, cash, interest, fees].into_iter().sum::<Option<Money>>() {
+        Some(Money::ZERO) => {}
+        Some(total) => return Err(format!("Ledger is off by {}", total)),
+        None => return Err("Ledger total overflows".to_string()),
+    }
+    for account in ledger.accounts() {
+        let balance = ledger.balance(Party::Account(account.id));
+        // Fees can take an account past its limit, payments can't
+        if let Some(past) = (-account.product.overdraft()).checked_sub(balance).filter(|past| *past > Money::ZERO) {
+            println!("Note: account {} is {} past its overdraft limit", account.id, past);
+        }
+    }
+    println!("Ledger balances.");
+    Ok(())
+}
+
+fn menu(ledger: &mut Ledger) {
     loop {
         println!("\nBanking System:");
         println!("1. Create Account");
         println!("2. View Balance");
         println!("3. Deposit");
         println!("4. Withdraw");
-        println!("5. Exit");
+        println!("5. Transfer");
+        println!("6. Statement");
+        println!("7. List Accounts");
+        println!("8. New Standing Order");
+        println!("9. Advance Clock");
+        println!("10. Exit");
 
-        match get_input("Select your choice: ").as_str() {
+        let result = match get_input("Select your choice: ").as_str() {
             "1" => {
                 let name = get_input("Account holder name: ");
-                let amount = get_input("Initial deposit: ").parse::<f64>().unwrap_or(0.0);
-                accounts.push(Account {id: next_id, name, balance: amount});
-                println!("Account created with ID: {}", next_id);
-                next_id += 1;
+                let deposit = get_input("Initial deposit (empty for none): ");
+                let mut args = vec!["open".to_string(), name];
+                match get_input("Account type (basic/savings/checking, empty for basic): ").as_str() {
+                    "savings" => args.extend(["--savings".to_string(), get_input("Yearly interest rate in %: ")]),
+                    "checking" => {
+                        args.push("--checking".to_string());
+                        for (flag, prompt) in [("--overdraft", "Overdraft limit: "), ("--fee", "Monthly fee: "), ("--overdraft-fee", "Fee per overdrawn payment: ")] {
+                            let value = get_input(prompt);
+                            if !value.is_empty() {
+                                args.extend([flag.to_string(), value]);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                if !deposit.is_empty() {
+                    args.extend(["--deposit".to_string(), deposit]);
+                }
+                run_command(ledger, &args)
             }
             "2" => {
-                let id = get_input("Account Id: ").parse::<usize>().unwrap_or(0);
-                match accounts.iter().find(|acc| acc.id == id) {
-                    Some(acc) => { println!("{} balance is {:.2}", acc.name, acc.balance); },
-                    None => println!("Account not found!")
-                }
+                let id = get_input("Account Id: ");
+                run_command(ledger, &["balance".to_string(), id])
             }
             "3" => {
-                let id = get_input("Account Id: ").parse::<usize>().unwrap_or(0);
-                let amount = get_input("Deposit amount: ").parse::<f64>().unwrap_or(0.0);
-
-                match accounts.iter_mut().find(|acc| acc.id == id) {
-                    Some(acc) => {
-                        acc.balance += amount;
-                        println!("New balance: {}", acc.balance);
-                    },
-                    None => println!("No id found!")
-                }
+                let id = get_input("Account Id: ");
+                let amount = get_input("Deposit amount: ");
+                run_command(ledger, &["deposit".to_string(), id, amount])
             }
             "4" => {
-                let id = get_input("Account Id: ").parse::<usize>().unwrap_or(0);
-                let amount = get_input("Amount to withdraw: ").parse::<f64>().unwrap_or(0.0);
-
-                match accounts.iter_mut().find(|acc| acc.id == id) {
-                    Some(acc) => {
-                        if acc.balance >= amount {
-                            acc.balance -= amount;
-                            println!("New balance: {}", acc.balance);
-                        } else {
-                            println!("Not enough funds!");
-                        }
-                        
-                    },
-                    None => println!("No id found!")
-                }
+                let id = get_input("Account Id: ");
+                let amount = get_input("Amount to withdraw: ");
+                run_command(ledger, &["withdraw".to_string(), id, amount])
             }
             "5" => {
+                let from = get_input("From account Id: ");
+                let to = get_input("To account Id: ");
+                let amount = get_input("Amount to transfer: ");
+                run_command(ledger, &["transfer".to_string(), from, to, amount])
+            }
+            "6" => {
+                let mut args = vec!["statement".to_string(), get_input("Account Id: ")];
+                for (flag, prompt) in [("--from", "From date (YYYY-MM-DD, empty for opening): "), ("--to", "To date (empty for today): ")] {
+                    let date = get_input(prompt);
+                    if !date.is_empty() {
+                        args.extend([flag.to_string(), date]);
+                    }
+                }
+                run_command(ledger, &args)
+            }
+            "7" => {
+                view_accounts(ledger);
+                Ok(())
+            }
+            "8" => {
+                let from = get_input("From account Id: ");
+                let to = get_input("To account Id: ");
+                let amount = get_input("Amount: ");
+                let every = get_input("Every (e.g. 1m, 2w, 30d): ");
+                run_command(ledger, &["order".to_string(), from, to, amount, "--every".to_string(), every])
+            }
+            "9" => {
+                let by = get_input("Advance by (e.g. 30d, 1m): ");
+                run_command(ledger, &["advance".to_string(), by])
+            }
+            "10" => {
                 println!("Good bye");
                 break;
             }
             _ => {
                 println!("Invalid input!");
+                Ok(())
             }
+        };
+
+        if let Err(e) = result {
+            println!("{}", e);
         }
     }
 }
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Neg;
+
+use serde::{Deserialize, Serialize};
+
+/// An amount in minor units (cents). Integers add up exactly, where
+/// 0.1 + 0.2 in f64 doesn't. There is no `+` or `-`, sums are checked
+/// and come back as `None` when they don't fit.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[serde(transparent)]
+pub struct Money(pub i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub const MAX: Money = Money(i64::MAX);
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    /// `i64::MIN` is left out of the range, so any result can be negated
+    fn checked(cents: Option<i64>) -> Option<Money> {
+        cents.filter(|c| *c != i64::MIN).map(Money)
+    }
+
+    pub fn checked_add(self, other: Money) -> Option<Money> {
+        Money::checked(self.0.checked_add(other.0))
+    }
+
+    pub fn checked_sub(self, other: Money) -> Option<Money> {
+        Money::checked(self.0.checked_sub(other.0))
+    }
+
+    /// Parse "12", "12.5" or "-0.99" without going through floating point.
+    /// More than two decimals is an error rather than silently rounded.
+    pub fn parse(text: &str) -> Result<Money, String> {
+        let invalid = || format!("Invalid amount '{}'", text);
+        let trimmed = text.trim();
+        let (negative, digits) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed),
+        };
+
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+        if !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        if fraction.len() > 2 {
+            return Err(format!("Amount '{}' has more than two decimals", text));
+        }
+
+        let whole: i64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| invalid())? };
+        let fraction: i64 = format!("{:0<2}", fraction).parse().map_err(|_| invalid())?;
+        let cents = whole.checked_mul(100).and_then(|c| c.checked_add(fraction)).ok_or_else(invalid)?;
+        Ok(Money(if negative { -cents } else { cents }))
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let text = format!("{}{}.{:02}", sign, self.0.unsigned_abs() / 100, self.0.unsigned_abs() % 100);
+        // Let callers use width and alignment, "{:>10}"
+        f.pad(&text)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+/// `None` if the total doesn't fit
+impl Sum<Money> for Option<Money> {
+    fn sum<I: Iterator<Item = Money>>(mut iter: I) -> Option<Money> {
+        iter.try_fold(Money::ZERO, Money::checked_add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_exact_amounts() {
+        assert_eq!(Money::parse("12"), Ok(Money(1200)));
+        assert_eq!(Money::parse("12.5"), Ok(Money(1250)));
+        assert_eq!(Money::parse(" 0.01 "), Ok(Money(1)));
+        assert_eq!(Money::parse("-0.99"), Ok(Money(-99)));
+        assert_eq!(Money::parse(".5"), Ok(Money(50)));
+        assert_eq!(Money::parse("0.1"), Ok(Money(10)));
+    }
+
+    #[test]
+    fn rejects_invalid_amounts() {
+        for text in ["", "-", ".", "abc", "1.2.3", "1,50", "+1", "1e3", "1.234"] {
+            assert!(Money::parse(text).is_err(), "{:?} should be rejected", text);
+        }
+        // Too many cents for an i64
+        assert!(Money::parse("92233720368547758.08").is_err());
+        assert_eq!(Money::parse("92233720368547758.07"), Ok(Money::MAX));
+    }
+
+    #[test]
+    fn displays_two_decimals() {
+        assert_eq!(Money(123456).to_string(), "1234.56");
+        assert_eq!(Money(-5).to_string(), "-0.05");
+        assert_eq!(Money::ZERO.to_string(), "0.00");
+        assert_eq!(format!("{:>8}", Money(100)), "    1.00");
+    }
+
+    #[test]
+    fn arithmetic_is_checked() {
+        assert_eq!(Money(1).checked_add(Money(2)), Some(Money(3)));
+        assert_eq!(Money(1).checked_sub(Money(2)), Some(Money(-1)));
+        assert_eq!(Money::MAX.checked_add(Money(1)), None);
+        // i64::MIN is out of range, so every result can be negated
+        assert_eq!(Money(-Money::MAX.0).checked_sub(Money(1)), None);
+    }
+
+    #[test]
+    fn sum_is_none_on_overflow() {
+        assert_eq!([Money(1), Money(2), Money(-3)].into_iter().sum::<Option<Money>>(), Some(Money::ZERO));
+        assert_eq!([Money::MAX, Money(1), Money(-1)].into_iter().sum::<Option<Money>>(), None);
+        assert_eq!(std::iter::empty().sum::<Option<Money>>(), Some(Money::ZERO));
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt;
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::money::Money;
+
+/// Interest is worked out with a 365 day year, whatever the year
+const DAYS_PER_YEAR: i128 = 365;
+
+/// What kind of account it is, which decides interest, overdraft and fees
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Product {
+    /// No interest, no overdraft, no fees. Accounts opened before products
+    /// existed are basic.
+    #[default]
+    Basic,
+    /// Interest accrues daily on the end of day balance and is paid at the
+    /// end of the month, so it compounds monthly
+    Savings {
+        /// Yearly rate in basis points, 250 is 2.50%
+        rate: u32,
+    },
+    /// May go below zero down to `-overdraft`. `overdraft_fee` is charged
+    /// for every payment that leaves the account below zero.
+    Checking { overdraft: Money, monthly_fee: Money, overdraft_fee: Money },
+}
+
+impl Product {
+    /// How far below zero the balance may go
+    pub fn overdraft(self) -> Money {
+        match self {
+            Product::Checking { overdraft, .. } => overdraft,
+            _ => Money::ZERO,
+        }
+    }
+}
+
+impl fmt::Display for Product {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Product::Basic => write!(f, "basic"),
+            Product::Savings { rate } => write!(f, "savings {}.{:02}%", rate / 100, rate % 100),
+            Product::Checking { overdraft, monthly_fee, overdraft_fee } => {
+                write!(f, "checking, overdraft {}, fee {}/month, {} per overdrawn payment", overdraft, monthly_fee, overdraft_fee)
+            }
+        }
+    }
+}
+
+/// "2.5" or "2.5%" as basis points
+pub fn parse_rate(text: &str) -> Result<u32, String> {
+    let number = text.trim().trim_end_matches('%');
+    // A percentage with two decimals has the same shape as an amount of money
+    match Money::parse(number) {
+        Ok(Money(bp)) if (0..=10_000).contains(&bp) => Ok(bp as u32),
+        _ => Err(format!("Invalid interest rate '{}', use a percentage like 2.5", text)),
+    }
+}
+
+/// Interest for one month: each day's closing balance accrues
+/// rate / 365, and the sum is rounded to the cent once, when it is paid.
+/// `closing_balances` has the balance at the end of each day.
+pub fn monthly_interest(rate: u32, closing_balances: impl Iterator<Item = Money>) -> Money {
+    // Cents times basis points, so nothing is lost until the final division
+    let accrued: i128 = closing_balances.map(|balance| balance.0.max(0) as i128 * rate as i128).sum();
+    let divisor = 10_000 * DAYS_PER_YEAR;
+    // Round half up
+    Money(((accrued + divisor / 2) / divisor) as i64)
+}
+
+pub fn is_month_end(date: NaiveDate) -> bool {
+    date.succ_opt().is_none_or(|next| next.day() == 1)
+}
+
+pub fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Interval {
+    Days(u32),
+    Weeks(u32),
+    Months(u32),
+}
+
+impl Interval {
+    /// "30d", "2w" or "1m"
+    pub fn parse(text: &str) -> Option<Interval> {
+        let text = text.trim();
+        let (at, unit) = text.char_indices().last()?;
+        let count: u32 = text[..at].parse().ok().filter(|n| *n > 0)?;
+        match unit {
+            'd' => Some(Interval::Days(count)),
+            'w' => Some(Interval::Weeks(count)),
+            'm' => Some(Interval::Months(count)),
+            _ => None,
+        }
+    }
+
+    /// The `n`th date after `start`. Counted from the start every time, so
+    /// monthly on the 31st stays on the last day of short months without
+    /// drifting to the 28th for good.
+    pub fn nth_after(self, start: NaiveDate, n: u32) -> Option<NaiveDate> {
+        match self {
+            Interval::Days(days) => start.checked_add_days(Days::new(days as u64 * n as u64)),
+            Interval::Weeks(weeks) => start.checked_add_days(Days::new(7 * weeks as u64 * n as u64)),
+            Interval::Months(months) => start.checked_add_months(Months::new(months * n)),
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (n, unit) = match self {
+            Interval::Days(n) => (n, "day"),
+            Interval::Weeks(n) => (n, "week"),
+            Interval::Months(n) => (n, "month"),
+        };
+        if *n == 1 { write!(f, "every {}", unit) } else { write!(f, "every {} {}s", n, unit) }
+    }
+}
+
+/// A transfer that repeats on a schedule, run when the clock passes its dates
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StandingOrder {
+    pub id: usize,
+    pub from: usize,
+    pub to: usize,
+    pub amount: Money,
+    pub interval: Interval,
+    /// The first payment date
+    pub start: NaiveDate,
+}
+
+impl StandingOrder {
+    pub fn is_due(&self, date: NaiveDate) -> bool {
+        self.next_on_or_after(date) == Some(date)
+    }
+
+    /// The first payment on or after `date`
+    pub fn next_on_or_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        (0..).map_while(|n| self.interval.nth_after(self.start, n)).find(|d| *d >= date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_rates_as_basis_points() {
+        assert_eq!(parse_rate("2.5"), Ok(250));
+        assert_eq!(parse_rate("2.50%"), Ok(250));
+        assert_eq!(parse_rate("0"), Ok(0));
+        assert_eq!(parse_rate("100"), Ok(10_000));
+        for text in ["-1", "100.01", "2.555", "abc", ""] {
+            assert!(parse_rate(text).is_err(), "{:?} should be rejected", text);
+        }
+    }
+
+    #[test]
+    fn interest_accrues_daily_and_rounds_once() {
+        // 1000.00 at 2.5% for 31 days: 100000 * 0.025 * 31 / 365 = 212.33 cents
+        let balances = std::iter::repeat_n(Money(100_000), 31);
+        assert_eq!(monthly_interest(250, balances), Money(212));
+
+        // Rounding each day would give 2.24, the month as a whole is 2.19
+        let half_up = std::iter::repeat_n(Money(100_000), 30).chain([Money(200_000)]);
+        assert_eq!(monthly_interest(250, half_up), Money(219));
+
+        // Days below zero earn nothing
+        let overdrawn = [Money(-100_000), Money(100_000)].into_iter();
+        assert_eq!(monthly_interest(250, overdrawn), Money(7));
+        assert_eq!(monthly_interest(0, std::iter::repeat_n(Money(100_000), 31)), Money::ZERO);
+    }
+
+    #[test]
+    fn finds_month_ends() {
+        assert!(is_month_end(date("2026-01-31")));
+        assert!(is_month_end(date("2028-02-29")));
+        assert!(is_month_end(date("2027-02-28")));
+        assert!(!is_month_end(date("2028-02-28")));
+        assert_eq!(first_of_month(date("2026-10-18")), date("2026-10-01"));
+    }
+
+    #[test]
+    fn parses_intervals() {
+        assert_eq!(Interval::parse("30d"), Some(Interval::Days(30)));
+        assert_eq!(Interval::parse(" 2w "), Some(Interval::Weeks(2)));
+        assert_eq!(Interval::parse("1m"), Some(Interval::Months(1)));
+        for text in ["", "d", "0d", "-1d", "5y", "5é", "é", "1.5m"] {
+            assert_eq!(Interval::parse(text), None, "{:?} should be rejected", text);
+        }
+        assert_eq!(Interval::Months(1).to_string(), "every month");
+        assert_eq!(Interval::Weeks(2).to_string(), "every 2 weeks");
+    }
+
+    #[test]
+    fn monthly_orders_do_not_drift() {
+        let order = StandingOrder { id: 1, from: 1, to: 2, amount: Money(100), interval: Interval::Months(1), start: date("2026-01-31") };
+        assert_eq!(order.next_on_or_after(date("2026-02-01")), Some(date("2026-02-28")));
+        assert_eq!(order.next_on_or_after(date("2026-03-01")), Some(date("2026-03-31")));
+        assert!(order.is_due(date("2026-04-30")));
+        assert!(!order.is_due(date("2026-01-30")));
+        assert_eq!(order.next_on_or_after(date("2025-12-01")), Some(date("2026-01-31")));
+    }
+}